@@ -141,6 +141,7 @@ pub mod rasters;
 pub mod renders;
 pub mod scanlines;
 pub mod sources;
+pub mod svg;
 pub mod types;
 pub mod utils;
 
@@ -163,6 +164,8 @@ pub use crate::scanlines::*;
 #[doc(hidden)]
 pub use crate::sources::*;
 #[doc(hidden)]
+pub use crate::svg::*;
+#[doc(hidden)]
 pub use crate::types::*;
 
 const POLY_SUBPIXEL_SHIFT: i64 = 8;
@@ -171,7 +171,7 @@ pub trait Pixel {
     let covers: Covers<'a, T> = covers.into();
     assert!(covers.check_len(len as usize));
 
-    for (i, (&color, cover)) in colors.iter().zip(covers.into_iter()).enumerate() {
+    for (i, (&color, cover)) in colors.iter().zip(covers).enumerate() {
       self.copy_or_blend_pix_with_cover((x + i as Position, y), color, cover);
     }
   }
@@ -189,7 +189,7 @@ pub trait Pixel {
     let covers: Covers<'_, T> = covers.into();
     assert!(covers.check_len(len as usize));
 
-    for (i, (&color, cover)) in colors.iter().zip(covers.into_iter()).enumerate() {
+    for (i, (&color, cover)) in colors.iter().zip(covers).enumerate() {
       self.copy_or_blend_pix_with_cover((x, y + i as Position), color, cover);
     }
   }
@@ -100,6 +100,10 @@ impl<Area: PixelLike> RasterizerCell<Area> {
     self.cells.clear(); // Not sure if this should be cleared
   }
 
+  /// Drop the sorted cells, keeping the cells, so more can be added
+  pub fn unsort(&mut self) {
+    self.sorted_y.clear();
+  }
   /// Return total number of cells
   pub fn total_cells(&self) -> usize {
     self.cells.len()
@@ -121,7 +125,7 @@ impl<Area: PixelLike> RasterizerCell<Area> {
     }
     // Sort by the x value
     for i in 0..self.sorted_y.len() {
-      self.sorted_y[i].sort_by(|a, b| (a.x).cmp(&b.x));
+      self.sorted_y[i].sort_by_key(|a| a.x);
     }
   }
   /// Returns the cells of a specific y row
@@ -26,6 +26,7 @@ use crate::POLY_SUBPIXEL_SHIFT;
 use crate::Pixel;
 use crate::RendererPrimatives;
 use crate::VertexSource;
+use crate::curves::ConvCurve;
 use crate::paths::PathCommand;
 
 #[derive(Debug, Copy, Clone, PartialEq, Default)]
@@ -74,13 +75,16 @@ where
     }
   }
   /// Add a path and render
+  ///
+  /// Curves in the path are flattened with [`ConvCurve`]
   pub fn add_path<VS: VertexSource>(&mut self, path: &VS) {
-    for v in path.xconvert().iter() {
+    for v in ConvCurve::new(path).vertices() {
       match v.cmd {
         PathCommand::MoveTo => self.move_to_d(v.x, v.y),
         PathCommand::LineTo => self.line_to_d(v.x, v.y),
         PathCommand::Close => self.close(),
         PathCommand::Stop => unimplemented!("stop encountered"),
+        PathCommand::Curve3 | PathCommand::Curve4 => unreachable!("curves are flattened"),
       }
     }
   }
@@ -35,6 +35,7 @@
 
 use crate::DrawVars;
 use crate::LineParameters;
+use crate::curves::ConvCurve;
 use crate::paths::PathCommand;
 use crate::paths::Vertex;
 use crate::stroke::LineJoin;
@@ -82,13 +83,16 @@ where
     self.round_cap = on;
   }
   /// Add and Render a path
+  ///
+  /// Curves in the path are flattened with [`ConvCurve`]
   pub fn add_path<VS: VertexSource>(&mut self, path: &VS) {
-    for v in path.xconvert().iter() {
+    for v in ConvCurve::new(path).vertices() {
       match v.cmd {
         PathCommand::MoveTo => self.move_to_d(v.x, v.y),
         PathCommand::LineTo => self.line_to_d(v.x, v.y),
         PathCommand::Close => self.close_path(),
         PathCommand::Stop => unimplemented!("stop encountered"),
+        PathCommand::Curve3 | PathCommand::Curve4 => unreachable!("curves are flattened"),
       }
     }
     self.render(false);
@@ -11,9 +11,11 @@ use crate::Position;
 
 use crate::cell::RasterizerCell;
 use crate::clip::Clip;
+use crate::curves::ConvCurve;
 use crate::paths::PathCommand;
 use crate::paths::Vertex;
-use crate::scanlines::ScanlineU8;
+use crate::scanlines::Scanline;
+use crate::scanlines::ScanlineHitTest;
 
 //use crate::Rasterize;
 use crate::VertexSource;
@@ -66,6 +68,8 @@ pub struct RasterizerScanline<P = I56F8, Area = I48F16> {
   scan_y: Position,
   /// Filling Rule for Polygons
   filling_rule: FillingRule,
+  /// Close open sub-paths automatically
+  auto_close: bool,
   /// Gamma Corection Values
   gamma: Vec<u64>,
 }
@@ -87,6 +91,7 @@ impl<P: PixelLike, Area> RasterizerScanline<P, Area> {
       y0: P::ZERO,
       scan_y: 0,
       filling_rule: FillingRule::NonZero,
+      auto_close: true,
       gamma: (0..256).collect(),
     }
   }
@@ -102,18 +107,47 @@ impl<P: PixelLike, Area: PixelLike> RasterizerScanline<P, Area> {
   }
   /// Add a Path
   ///
-  /// Walks the path from the VertexSource and rasterizes it
+  /// Walks the path from the VertexSource and rasterizes it. If the previous
+  ///   shapes were already rendered, they are removed first.
+  ///
+  /// Curves in the path are flattened with [`ConvCurve`]
   pub fn add_path<VS: VertexSource>(&mut self, path: &VS) {
     //path.rewind();
     if !self.outline.sorted_y.is_empty() {
       self.reset();
     }
-    for seg in path.xconvert() {
+    self.add_vertices(path);
+  }
+  /// Add a Path to the current shapes
+  ///
+  /// Like [`add_path`](Self::add_path), but shapes already in the rasterizer
+  ///   are kept, even if they were rendered, so several paths make one fill
+  ///   under the filling rule
+  pub fn add_path_accumulate<VS: VertexSource>(&mut self, path: &VS) {
+    self.outline.unsort();
+    self.add_vertices(path);
+  }
+  /// Add many Paths as a single fill
+  ///
+  /// Previous shapes are removed if they were already rendered, the paths
+  ///   are then accumulated, see [`add_path_accumulate`](Self::add_path_accumulate)
+  pub fn add_paths<VS: VertexSource>(&mut self, paths: &[VS]) {
+    if !self.outline.sorted_y.is_empty() {
+      self.reset();
+    }
+    for path in paths {
+      self.add_vertices(path);
+    }
+  }
+  /// Rasterize the vertices of a path, flattening curves
+  fn add_vertices<VS: VertexSource>(&mut self, path: &VS) {
+    for seg in ConvCurve::new(path).vertices() {
       match seg.cmd {
         PathCommand::LineTo => self.line_to(seg.x, seg.y),
         PathCommand::MoveTo => self.move_to(seg.x, seg.y),
         PathCommand::Close => self.close_polygon(),
         PathCommand::Stop => unimplemented!("stop encountered"),
+        PathCommand::Curve3 | PathCommand::Curve4 => unreachable!("curves are flattened"),
       }
     }
   }
@@ -124,7 +158,9 @@ impl<P: PixelLike, Area: PixelLike> RasterizerScanline<P, Area> {
   /// scan_y value to the minimum y value and return if any cells
   /// are present
   pub(crate) fn rewind_scanlines(&mut self) -> bool {
-    self.close_polygon();
+    if self.auto_close {
+      self.close_polygon();
+    }
     self.outline.sort_cells();
     if self.outline.total_cells() == 0 {
       false
@@ -139,7 +175,7 @@ impl<P: PixelLike, Area: PixelLike> RasterizerScanline<P, Area> {
   /// For individual y rows adding any to the input Scanline
   ///
   /// Returns true if data exists in the input Scanline
-  pub(crate) fn sweep_scanline(&mut self, sl: &mut ScanlineU8) -> bool {
+  pub(crate) fn sweep_scanline<S: Scanline>(&mut self, sl: &mut S) -> bool {
     loop {
       if self.scan_y < 0 {
         self.scan_y += 1;
@@ -199,6 +235,17 @@ impl<P: PixelLike, Area: PixelLike> RasterizerScanline<P, Area> {
     self.scan_y += 1;
     true
   }
+  /// Check if the pixel at (x,y) is covered by the rasterized shapes
+  ///
+  /// Any non-zero coverage, after the filling rule and gamma, is a hit
+  pub fn hit_test(&mut self, x: Position, y: Position) -> bool {
+    if !self.rewind_scanlines() || y < self.outline.min_y.max(0) || y > self.outline.max_y {
+      return false;
+    }
+    self.scan_y = y;
+    let mut sl = ScanlineHitTest::new(x);
+    self.sweep_scanline(&mut sl) && sl.hit()
+  }
   /// Return minimum x value from the RasterizerCell
   pub fn min_x(&self) -> Position {
     self.outline.min_x
@@ -239,6 +286,29 @@ impl<P: PixelLike, Area: PixelLike> RasterizerScanline<P, Area> {
     new.gamma(gfunc);
     new
   }
+  /// Set the Filling Rule
+  ///
+  /// Used for the shapes in the rasterizer when they are rendered,
+  ///   NonZero by default
+  pub fn filling_rule(&mut self, filling_rule: FillingRule) {
+    self.filling_rule = filling_rule;
+  }
+  /// Get the Filling Rule
+  pub fn get_filling_rule(&self) -> FillingRule {
+    self.filling_rule
+  }
+  /// Close open sub-paths automatically, true
+  ///
+  /// Each sub-path is closed by a line back to its start at the next
+  ///   "move to" and before rendering. Without it, open sub-paths are left
+  ///   open and should only be used when the path closes itself.
+  pub fn auto_close(&mut self, auto_close: bool) {
+    self.auto_close = auto_close;
+  }
+  /// Get if open sub-paths are closed automatically
+  pub fn get_auto_close(&self) -> bool {
+    self.auto_close
+  }
   /// Set Clip Box
   pub fn clip_box(&mut self, x1: f64, y1: f64, x2: f64, y2: f64) {
     self.clipper.clip_box(
@@ -250,8 +320,12 @@ impl<P: PixelLike, Area: PixelLike> RasterizerScanline<P, Area> {
   }
   /// Move to point (x,y)
   ///
-  /// Sets point as the initial point
+  /// Sets point as the initial point, closing the previous polygon if
+  ///   [`auto_close`](Self::auto_close) is set
   pub fn move_to(&mut self, x: f64, y: f64) {
+    if self.auto_close {
+      self.close_polygon();
+    }
     self.x0 = RasConvInt::upscale(x);
     self.y0 = RasConvInt::upscale(y);
     self.clipper.move_to(self.x0, self.y0);
@@ -0,0 +1,347 @@
+//! Hairline Renderer
+//!
+//! Hairlines are lines exactly one device pixel wide, whatever the
+//!   transform. Paths are transformed and flattened, then each segment is
+//!   drawn directly as anti-aliased spans, without going through
+//!   [`Stroke`](../../sources/stroke/struct.Stroke.html) and the scanline
+//!   rasterizer. The coverage is that of a one pixel wide band around the
+//!   segment, so lines keep the same weight under rotation. Pixels shared
+//!   by consecutive segments add up their coverage, so polylines and
+//!   flattened curves have the same weight as a single segment.
+//!
+//! # Example
+//!
+//!     use agg::prelude::*;
+//!     use agg::{RendererHairline, Transform};
+//!
+//!     let pix = Pixfmt::<Rgb8>::create(100, 100);
+//!     let mut ren_base = agg::RenderingBase::new(pix);
+//!     ren_base.clear(Rgb8::WHITE);
+//!
+//!     // Grid in data space, zoomed by 10
+//!     let mut grid = agg::Path::new();
+//!     for i in 0..10 {
+//!       grid.move_to(f64::from(i), 0.0);
+//!       grid.line_to(f64::from(i), 10.0);
+//!     }
+//!     let mtx = Transform::scaling(10.0, 10.0);
+//!
+//!     let mut ren = RendererHairline::new_black(&mut ren_base)
+//!       .with_transform(mtx)
+//!       .with_snap(true);
+//!     ren.add_path(&grid);
+//!     //ren_base.to_file("hairline.png").unwrap();
+
+use crate::Color;
+use crate::NamedColor;
+use crate::Pixel;
+use crate::RenderingBase;
+use crate::Transform;
+use crate::U8;
+use crate::VertexSource;
+use crate::color::Rgba8;
+use crate::curves::flatten;
+use crate::paths::PathCommand;
+use crate::paths::Vertex;
+
+/// Hairline Renderer, lines one device pixel wide with Anti-Aliasing
+#[derive(Debug)]
+pub struct RendererHairline<'a, T, C = Rgba8> {
+  ren: &'a mut RenderingBase<T>,
+  color: C,
+  trans: Transform,
+  snap: bool,
+  /// Points of the current sub-path, in device space
+  points: Vec<(f64, f64)>,
+  /// Coverage of pixels at the ends of segments, (x, y, cover)
+  ends: Vec<(i64, i64, f64)>,
+}
+
+impl<'a, T> RendererHairline<'a, T, Rgba8>
+where
+  T: Pixel,
+{
+  /// Create Hairline Renderer with a [`RenderingBase`](../base/struct.RenderingBase.html)
+  pub fn new_black(ren: &'a mut RenderingBase<T>) -> Self {
+    Self::new(ren, Rgba8::BLACK)
+  }
+}
+
+impl<'a, T, C> RendererHairline<'a, T, C>
+where
+  T: Pixel,
+  C: Color,
+{
+  /// Create Hairline Renderer with a [`RenderingBase`](../base/struct.RenderingBase.html)
+  pub fn new(ren: &'a mut RenderingBase<T>, color: C) -> Self {
+    Self {
+      ren,
+      color,
+      trans: Transform::new(),
+      snap: false,
+      points: vec![],
+      ends: vec![],
+    }
+  }
+  /// Set transform applied to vertices before drawing
+  #[must_use]
+  pub fn with_transform(mut self, trans: Transform) -> Self {
+    self.trans = trans;
+    self
+  }
+  /// Snap transformed vertices to pixel centers
+  ///
+  /// Horizontal and vertical lines then cover a single row or column
+  ///   of pixels at full intensity. Only the vertices of the path are
+  ///   snapped, curves are flattened from the snapped end points and
+  ///   stay smooth.
+  #[must_use]
+  pub fn with_snap(mut self, snap: bool) -> Self {
+    self.snap = snap;
+    self
+  }
+  /// Set the current Color
+  pub fn color(&mut self, color: C) {
+    self.color = color;
+  }
+  /// Get the transform
+  pub fn get_transform(&self) -> Transform {
+    self.trans
+  }
+  /// Get if vertices are snapped to pixel centers
+  pub fn get_snap(&self) -> bool {
+    self.snap
+  }
+
+  fn snapped(&self, x: f64, y: f64) -> (f64, f64) {
+    if self.snap {
+      (x.floor() + 0.5, y.floor() + 0.5)
+    } else {
+      (x, y)
+    }
+  }
+  /// Draw a path as hairlines
+  ///
+  /// Curves are flattened after the transform, so in device space
+  pub fn add_path<VS: VertexSource>(&mut self, path: &VS) {
+    let (trans, snap) = (self.trans, self.snap);
+    // Control points left before the end point of a curve
+    let mut ctrl = 0;
+    let device = path.vertices().map(move |v| {
+      let (x, y) = trans.transform(v.x, v.y);
+      let on_path = match v.cmd {
+        _ if ctrl > 0 => {
+          ctrl -= 1;
+          ctrl == 0
+        }
+        PathCommand::Curve3 => {
+          ctrl = 1;
+          false
+        }
+        PathCommand::Curve4 => {
+          ctrl = 2;
+          false
+        }
+        PathCommand::MoveTo | PathCommand::LineTo => true,
+        PathCommand::Close | PathCommand::Stop => false,
+      };
+      if snap && on_path {
+        Vertex::new(x.floor() + 0.5, y.floor() + 0.5, v.cmd)
+      } else {
+        Vertex::new(x, y, v.cmd)
+      }
+    });
+    let mut points = std::mem::take(&mut self.points);
+    points.clear();
+    for v in flatten(Box::new(device), 1.0) {
+      match v.cmd {
+        PathCommand::MoveTo => {
+          self.sub_path(&points, false);
+          points.clear();
+          points.push((v.x, v.y));
+        }
+        PathCommand::LineTo => points.push((v.x, v.y)),
+        PathCommand::Close => {
+          self.sub_path(&points, true);
+          // Lines after a close start from the first point
+          points.truncate(1);
+        }
+        PathCommand::Stop => break,
+        PathCommand::Curve3 | PathCommand::Curve4 => unreachable!("curves are flattened"),
+      }
+    }
+    self.sub_path(&points, false);
+    self.points = points;
+  }
+  /// Draw a line from (`x1`,`y1`) to (`x2`,`y2`) as a hairline
+  pub fn line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64) {
+    let (x1, y1) = self.trans.transform(x1, y1);
+    let (x2, y2) = self.trans.transform(x2, y2);
+    let p1 = self.snapped(x1, y1);
+    let p2 = self.snapped(x2, y2);
+    self.sub_path(&[p1, p2], false);
+  }
+
+  /// Draw the segments of a sub-path in device space
+  ///
+  /// Open sub-paths are extended by half a pixel at both ends, so that end
+  ///   points cover their own pixel. Closed sub-paths are not extended.
+  fn sub_path(&mut self, points: &[(f64, f64)], closed: bool) {
+    let n = points.len();
+    if n < 2 {
+      return;
+    }
+    for i in 0..n - 1 {
+      let first = !closed && i == 0;
+      let last = !closed && i == n - 2;
+      self.segment(points[i], points[i + 1], first, last);
+    }
+    if closed {
+      self.segment(points[n - 1], points[0], false, false);
+    }
+    self.flush_ends();
+  }
+
+  /// Draw a segment in device space
+  ///
+  /// Walk along the major axis one pixel at a time. In each step the band
+  ///   is `len / major` pixels wide along the minor axis, which keeps the
+  ///   same coverage per length for all angles. Pixels in the first and
+  ///   last columns are kept to be added to those of the next segment.
+  fn segment(&mut self, (x1, y1): (f64, f64), (x2, y2): (f64, f64), extend1: bool, extend2: bool) {
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    if dx == 0.0 && dy == 0.0 || !(dx.is_finite() && dy.is_finite()) {
+      return;
+    }
+    let (xmin, xmax, ymin, ymax) = self.ren.limits();
+    let (ren, ends, color) = (&mut *self.ren, &mut self.ends, self.color);
+    let mut covers = [U8::new(0); 3];
+    if dx.abs() >= dy.abs() {
+      let band = Band::new((x1, y1), (x2, y2), extend1, extend2);
+      band.spans((xmin, xmax), (ymin, ymax), |x, y, c, end| {
+        if end {
+          ends.extend(c.iter().enumerate().map(|(k, &c)| (x, y + k as i64, c)));
+        } else {
+          let covers = to_covers(c, &mut covers);
+          ren.blend_solid_vspan(x, y, covers.len() as i64, color, covers);
+        }
+      });
+    } else {
+      let band = Band::new((y1, x1), (y2, x2), extend1, extend2);
+      band.spans((ymin, ymax), (xmin, xmax), |y, x, c, end| {
+        if end {
+          ends.extend(c.iter().enumerate().map(|(k, &c)| (x + k as i64, y, c)));
+        } else {
+          let covers = to_covers(c, &mut covers);
+          ren.blend_solid_hspan(x, y, covers.len() as i64, color, covers);
+        }
+      });
+    }
+  }
+
+  /// Blend the pixels at the ends of segments, adding up shared pixels
+  fn flush_ends(&mut self) {
+    let mut ends = std::mem::take(&mut self.ends);
+    ends.sort_unstable_by_key(|&(x, y, _)| (y, x));
+    let (mut covers, mut row) = (vec![], vec![]);
+    let mut i = 0;
+    while i < ends.len() {
+      // Run of consecutive pixels on a row
+      let (x0, y0, _) = ends[i];
+      covers.clear();
+      while i < ends.len() && ends[i].1 == y0 && ends[i].0 <= x0 + covers.len() as i64 {
+        let k = (ends[i].0 - x0) as usize;
+        if k == covers.len() {
+          covers.push(0.0);
+        }
+        covers[k] += ends[i].2;
+        i += 1;
+      }
+      row.clear();
+      row.extend(covers.iter().map(|&c| cover(c)));
+      self.ren.blend_solid_hspan(x0, y0, row.len() as i64, self.color, &row);
+    }
+    ends.clear();
+    self.ends = ends;
+  }
+}
+
+fn cover(c: f64) -> U8 {
+  U8::new((c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+fn to_covers<'a>(c: &[f64], out: &'a mut [U8; 3]) -> &'a [U8] {
+  for (o, &c) in out.iter_mut().zip(c) {
+    *o = cover(c);
+  }
+  &out[..c.len()]
+}
+
+/// One pixel wide band around a segment from (`a1`,`b1`) to (`a2`,`b2`)
+///
+/// `a` is the major axis
+struct Band {
+  a1: f64,
+  b1: f64,
+  a2: f64,
+  /// Slope, change in `b` per unit of `a`
+  k: f64,
+  /// Half of the extent of the band along `b`
+  half: f64,
+}
+
+impl Band {
+  /// Band of a segment, extended by half a pixel at the chosen ends
+  fn new((a1, b1): (f64, f64), (a2, b2): (f64, f64), extend1: bool, extend2: bool) -> Self {
+    let ((a1, b1, extend1), (a2, b2, extend2)) = if a1 <= a2 {
+      ((a1, b1, extend1), (a2, b2, extend2))
+    } else {
+      ((a2, b2, extend2), (a1, b1, extend1))
+    };
+    let (da, db) = (a2 - a1, b2 - b1);
+    let k = db / da;
+    let half = 0.5 * (da * da + db * db).sqrt() / da;
+    let e1 = if extend1 { 0.5 } else { 0.0 };
+    let e2 = if extend2 { 0.5 } else { 0.0 };
+    Self {
+      a1: a1 - e1,
+      b1: b1 - e1 * k,
+      a2: a2 + e2,
+      k,
+      half,
+    }
+  }
+  /// Coverage of the band
+  ///
+  /// For each pixel step along `a` within `alim`, `f` receives the major
+  ///   position, the first minor position, the covers along the minor axis,
+  ///   clipped to `blim`, and if the step is the first or last one.
+  fn spans<F>(&self, alim: (i64, i64), blim: (i64, i64), mut f: F)
+  where
+    F: FnMut(i64, i64, &[f64], bool),
+  {
+    let Self { a1, b1, a2, k, half } = *self;
+    let first = a1.floor() as i64;
+    let last = (a2.ceil() as i64) - 1;
+    let mut covers = [0.0; 3];
+    for i in first.max(alim.0)..=last.min(alim.1) {
+      let lo = a1.max(i as f64);
+      let hi = a2.min((i + 1) as f64);
+      let w = hi - lo;
+      if w <= 0.0 {
+        continue;
+      }
+      let bc = b1 + k * (0.5 * (lo + hi) - a1);
+      let (blo, bhi) = (bc - half, bc + half);
+      let j0 = (blo.floor() as i64).max(blim.0);
+      let j1 = ((bhi.ceil() as i64) - 1).min(blim.1).min(j0 + 2);
+      if j0 > j1 {
+        continue;
+      }
+      for j in j0..=j1 {
+        covers[(j - j0) as usize] = w * (bhi.min((j + 1) as f64) - blo.max(j as f64));
+      }
+      f(i, j0, &covers[..=(j1 - j0) as usize], i == first || i == last);
+    }
+  }
+}
@@ -1,9 +1,11 @@
 mod base;
+mod hairline;
 mod outline_aa;
 mod primitives;
 mod scanline;
 
 pub use base::*;
+pub use hairline::*;
 pub use outline_aa::*;
 pub use primitives::*;
 pub use scanline::*;
@@ -31,6 +31,7 @@ use crate::Pixel;
 use crate::Render;
 use crate::Source;
 use crate::VertexSource;
+use crate::{FillingRule, PathStorage, Stroke, Transform};
 
 pub(crate) const LINE_MAX_LENGTH: i64 = 1 << (POLY_SUBPIXEL_SHIFT + 10);
 
@@ -119,6 +120,9 @@ impl RenderData {
   pub(crate) fn scanline(&self) -> &ScanlineU8 {
     &self.sl
   }
+  pub(crate) fn scanline_mut(&mut self) -> &mut ScanlineU8 {
+    &mut self.sl
+  }
 }
 
 impl<T, C> Render for RenderingScanlineAASolid<'_, T, C>
@@ -283,6 +287,55 @@ where
   }
 }
 
+/// Render all paths of a [`PathStorage`] with their attributes
+///
+/// Each path is filled then stroked, after applying the transform. Strokes
+///   are computed before the transform, so the pen is transformed with the
+///   path.
+pub fn render_path_storage<REN>(ras: &mut RasterizerScanline, ren: &mut REN, storage: &PathStorage, mtx: &Transform)
+where
+  REN: Render,
+{
+  let filling_rule = ras.get_filling_rule();
+  for p in &storage.paths {
+    if let Some(fill) = p.attr.fill_color() {
+      ras.reset();
+      ras.filling_rule(p.attr.fill_rule);
+      ras.add_path(&(&p.path).transformed(*mtx));
+      ren.color(fill);
+      render_scanlines(ras, ren);
+    }
+    if let Some(stroke_color) = p.attr.stroke_color()
+      && p.attr.stroke_width > 0.0
+    {
+      let mut stroke = Stroke::new(&p.path);
+      stroke.width(p.attr.stroke_width);
+      stroke.transform(*mtx);
+      ras.reset();
+      ras.filling_rule(FillingRule::NonZero);
+      ras.add_path(&stroke);
+      ren.color(stroke_color);
+      render_scanlines(ras, ren);
+    }
+  }
+  ras.filling_rule(filling_rule);
+}
+
+/// Index of the top-most path covering the pixel at (x,y)
+///
+/// Paths are tested in reverse, as drawn in order by [`render_all_paths`],
+///   and the rasterizer is reset for each of them
+pub fn hit_test_all_paths<VS>(ras: &mut RasterizerScanline, paths: &[VS], x: Position, y: Position) -> Option<usize>
+where
+  VS: VertexSource,
+{
+  paths.iter().rposition(|path| {
+    ras.reset();
+    ras.add_path(path);
+    ras.hit_test(x, y)
+  })
+}
+
 pub(crate) struct BresenhamInterpolator<P> {
   /// First point, x position
   pub x1: Position,
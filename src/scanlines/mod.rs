@@ -1,5 +1,116 @@
+pub mod sbool;
+pub mod scanline_hit_test;
+pub mod scanline_p8;
 pub mod scanline_u8;
+pub use sbool::*;
+pub use scanline_hit_test::*;
+pub use scanline_p8::*;
 pub use scanline_u8::*;
 
-// pub mod scanline_p8;
-// pub use scanline_p8::*;
+use crate::{Position, U8};
+
+/// Scanline container, filled one row at a time by the
+/// [`RasterizerScanline`](crate::RasterizerScanline)
+pub trait Scanline {
+  /// Reset values and clear spans, with the x range of the next rows
+  fn reset(&mut self, min_x: Position, max_x: Position);
+  /// Clear spans before starting a new row
+  fn reset_spans(&mut self);
+  /// Add a single pixel with a cover value
+  fn add_cell(&mut self, x: Position, cover: u64);
+  /// Add `len` pixels sharing a cover value
+  fn add_span(&mut self, x: Position, len: Position, cover: u64);
+  /// Set the row (y) of the current spans
+  fn finalize(&mut self, y: Position);
+  /// Total number of spans
+  fn num_spans(&self) -> usize;
+  /// Current row
+  fn y(&self) -> Position;
+  /// Visit the spans in order, as starting x position and cover values
+  fn for_each_span<F: FnMut(Position, SpanCover<'_>)>(&self, f: F);
+}
+
+/// Cover values of a span, see [`Scanline::for_each_span`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpanCover<'a> {
+  /// One cover value per pixel
+  Covers(&'a [U8]),
+  /// A single cover value shared by `len` pixels
+  Solid(Position, U8),
+}
+
+impl SpanCover<'_> {
+  /// Number of pixels
+  pub fn len(&self) -> Position {
+    match self {
+      SpanCover::Covers(c) => c.len() as Position,
+      SpanCover::Solid(len, _) => *len,
+    }
+  }
+  /// If the span has no pixels
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+}
+
+impl Scanline for ScanlineU8 {
+  fn reset(&mut self, min_x: Position, max_x: Position) {
+    ScanlineU8::reset(self, min_x, max_x)
+  }
+  fn reset_spans(&mut self) {
+    ScanlineU8::reset_spans(self)
+  }
+  fn add_cell(&mut self, x: Position, cover: u64) {
+    ScanlineU8::add_cell(self, x, cover)
+  }
+  fn add_span(&mut self, x: Position, len: Position, cover: u64) {
+    ScanlineU8::add_span(self, x, len, cover)
+  }
+  fn finalize(&mut self, y: Position) {
+    ScanlineU8::finalize(self, y)
+  }
+  fn num_spans(&self) -> usize {
+    ScanlineU8::num_spans(self)
+  }
+  fn y(&self) -> Position {
+    self.y
+  }
+  fn for_each_span<F: FnMut(Position, SpanCover<'_>)>(&self, mut f: F) {
+    for span in &self.spans {
+      f(span.x, SpanCover::Covers(&span.covers));
+    }
+  }
+}
+
+impl Scanline for ScanlineP8 {
+  fn reset(&mut self, min_x: Position, max_x: Position) {
+    ScanlineP8::reset(self, min_x as i32, max_x as i32)
+  }
+  fn reset_spans(&mut self) {
+    ScanlineP8::reset_spans(self)
+  }
+  fn add_cell(&mut self, x: Position, cover: u64) {
+    ScanlineP8::add_cell(self, x as i32, cover)
+  }
+  fn add_span(&mut self, x: Position, len: Position, cover: u64) {
+    ScanlineP8::add_span(self, x as i32, len as u32, cover)
+  }
+  fn finalize(&mut self, y: Position) {
+    ScanlineP8::finalize(self, y as i32)
+  }
+  fn num_spans(&self) -> usize {
+    ScanlineP8::num_spans(self)
+  }
+  fn y(&self) -> Position {
+    Position::from(ScanlineP8::y(self))
+  }
+  fn for_each_span<F: FnMut(Position, SpanCover<'_>)>(&self, mut f: F) {
+    for span in self.spans() {
+      let x = Position::from(span.x);
+      match &span.covers {
+        SpanCovers::Single(c) => f(x, SpanCover::Solid(Position::from(span.len.unsigned_abs()), *c)),
+        SpanCovers::Slice(c) => f(x, SpanCover::Covers(c)),
+      }
+    }
+  }
+}
@@ -0,0 +1,273 @@
+//! Scanline Boolean Algebra
+//!
+//! Combine two rasterized shapes at the coverage level, without going back
+//! to the vector data. Each row of both rasterizers is swept into its own
+//! scanline, the cover values are merged span by span and the result is
+//! handed straight to a [`Render`].
+//!
+//! ```
+//! use agg::prelude::*;
+//!
+//! let pix = agg::Pixfmt::<agg::Rgb8>::create(100, 100);
+//! let mut ren_base = agg::RenderingBase::new(pix);
+//! ren_base.clear(agg::Rgb8::WHITE);
+//! let mut ren = agg::RenderingScanlineAASolid::new(&mut ren_base, agg::Rgb8::BLACK);
+//!
+//! let mut ras1 = agg::RasterizerScanline::new();
+//! ras1.add_path(&agg::Ellipse::new(40.0, 50.0, 30.0, 30.0, 64));
+//! let mut ras2 = agg::RasterizerScanline::new();
+//! ras2.add_path(&agg::Ellipse::new(60.0, 50.0, 30.0, 30.0, 64));
+//!
+//! let mut sl1 = agg::ScanlineU8::new();
+//! let mut sl2 = agg::ScanlineP8::new();
+//! agg::sbool_combine_shapes(
+//!   agg::SboolOp::XorSaddle,
+//!   &mut ras1,
+//!   &mut ras2,
+//!   &mut sl1,
+//!   &mut sl2,
+//!   &mut ren,
+//! );
+//! ```
+
+use crate::RasterizerScanline;
+use crate::Render;
+use crate::RenderData;
+use crate::scanlines::{Scanline, ScanlineU8, SpanCover};
+use crate::{Position, U8};
+
+const COVER_SHIFT: u32 = 8;
+const COVER_MASK: u32 = (1 << COVER_SHIFT) - 1;
+const COVER_FULL: u32 = COVER_MASK * COVER_MASK;
+
+/// Boolean Operation on Scanlines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SboolOp {
+  /// Union, `a + b - a * b`
+  Or,
+  /// Intersection, `a * b`
+  And,
+  /// Exclusive or, `a + b` folded back when greater than full cover
+  XorLinear,
+  /// Exclusive or, `1 - ((1 - a + a * b) * (1 - b + a * b))`
+  XorSaddle,
+  /// Exclusive or, `|a - b|`
+  XorAbsDiff,
+  /// Difference, `a * (1 - b)`
+  AMinusB,
+  /// Difference, `b * (1 - a)`
+  BMinusA,
+}
+
+impl SboolOp {
+  /// Combine two cover values of the same pixel
+  fn combine(self, a: u32, b: u32) -> u32 {
+    let full = |c: u32| if c == COVER_FULL { COVER_MASK } else { c >> COVER_SHIFT };
+    match self {
+      SboolOp::Or => full(COVER_FULL - (COVER_MASK - a) * (COVER_MASK - b)),
+      SboolOp::And => full(a * b),
+      SboolOp::XorLinear => {
+        let c = a + b;
+        if c > COVER_MASK { COVER_MASK + COVER_MASK - c } else { c }
+      }
+      SboolOp::XorSaddle => {
+        let k = a * b;
+        if k == COVER_FULL {
+          return 0;
+        }
+        let a = (COVER_FULL + k).saturating_sub(a << COVER_SHIFT) >> COVER_SHIFT;
+        let b = (COVER_FULL + k).saturating_sub(b << COVER_SHIFT) >> COVER_SHIFT;
+        COVER_MASK - ((a * b) >> COVER_SHIFT)
+      }
+      SboolOp::XorAbsDiff => a.abs_diff(b),
+      SboolOp::AMinusB => full(a * (COVER_MASK - b)),
+      SboolOp::BMinusA => full(b * (COVER_MASK - a)),
+    }
+  }
+  /// If pixels covered only by the first shape are kept
+  fn keeps_a(self) -> bool {
+    !matches!(self, SboolOp::And | SboolOp::BMinusA)
+  }
+  /// If pixels covered only by the second shape are kept
+  fn keeps_b(self) -> bool {
+    !matches!(self, SboolOp::And | SboolOp::AMinusB)
+  }
+}
+
+/// Span of a [`Row`], `len` pixels from `x`
+#[derive(Debug, Clone, Copy)]
+struct RowSpan {
+  x: Position,
+  len: Position,
+  cover: RowCover,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RowCover {
+  /// Same cover for all pixels
+  Solid(U8),
+  /// Covers start at this index of [`Row::covers`]
+  At(usize),
+}
+
+/// Spans of a single row, buffers are kept from one row to the next
+#[derive(Debug, Default)]
+struct Row {
+  spans: Vec<RowSpan>,
+  covers: Vec<U8>,
+}
+
+impl Row {
+  fn clear(&mut self) {
+    self.spans.clear();
+    self.covers.clear();
+  }
+  fn read<S: Scanline>(&mut self, sl: &S) {
+    self.clear();
+    sl.for_each_span(|x, c| {
+      let len = c.len();
+      let cover = match c {
+        SpanCover::Solid(_, c) => RowCover::Solid(c),
+        SpanCover::Covers(c) => {
+          self.covers.extend_from_slice(c);
+          RowCover::At(self.covers.len() - c.len())
+        }
+      };
+      self.spans.push(RowSpan { x, len, cover });
+    });
+  }
+  /// Cover of a span at `x`
+  fn cover(&self, s: &RowSpan, x: Position) -> u32 {
+    match s.cover {
+      RowCover::Solid(c) => u32::from(c.0),
+      RowCover::At(i) => u32::from(self.covers[i + (x - s.x) as usize].0),
+    }
+  }
+}
+
+/// Merge the spans of two rows into the output scanline
+///
+/// Either row may be empty, in which case the other is only kept if
+///   the operation allows it. Runs where both spans are solid are added as
+///   a single span.
+fn combine_row(op: SboolOp, a: &Row, b: &Row, out: &mut ScanlineU8) {
+  let end = |s: &RowSpan| s.x + s.len;
+  let (mut ia, mut ib) = (0, 0);
+  let mut x = Position::MIN;
+  loop {
+    while ia < a.spans.len() && end(&a.spans[ia]) <= x {
+      ia += 1;
+    }
+    while ib < b.spans.len() && end(&b.spans[ib]) <= x {
+      ib += 1;
+    }
+    let (sa, sb) = (a.spans.get(ia), b.spans.get(ib));
+    let next = match (sa, sb) {
+      (None, None) => break,
+      (Some(sa), None) => sa.x,
+      (None, Some(sb)) => sb.x,
+      (Some(sa), Some(sb)) => sa.x.min(sb.x),
+    };
+    x = x.max(next);
+    let in_a = sa.filter(|s| s.x <= x);
+    let in_b = sb.filter(|s| s.x <= x);
+    // Extent over which the pixels are covered by the same spans
+    let mut stop = Position::MAX;
+    for s in [sa, sb].into_iter().flatten() {
+      stop = stop.min(if s.x <= x { end(s) } else { s.x });
+    }
+    let keep = match (in_a, in_b) {
+      (Some(_), Some(_)) => true,
+      (Some(_), None) => op.keeps_a(),
+      (None, Some(_)) => op.keeps_b(),
+      (None, None) => false,
+    };
+    if keep {
+      let cover = |px: Position| match (in_a.map(|s| a.cover(s, px)), in_b.map(|s| b.cover(s, px))) {
+        (Some(ca), Some(cb)) => op.combine(ca, cb),
+        (Some(c), None) | (None, Some(c)) => c,
+        (None, None) => 0,
+      };
+      let solid = |s: Option<&RowSpan>| s.is_none_or(|s| matches!(s.cover, RowCover::Solid(_)));
+      if solid(in_a) && solid(in_b) {
+        let c = cover(x);
+        if c > 0 {
+          out.add_span(x, stop - x, u64::from(c));
+        }
+      } else {
+        for px in x..stop {
+          let c = cover(px);
+          if c > 0 {
+            out.add_cell(px, u64::from(c));
+          }
+        }
+      }
+    }
+    x = stop;
+  }
+}
+
+/// Combine two rasterized shapes and render the result
+///
+/// Both rasterizers are swept row by row into `sl1` and `sl2`, which may be
+///   any [`Scanline`] such as [`ScanlineU8`] or
+///   [`ScanlineP8`](crate::ScanlineP8). Rows present in only one of the
+///   shapes are passed through or dropped depending on `op`.
+pub fn sbool_combine_shapes<S1, S2, R>(
+  op: SboolOp,
+  ras1: &mut RasterizerScanline,
+  ras2: &mut RasterizerScanline,
+  sl1: &mut S1,
+  sl2: &mut S2,
+  ren: &mut R,
+) where
+  S1: Scanline,
+  S2: Scanline,
+  R: Render,
+{
+  let has1 = ras1.rewind_scanlines();
+  let has2 = ras2.rewind_scanlines();
+  let min_x = match (has1, has2) {
+    (true, true) => ras1.min_x().min(ras2.min_x()),
+    (true, false) => ras1.min_x(),
+    (false, true) => ras2.min_x(),
+    (false, false) => return,
+  };
+  if has1 {
+    sl1.reset(ras1.min_x(), ras1.max_x());
+  }
+  if has2 {
+    sl2.reset(ras2.min_x(), ras2.max_x());
+  }
+  let mut data = RenderData::new();
+  ren.prepare();
+
+  let (mut row1, mut row2) = (Row::default(), Row::default());
+  let mut more1 = has1 && ras1.sweep_scanline(sl1);
+  let mut more2 = has2 && ras2.sweep_scanline(sl2);
+  while more1 || more2 {
+    let y1 = if more1 { sl1.y() } else { Position::MAX };
+    let y2 = if more2 { sl2.y() } else { Position::MAX };
+    let y = y1.min(y2);
+    if y1 == y {
+      row1.read(sl1);
+      more1 = ras1.sweep_scanline(sl1);
+    } else {
+      row1.clear();
+    }
+    if y2 == y {
+      row2.read(sl2);
+      more2 = ras2.sweep_scanline(sl2);
+    } else {
+      row2.clear();
+    }
+
+    let out = data.scanline_mut();
+    out.reset(min_x, 0);
+    combine_row(op, &row1, &row2, out);
+    if out.num_spans() > 0 {
+      out.finalize(y);
+      ren.render(&data);
+    }
+  }
+}
@@ -0,0 +1,56 @@
+//! Hit Test Scanline
+
+use crate::Position;
+
+use super::{Scanline, SpanCover};
+
+/// Scanline that only records if a single x position is covered
+///
+/// Used by [`RasterizerScanline::hit_test`](crate::RasterizerScanline::hit_test)
+#[derive(Debug, Default)]
+pub struct ScanlineHitTest {
+  /// Position being tested
+  x: Position,
+  /// Current row
+  y: Position,
+  /// Position is covered
+  hit: bool,
+}
+
+impl ScanlineHitTest {
+  /// Create a new hit test for position x
+  pub fn new(x: Position) -> Self {
+    Self { x, y: 0, hit: false }
+  }
+  /// Check if the position was covered
+  pub fn hit(&self) -> bool {
+    self.hit
+  }
+}
+
+impl Scanline for ScanlineHitTest {
+  fn reset(&mut self, _min_x: Position, _max_x: Position) {}
+  fn reset_spans(&mut self) {}
+  fn add_cell(&mut self, x: Position, _cover: u64) {
+    if x == self.x {
+      self.hit = true;
+    }
+  }
+  fn add_span(&mut self, x: Position, len: Position, _cover: u64) {
+    if self.x >= x && self.x < x + len {
+      self.hit = true;
+    }
+  }
+  fn finalize(&mut self, y: Position) {
+    self.y = y;
+  }
+  /// Always a single span, so that sweeping stops after one row
+  fn num_spans(&self) -> usize {
+    1
+  }
+  fn y(&self) -> Position {
+    self.y
+  }
+  /// No cover values are kept
+  fn for_each_span<F: FnMut(Position, SpanCover<'_>)>(&self, _f: F) {}
+}
@@ -0,0 +1,503 @@
+//! Polygon Boolean Operations
+//!
+//! Union, intersection, difference and exclusive-or of two filled shapes,
+//! with vector output.
+//!
+//! Each operand is any [`VertexSource`], filled with its own
+//! [`FillingRule`]; curves are flattened first and open sub-paths are
+//! closed. Operands may have any number of contours, including holes and
+//! self-intersections.
+//!
+//! The result is a [`Path`] of closed contours with the filled region on the
+//! left of each edge: outer contours are [`PathOrientation::CounterClockwise`]
+//! and holes are [`PathOrientation::Clockwise`], as reported by
+//! [`preceive_polygon_orientation`](crate::preceive_polygon_orientation).
+//! The result renders the same with either filling rule.
+//!
+//! Edges are compared for intersections with a sweep along y, and the
+//! winding of each piece is found from the edges in its horizontal band.
+//! The cost grows with the number of edges and of pairs of edges sharing
+//! rows, close to O(E log E) for map-like data with short edges. Operands
+//! made of many long edges spanning the same rows still approach O(E²).
+//!
+//! # Example
+//!
+//!     use agg::{BoolOp, FillingRule};
+//!
+//!     let mut region = agg::Path::new();
+//!     region.move_to(0.0, 0.0);
+//!     region.line_to(100.0, 0.0);
+//!     region.line_to(100.0, 100.0);
+//!     region.line_to(0.0, 100.0);
+//!     region.close_polygon();
+//!
+//!     let hole = agg::Ellipse::new(50.0, 50.0, 20.0, 20.0, 32);
+//!
+//!     // Cut the hole out of the region, then stroke the outline
+//!     let cut = agg::polygon_bool(&region, FillingRule::NonZero, &hole, FillingRule::NonZero, BoolOp::Difference);
+//!     assert_eq!(agg::split(&cut.vertices).len(), 2);
+//!     let stroke = agg::Stroke::new(cut);
+
+use crate::FillingRule;
+use crate::VertexSource;
+use crate::paths::{Edge, Path, edges, filled, winding};
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Boolean Operation between two shapes
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BoolOp {
+  /// Area in either shape, A or B
+  Union,
+  /// Area in both shapes, A and B
+  Intersection,
+  /// Area in the first shape but not the second, A minus B
+  Difference,
+  /// Area in exactly one of the shapes, A xor B
+  Xor,
+}
+
+impl BoolOp {
+  fn apply(&self, a: bool, b: bool) -> bool {
+    match self {
+      BoolOp::Union => a || b,
+      BoolOp::Intersection => a && b,
+      BoolOp::Difference => a && !b,
+      BoolOp::Xor => a != b,
+    }
+  }
+}
+
+/// Combine two shapes with a Boolean Operation
+///
+/// `rule_a` and `rule_b` are the Filling Rules of the shapes `a` and `b`
+pub fn polygon_bool<A, B>(a: &A, rule_a: FillingRule, b: &B, rule_b: FillingRule, op: BoolOp) -> Path
+where
+  A: VertexSource,
+  B: VertexSource,
+{
+  let edges = [edges(a), edges(b)];
+  let rules = [rule_a, rule_b];
+
+  // Tolerances relative to the size of the input
+  let scale = edges
+    .iter()
+    .flatten()
+    .flat_map(|e| [e.0.abs(), e.1.abs(), e.2.abs(), e.3.abs()])
+    .fold(1.0, f64::max);
+  let eps = scale * 1e-10;
+
+  let mut points = PointPool::new(eps);
+  let segments = split_edges(&edges, &mut points, eps);
+  let bands = [Bands::new(&edges[0]), Bands::new(&edges[1])];
+
+  // Keep segments where the result changes, with the result on the left
+  let mut out: Vec<(usize, usize)> = vec![];
+  for &(i, j) in &segments {
+    let (x1, y1) = points.pts[i];
+    let (x2, y2) = points.pts[j];
+    let len = (x2 - x1).hypot(y2 - y1);
+    let d = (scale * 1e-7).min(len * 1e-3) / len;
+    let (nx, ny) = (-(y2 - y1) * d, (x2 - x1) * d);
+    let (mx, my) = ((x1 + x2) / 2.0, (y1 + y2) / 2.0);
+    let inside = |x: f64, y: f64| {
+      let a = filled(bands[0].winding(x, y), rules[0]);
+      let b = filled(bands[1].winding(x, y), rules[1]);
+      op.apply(a, b)
+    };
+    match (inside(mx + nx, my + ny), inside(mx - nx, my - ny)) {
+      (true, false) => out.push((i, j)),
+      (false, true) => out.push((j, i)),
+      _ => {}
+    }
+  }
+
+  let mut path = Path::new();
+  for contour in link(&out, &points.pts) {
+    let contour = remove_collinear(contour, eps);
+    if contour.len() < 3 {
+      continue;
+    }
+    path.move_to(contour[0].0, contour[0].1);
+    for &(x, y) in &contour[1..] {
+      path.line_to(x, y);
+    }
+    path.close_polygon();
+  }
+  path
+}
+
+/// Edges sorted into horizontal bands, for winding numbers
+///
+/// Only the edges of the band containing a point can cross its row
+struct Bands {
+  /// Top of the first band
+  y0: f64,
+  /// Height of each band
+  h: f64,
+  /// Edges overlapping each band
+  bands: Vec<Vec<Edge>>,
+}
+
+impl Bands {
+  fn new(edges: &[Edge]) -> Self {
+    let n = (edges.len() / 4).clamp(1, 4096);
+    let (y0, y1) = edges.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), e| {
+      (lo.min(e.1.min(e.3)), hi.max(e.1.max(e.3)))
+    });
+    let mut out = Self {
+      y0,
+      h: ((y1 - y0) / n as f64).max(f64::MIN_POSITIVE),
+      bands: vec![vec![]; n],
+    };
+    for e in edges {
+      for b in out.band(e.1.min(e.3))..=out.band(e.1.max(e.3)) {
+        out.bands[b].push(*e);
+      }
+    }
+    out
+  }
+  fn band(&self, y: f64) -> usize {
+    let b = ((y - self.y0) / self.h).floor();
+    if b.is_nan() {
+      0
+    } else {
+      (b.max(0.0) as usize).min(self.bands.len() - 1)
+    }
+  }
+  /// Winding number of the edges around (x,y)
+  fn winding(&self, x: f64, y: f64) -> i32 {
+    winding(&self.bands[self.band(y)], x, y)
+  }
+}
+
+/// Unique points, points closer than `eps` are merged
+struct PointPool {
+  pts: Vec<(f64, f64)>,
+  grid: HashMap<(i64, i64), Vec<usize>>,
+  eps: f64,
+}
+
+impl PointPool {
+  fn new(eps: f64) -> Self {
+    Self {
+      pts: vec![],
+      grid: HashMap::new(),
+      eps,
+    }
+  }
+  fn insert(&mut self, x: f64, y: f64) -> usize {
+    let (gx, gy) = ((x / self.eps).floor() as i64, (y / self.eps).floor() as i64);
+    for cx in gx - 1..=gx + 1 {
+      for cy in gy - 1..=gy + 1 {
+        for &i in self.grid.get(&(cx, cy)).into_iter().flatten() {
+          let (px, py) = self.pts[i];
+          if (px - x).abs() <= self.eps && (py - y).abs() <= self.eps {
+            return i;
+          }
+        }
+      }
+    }
+    self.pts.push((x, y));
+    self.grid.entry((gx, gy)).or_default().push(self.pts.len() - 1);
+    self.pts.len() - 1
+  }
+}
+
+/// Pairs of edges with overlapping bounding boxes, `(i, j)` with `i < j`
+///
+/// Edges are swept by their lowest y, and each is compared only to the
+///   edges still active there, those reaching down to it
+fn overlapping(edges: &[Edge], eps: f64) -> Vec<(usize, usize)> {
+  let ymin = |e: &Edge| e.1.min(e.3);
+  let ymax = |e: &Edge| e.1.max(e.3);
+  let mut order: Vec<usize> = (0..edges.len()).collect();
+  order.sort_by(|&i, &j| ymin(&edges[i]).total_cmp(&ymin(&edges[j])));
+  let mut active: Vec<usize> = vec![];
+  let mut pairs = vec![];
+  for i in order {
+    let (ax1, ay1, ax2, ay2) = edges[i];
+    let y = ay1.min(ay2);
+    active.retain(|&j| ymax(&edges[j]) + eps >= y);
+    for &j in &active {
+      let (bx1, _, bx2, _) = edges[j];
+      if ax1.max(ax2) + eps >= bx1.min(bx2) && bx1.max(bx2) + eps >= ax1.min(ax2) {
+        pairs.push((i.min(j), i.max(j)));
+      }
+    }
+    active.push(i);
+  }
+  // Same order as comparing all pairs, so points are created in that order
+  pairs.sort_unstable();
+  pairs
+}
+
+/// Split all edges where they cross or touch other edges
+///
+/// Returns the unique, undirected segments as pairs of point indices
+fn split_edges(edges: &[Vec<Edge>; 2], points: &mut PointPool, eps: f64) -> Vec<(usize, usize)> {
+  let all: Vec<Edge> = edges.iter().flatten().copied().collect();
+  // Positions along each edge where it is split, with the point there
+  let mut splits: Vec<Vec<(f64, usize)>> = all
+    .iter()
+    .map(|&(x1, y1, x2, y2)| vec![(0.0, points.insert(x1, y1)), (1.0, points.insert(x2, y2))])
+    .collect();
+  for (i, j) in overlapping(&all, eps) {
+    let (ax1, ay1, ax2, ay2) = all[i];
+    let (bx1, by1, bx2, by2) = all[j];
+    let (dax, day) = (ax2 - ax1, ay2 - ay1);
+    let (dbx, dby) = (bx2 - bx1, by2 - by1);
+    let (la, lb) = (dax.hypot(day), dbx.hypot(dby));
+    let d = dax * dby - day * dbx;
+    if d.abs() > 1e-12 * la * lb {
+      // Crossing lines
+      let t = ((bx1 - ax1) * dby - (by1 - ay1) * dbx) / d;
+      let u = ((bx1 - ax1) * day - (by1 - ay1) * dax) / d;
+      let (ta, tb) = (eps / la, eps / lb);
+      if t < -ta || t > 1.0 + ta || u < -tb || u > 1.0 + tb {
+        continue;
+      }
+      // Snap to end points, so touching edges share them
+      let p = if t <= ta {
+        splits[i][0].1
+      } else if t >= 1.0 - ta {
+        splits[i][1].1
+      } else if u <= tb {
+        splits[j][0].1
+      } else if u >= 1.0 - tb {
+        splits[j][1].1
+      } else {
+        points.insert(ax1 + t * dax, ay1 + t * day)
+      };
+      splits[i].push((t.clamp(0.0, 1.0), p));
+      splits[j].push((u.clamp(0.0, 1.0), p));
+    } else if ((bx1 - ax1) * day - (by1 - ay1) * dax).abs() <= eps * la {
+      // Collinear, split each at the end points of the other
+      for (e, o) in [(i, j), (j, i)] {
+        let (x1, y1, x2, y2) = all[e];
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let l2 = dx * dx + dy * dy;
+        for k in 0..2 {
+          let (px, py) = if k == 0 {
+            (all[o].0, all[o].1)
+          } else {
+            (all[o].2, all[o].3)
+          };
+          let t = ((px - x1) * dx + (py - y1) * dy) / l2;
+          if t > 0.0 && t < 1.0 {
+            let p = splits[o][k].1;
+            splits[e].push((t, p));
+          }
+        }
+      }
+    }
+  }
+  let mut segments = HashMap::new();
+  for s in &mut splits {
+    s.sort_by(|a, b| a.0.total_cmp(&b.0));
+    for w in s.windows(2) {
+      let (i, j) = (w[0].1, w[1].1);
+      if i != j {
+        segments.entry((i.min(j), i.max(j))).or_insert((i, j));
+      }
+    }
+  }
+  let mut segments: Vec<_> = segments.into_values().collect();
+  segments.sort_unstable();
+  segments
+}
+
+/// Link directed segments into closed contours
+///
+/// At each point the contour continues along the first segment clockwise
+/// from the one it arrived on, which keeps touching contours apart
+fn link(segments: &[(usize, usize)], pts: &[(f64, f64)]) -> Vec<Vec<(f64, f64)>> {
+  let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+  for (k, &(i, _)) in segments.iter().enumerate() {
+    outgoing.entry(i).or_default().push(k);
+  }
+  let angle = |i: usize, j: usize| (pts[j].1 - pts[i].1).atan2(pts[j].0 - pts[i].0);
+  let mut used = vec![false; segments.len()];
+  let mut contours = vec![];
+  for start in 0..segments.len() {
+    if used[start] {
+      continue;
+    }
+    let mut contour = vec![];
+    let mut k = start;
+    loop {
+      used[k] = true;
+      let (i, j) = segments[k];
+      contour.push(pts[i]);
+      let back = angle(j, i);
+      let next = outgoing[&j]
+        .iter()
+        .filter(|&&n| !used[n] || n == start)
+        .min_by(|&&n1, &&n2| {
+          let a1 = (back - angle(j, segments[n1].1)).rem_euclid(2.0 * PI);
+          let a2 = (back - angle(j, segments[n2].1)).rem_euclid(2.0 * PI);
+          a1.total_cmp(&a2)
+        });
+      match next {
+        Some(&n) if n != start => k = n,
+        _ => break,
+      }
+    }
+    contours.push(contour);
+  }
+  contours
+}
+
+/// Remove points on a straight line between their neighbors
+fn remove_collinear(mut pts: Vec<(f64, f64)>, eps: f64) -> Vec<(f64, f64)> {
+  let mut k = 0;
+  while pts.len() > 2 && k < pts.len() {
+    let n = pts.len();
+    let (x0, y0) = pts[(k + n - 1) % n];
+    let (x1, y1) = pts[k];
+    let (x2, y2) = pts[(k + 1) % n];
+    let (dx, dy) = (x2 - x0, y2 - y0);
+    let len = dx.hypot(dy);
+    let dist = ((x1 - x0) * dy - (y1 - y0) * dx).abs() / len.max(f64::MIN_POSITIVE);
+    let forward = (x1 - x0) * dx + (y1 - y0) * dy > 0.0 && (x2 - x1) * dx + (y2 - y1) * dy > 0.0;
+    if dist <= eps && forward {
+      pts.remove(k);
+      k = k.saturating_sub(1);
+    } else {
+      k += 1;
+    }
+  }
+  pts
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::paths::{PathOrientation, preceive_polygon_orientation, split};
+
+  fn rect(path: &mut Path, x1: f64, y1: f64, x2: f64, y2: f64) {
+    path.move_to(x1, y1);
+    path.line_to(x2, y1);
+    path.line_to(x2, y2);
+    path.line_to(x1, y2);
+    path.close_polygon();
+  }
+  /// Signed area, positive for counter-clockwise contours
+  fn area(path: &Path) -> f64 {
+    let mut area = 0.0;
+    for (s, e) in split(&path.vertices) {
+      let v = &path.vertices[s..=e];
+      for k in 0..v.len() {
+        let (p, q) = (v[k], v[(k + 1) % v.len()]);
+        area += p.x * q.y - q.x * p.y;
+      }
+    }
+    area / 2.0
+  }
+  fn orientations(path: &Path) -> Vec<PathOrientation> {
+    split(&path.vertices)
+      .into_iter()
+      .map(|(s, e)| preceive_polygon_orientation(&path.vertices[s..=e]))
+      .collect()
+  }
+
+  #[test]
+  fn test_overlapping_squares() {
+    let mut a = Path::new();
+    rect(&mut a, 0.0, 0.0, 2.0, 2.0);
+    let mut b = Path::new();
+    rect(&mut b, 1.0, 1.0, 3.0, 3.0);
+    let nz = FillingRule::NonZero;
+    let run = |op| polygon_bool(&a, nz, &b, nz, op);
+    let p = run(BoolOp::Union);
+    assert!((area(&p) - 7.0).abs() < 1e-9);
+    assert_eq!(p.vertices.len(), 9);
+    assert!((area(&run(BoolOp::Intersection)) - 1.0).abs() < 1e-9);
+    assert!((area(&run(BoolOp::Difference)) - 3.0).abs() < 1e-9);
+    let p = run(BoolOp::Xor);
+    assert!((area(&p) - 6.0).abs() < 1e-9);
+    assert!(orientations(&p).iter().all(|o| *o == PathOrientation::CounterClockwise));
+  }
+
+  #[test]
+  fn test_holes_and_rules() {
+    // Square with a hole, both counter-clockwise so only even-odd has a hole
+    let mut a = Path::new();
+    rect(&mut a, 0.0, 0.0, 4.0, 4.0);
+    rect(&mut a, 1.0, 1.0, 3.0, 3.0);
+    let mut b = Path::new();
+    rect(&mut b, 2.0, -1.0, 6.0, 5.0);
+    let (nz, eo) = (FillingRule::NonZero, FillingRule::EvenOdd);
+
+    let p = polygon_bool(&a, eo, &b, nz, BoolOp::Difference);
+    assert!((area(&p) - 6.0).abs() < 1e-9);
+    let p = polygon_bool(&a, nz, &b, nz, BoolOp::Difference);
+    assert!((area(&p) - 8.0).abs() < 1e-9);
+
+    // Union keeps part of the hole, as a clockwise contour
+    let mut c = Path::new();
+    rect(&mut c, -1.0, 0.0, 2.0, 4.0);
+    let p = polygon_bool(&a, eo, &c, nz, BoolOp::Union);
+    assert!((area(&p) - (20.0 - 2.0)).abs() < 1e-9);
+    let mut o = orientations(&p);
+    o.sort_by_key(|o| *o == PathOrientation::Clockwise);
+    assert_eq!(o, vec![PathOrientation::CounterClockwise, PathOrientation::Clockwise]);
+
+    // Shared edges and touching shapes
+    let mut d = Path::new();
+    rect(&mut d, 4.0, 0.0, 5.0, 4.0);
+    let p = polygon_bool(&a, eo, &d, nz, BoolOp::Union);
+    assert!((area(&p) - 16.0).abs() < 1e-9);
+    assert_eq!(split(&p.vertices).len(), 2);
+    let p = polygon_bool(&a, eo, &d, nz, BoolOp::Intersection);
+    assert!(p.vertices.is_empty());
+    let p = polygon_bool(&a, eo, &a, eo, BoolOp::Xor);
+    assert!(p.vertices.is_empty());
+  }
+
+  #[test]
+  fn test_self_intersection() {
+    // Bow tie, each half is a separate contour in the result
+    let mut a = Path::new();
+    a.move_to(0.0, 0.0);
+    a.line_to(2.0, 2.0);
+    a.line_to(2.0, 0.0);
+    a.line_to(0.0, 2.0);
+    let p = polygon_bool(
+      &a,
+      FillingRule::NonZero,
+      &Path::new(),
+      FillingRule::NonZero,
+      BoolOp::Union,
+    );
+    assert_eq!(split(&p.vertices).len(), 2);
+    assert!((area(&p) - 2.0).abs() < 1e-9);
+  }
+
+  #[test]
+  fn test_many_edges() {
+    // Grid of 30x30 cells against a finely flattened disk, enough edges for
+    //   the sweep and the bands to matter
+    let mut a = Path::new();
+    for i in 0..30 {
+      for j in 0..30 {
+        let (x, y) = (f64::from(i) * 2.0, f64::from(j) * 2.0);
+        rect(&mut a, x, y, x + 1.0, y + 1.0);
+      }
+    }
+    let b = crate::Ellipse::new(30.0, 30.0, 100.0, 100.0, 2000);
+    let nz = FillingRule::NonZero;
+    // The disk covers the whole grid
+    let p = polygon_bool(&a, nz, &b, nz, BoolOp::Intersection);
+    assert!((area(&p) - 900.0).abs() < 1e-6);
+    assert_eq!(split(&p.vertices).len(), 900);
+    let mut c = Path::new();
+    rect(&mut c, 0.5, 0.5, 59.5, 59.5);
+    let p = polygon_bool(&a, nz, &c, nz, BoolOp::Difference);
+    // Half of the cells along the left and bottom, and a quarter less of
+    //   the corner one
+    assert!((area(&p) - (0.75 + 58.0 * 0.5)).abs() < 1e-6);
+    assert_eq!(split(&p.vertices).len(), 59);
+  }
+}
@@ -0,0 +1,396 @@
+//! Clipping of Vertex Sources
+//!
+//! Trim paths to a rectangle before stroking, dashing or filling, so the
+//!   work done afterwards follows the visible part of the path, ports of
+//!   AGG's `conv_clip_polygon` and `conv_clip_polyline` using the
+//!   [Liang Barsky](https://en.wikipedia.org/wiki/Liang-Barsky_algorithm)
+//!   algorithm
+//!
+//! # Example
+//!
+//!     // Long polyline, mostly off screen
+//!     let mut path = agg::Path::new();
+//!     path.move_to(-1000.0, 50.0);
+//!     for i in 0..200 {
+//!       path.line_to(-1000.0 + 10.0 * i as f64, 50.0 + (i % 2) as f64 * 20.0);
+//!     }
+//!
+//!     // Keep the part near the canvas, with room for the line width
+//!     let clip = agg::Rectangle::new(-5.0, -5.0, 105.0, 105.0);
+//!     let visible = agg::ConvClipPolyline::new(&path, clip);
+//!     let mut stroke = agg::Stroke::new(visible);
+//!     stroke.width(2.0);
+//!
+//!     // Draw
+//!     // let mut ras = agg::RasterizerScanline::new();
+//!     // ras.add_path(&stroke);
+
+use crate::VertexSource;
+use crate::Vertices;
+use crate::curves::flatten;
+use crate::paths::PathCommand;
+use crate::paths::Vertex;
+use crate::paths::sub_paths;
+use crate::sources::Rectangle;
+
+/// Parameters of the part of the segment from a to b inside the rectangle
+///
+/// Returns `None` if the segment misses the rectangle
+fn clip_segment(r: &Rectangle<f64>, a: (f64, f64), b: (f64, f64)) -> Option<(f64, f64)> {
+  let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+  let (mut t0, mut t1) = (0.0_f64, 1.0_f64);
+  for (p, q) in [
+    (-dx, a.0 - r.x1()),
+    (dx, r.x2() - a.0),
+    (-dy, a.1 - r.y1()),
+    (dy, r.y2() - a.1),
+  ] {
+    if p == 0.0 {
+      if q < 0.0 {
+        return None;
+      }
+    } else if p < 0.0 {
+      t0 = t0.max(q / p);
+    } else {
+      t1 = t1.min(q / p);
+    }
+  }
+  (t0 <= t1).then_some((t0, t1))
+}
+
+/// Points of an edge from (x1,y1) to (x2,y2) of a polygon clipped to a rectangle
+///
+/// Besides the visible part of the edge, corners of the rectangle are added
+///   where the edge passes them outside, so the clipped polygon follows the
+///   sides of the rectangle. Port of AGG's polygon `clip_liang_barsky`.
+fn clip_edge(r: &Rectangle<f64>, x1: f64, y1: f64, x2: f64, y2: f64, out: &mut Vec<(f64, f64)>) {
+  const NEARZERO: f64 = 1e-30;
+  let mut dx = x2 - x1;
+  let mut dy = y2 - y1;
+  if dx == 0.0 {
+    dx = if x1 > r.x1() { -NEARZERO } else { NEARZERO };
+  }
+  if dy == 0.0 {
+    dy = if y1 > r.y1() { -NEARZERO } else { NEARZERO };
+  }
+  let (xin, xout) = if dx > 0.0 { (r.x1(), r.x2()) } else { (r.x2(), r.x1()) };
+  let (yin, yout) = if dy > 0.0 { (r.y1(), r.y2()) } else { (r.y2(), r.y1()) };
+  let tinx = (xin - x1) / dx;
+  let tiny = (yin - y1) / dy;
+  let (tin1, tin2) = if tinx < tiny { (tinx, tiny) } else { (tiny, tinx) };
+  if tin1 > 1.0 {
+    return;
+  }
+  if tin1 > 0.0 {
+    out.push((xin, yin));
+  }
+  if tin2 > 1.0 {
+    return;
+  }
+  let toutx = (xout - x1) / dx;
+  let touty = (yout - y1) / dy;
+  let tout1 = toutx.min(touty);
+  if tin2 <= 0.0 && tout1 <= 0.0 {
+    return;
+  }
+  if tin2 <= tout1 {
+    if tin2 > 0.0 {
+      if tinx > tiny {
+        out.push((xin, y1 + tinx * dy));
+      } else {
+        out.push((x1 + tiny * dx, yin));
+      }
+    }
+    if tout1 < 1.0 {
+      if toutx < touty {
+        out.push((xout, y1 + toutx * dy));
+      } else {
+        out.push((x1 + touty * dx, yout));
+      }
+    } else {
+      out.push((x2, y2));
+    }
+  } else if tinx > tiny {
+    out.push((xin, yout));
+  } else {
+    out.push((xout, yin));
+  }
+}
+
+/// Sub-paths of a Vertex Source, flattened, as points and if they are closed
+///
+/// Pulled from the source one sub-path at a time
+fn polygons<VS: VertexSource>(source: &VS, scale: f64) -> impl Iterator<Item = (Vec<(f64, f64)>, bool)> + '_ {
+  sub_paths(flatten(source.vertices(), scale)).map(|v| {
+    let closed = v.iter().any(|v| v.cmd == PathCommand::Close);
+    let pts = v.iter().filter(|v| v.cmd.is_vertex()).map(|v| (v.x, v.y)).collect();
+    (pts, closed)
+  })
+}
+
+/// Polygon Clipping for Paths and Vertex Sources
+///
+/// Every sub-path is treated as a closed polygon and clipped to the
+///   rectangle. Parts outside are replaced by runs along the sides of the
+///   rectangle, so the fill inside the rectangle is unchanged. Curves are
+///   flattened.
+#[derive(Debug)]
+pub struct ConvClipPolygon<T: VertexSource> {
+  /// Source of Vertices
+  source: T,
+  /// Clipping rectangle
+  clip_box: Rectangle<f64>,
+  /// Approximation scale of curves in the source, 1.0
+  approx_scale: f64,
+}
+
+impl<T> VertexSource for ConvClipPolygon<T>
+where
+  T: VertexSource,
+{
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    self.vertices().collect()
+  }
+  fn vertices(&self) -> Vertices<'_> {
+    Box::new(polygons(&self.source, self.approx_scale).flat_map(move |(pts, _)| self.clip(&pts)))
+  }
+}
+
+impl<T> ConvClipPolygon<T>
+where
+  T: VertexSource,
+{
+  /// Create a new Polygon Clipper from a Vertex Source and a clipping rectangle
+  pub fn new(source: T, clip_box: Rectangle<f64>) -> Self {
+    Self {
+      source,
+      clip_box,
+      approx_scale: 1.0,
+    }
+  }
+  /// Set the clipping rectangle
+  pub fn clip_box(&mut self, clip_box: Rectangle<f64>) {
+    self.clip_box = clip_box;
+  }
+  /// Get the clipping rectangle
+  pub fn get_clip_box(&self) -> Rectangle<f64> {
+    self.clip_box
+  }
+  /// Set the approximation scale of curves in the source
+  ///
+  /// Use the scale of any later transform, see [`Stroke::transform`](crate::Stroke::transform)
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.approx_scale = scale;
+  }
+  /// Clip a sub-path
+  fn clip(&self, pts: &[(f64, f64)]) -> Vec<Vertex<f64>> {
+    let r = &self.clip_box;
+    if pts.len() < 2 {
+      return vec![];
+    }
+    let mut clipped: Vec<(f64, f64)> = vec![];
+    let (mut x1, mut y1) = pts[0];
+    let mut f1 = r.clip_flags(x1, y1);
+    if f1 == 0 {
+      clipped.push((x1, y1));
+    }
+    for &(x2, y2) in pts.iter().skip(1).chain(std::iter::once(&pts[0])) {
+      let f2 = r.clip_flags(x2, y2);
+      if f1 != f2 {
+        clip_edge(r, x1, y1, x2, y2, &mut clipped);
+      } else if f2 == 0 {
+        clipped.push((x2, y2));
+      }
+      (x1, y1, f1) = (x2, y2, f2);
+    }
+    clipped.dedup();
+    if clipped.len() > 1 && clipped.first() == clipped.last() {
+      clipped.pop();
+    }
+    if clipped.len() < 3 {
+      return vec![];
+    }
+    let (x0, y0) = clipped[0];
+    let mut out = vec![Vertex::move_to(x0, y0)];
+    out.extend(clipped[1..].iter().map(|&(x, y)| Vertex::line_to(x, y)));
+    let (x, y) = clipped[clipped.len() - 1];
+    out.push(Vertex::close_polygon(x, y));
+    out
+  }
+}
+
+/// Polyline Clipping for Paths and Vertex Sources
+///
+/// Segments are cut at the sides of the rectangle and parts outside are
+///   dropped, so a sub-path may become several open sub-paths. Closed
+///   sub-paths fully inside are unchanged, others are opened, including
+///   their closing segment. Curves are flattened.
+#[derive(Debug)]
+pub struct ConvClipPolyline<T: VertexSource> {
+  /// Source of Vertices
+  source: T,
+  /// Clipping rectangle
+  clip_box: Rectangle<f64>,
+  /// Approximation scale of curves in the source, 1.0
+  approx_scale: f64,
+}
+
+impl<T> VertexSource for ConvClipPolyline<T>
+where
+  T: VertexSource,
+{
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    self.vertices().collect()
+  }
+  fn vertices(&self) -> Vertices<'_> {
+    Box::new(polygons(&self.source, self.approx_scale).flat_map(move |(pts, closed)| self.clip(pts, closed)))
+  }
+}
+
+impl<T> ConvClipPolyline<T>
+where
+  T: VertexSource,
+{
+  /// Create a new Polyline Clipper from a Vertex Source and a clipping rectangle
+  pub fn new(source: T, clip_box: Rectangle<f64>) -> Self {
+    Self {
+      source,
+      clip_box,
+      approx_scale: 1.0,
+    }
+  }
+  /// Set the clipping rectangle
+  pub fn clip_box(&mut self, clip_box: Rectangle<f64>) {
+    self.clip_box = clip_box;
+  }
+  /// Get the clipping rectangle
+  pub fn get_clip_box(&self) -> Rectangle<f64> {
+    self.clip_box
+  }
+  /// Set the approximation scale of curves in the source
+  ///
+  /// Use the scale of any later transform, see [`Stroke::transform`](crate::Stroke::transform)
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.approx_scale = scale;
+  }
+  /// Clip a sub-path
+  fn clip(&self, mut pts: Vec<(f64, f64)>, closed: bool) -> Vec<Vertex<f64>> {
+    let r = &self.clip_box;
+    let mut out = vec![];
+    if pts.is_empty() {
+      return out;
+    }
+    if pts.iter().all(|&(x, y)| r.clip_flags(x, y) == 0) {
+      out.push(Vertex::move_to(pts[0].0, pts[0].1));
+      out.extend(pts[1..].iter().map(|&(x, y)| Vertex::line_to(x, y)));
+      if closed {
+        let (x, y) = pts[pts.len() - 1];
+        out.push(Vertex::close_polygon(x, y));
+      }
+      return out;
+    }
+    if closed {
+      pts.push(pts[0]);
+    }
+    // Pen is down while the previous segment ended inside
+    let mut down = false;
+    for w in pts.windows(2) {
+      let (a, b) = (w[0], w[1]);
+      let Some((t0, t1)) = clip_segment(r, a, b) else {
+        down = false;
+        continue;
+      };
+      let at = |t: f64| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+      let (x0, y0) = if t0 > 0.0 { at(t0) } else { a };
+      let (x1, y1) = if t1 < 1.0 { at(t1) } else { b };
+      if !down || t0 > 0.0 {
+        out.push(Vertex::move_to(x0, y0));
+      }
+      out.push(Vertex::line_to(x1, y1));
+      down = t1 >= 1.0;
+    }
+    out
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Path;
+
+  fn xy(v: &[Vertex<f64>]) -> Vec<(f64, f64)> {
+    v.iter().map(|v| (v.x, v.y)).collect()
+  }
+
+  fn cmds(v: &[Vertex<f64>]) -> Vec<PathCommand> {
+    v.iter().map(|v| v.cmd).collect()
+  }
+
+  #[test]
+  fn polyline() {
+    use PathCommand::*;
+    let mut path = Path::new();
+    path.move_to(-10.0, 5.0);
+    path.line_to(5.0, 5.0);
+    path.line_to(5.0, 20.0);
+    path.line_to(8.0, 20.0);
+    path.line_to(8.0, 2.0);
+    let clip = ConvClipPolyline::new(&path, Rectangle::new(0.0, 0.0, 10.0, 10.0));
+    let v = clip.xconvert();
+    assert_eq!(xy(&v), [(0.0, 5.0), (5.0, 5.0), (5.0, 10.0), (8.0, 10.0), (8.0, 2.0)]);
+    assert_eq!(cmds(&v), [MoveTo, LineTo, LineTo, MoveTo, LineTo]);
+
+    // Missing entirely
+    let mut path = Path::new();
+    path.move_to(-10.0, -5.0);
+    path.line_to(20.0, -5.0);
+    let clip = ConvClipPolyline::new(&path, Rectangle::new(0.0, 0.0, 10.0, 10.0));
+    assert!(clip.xconvert().is_empty());
+  }
+
+  #[test]
+  fn polyline_closed() {
+    use PathCommand::*;
+    let mut path = Path::new();
+    path.move_to(2.0, 2.0);
+    path.line_to(8.0, 2.0);
+    path.line_to(8.0, 8.0);
+    path.close_polygon();
+    let mut clip = ConvClipPolyline::new(&path, Rectangle::new(0.0, 0.0, 10.0, 10.0));
+    assert_eq!(cmds(&clip.xconvert()), [MoveTo, LineTo, LineTo, Close]);
+    // Cut through the triangle, the closing segment is kept
+    clip.clip_box(Rectangle::new(0.0, 0.0, 5.0, 10.0));
+    let v = clip.xconvert();
+    assert_eq!(xy(&v), [(2.0, 2.0), (5.0, 2.0), (5.0, 5.0), (2.0, 2.0)]);
+    assert_eq!(cmds(&v), [MoveTo, LineTo, MoveTo, LineTo]);
+  }
+
+  #[test]
+  fn polygon() {
+    use PathCommand::*;
+    // Triangle sticking out to the right, and over the top right corner
+    let mut path = Path::new();
+    path.move_to(2.0, 2.0);
+    path.line_to(20.0, 2.0);
+    path.line_to(2.0, 20.0);
+    path.close_polygon();
+    let clip = ConvClipPolygon::new(&path, Rectangle::new(0.0, 0.0, 10.0, 10.0));
+    let v = clip.xconvert();
+    assert_eq!(cmds(&v), [MoveTo, LineTo, LineTo, LineTo, Close]);
+    // The long edge passes outside the corner
+    assert_eq!(xy(&v)[..4], [(2.0, 2.0), (10.0, 2.0), (10.0, 10.0), (2.0, 10.0)]);
+
+    // Around the rectangle, its outline is left
+    let mut path = Path::new();
+    path.move_to(-5.0, -5.0);
+    path.line_to(15.0, -5.0);
+    path.line_to(15.0, 15.0);
+    path.line_to(-5.0, 15.0);
+    let clip = ConvClipPolygon::new(&path, Rectangle::new(0.0, 0.0, 10.0, 10.0));
+    let r = crate::paths::bounding_rect(&clip).unwrap();
+    assert_eq!((r.x1(), r.y1(), r.x2(), r.y2()), (0.0, 0.0, 10.0, 10.0));
+    // Away from it, nothing
+    let clip = ConvClipPolygon::new(&path, Rectangle::new(20.0, 20.0, 30.0, 30.0));
+    assert!(clip.xconvert().is_empty());
+  }
+}
@@ -0,0 +1,204 @@
+//! Polygon Contour
+//!
+//! Offset closed polygons outwards or inwards, using the same joins as
+//!   [`Stroke`](crate::Stroke) but only on one side
+//!
+//! # Example
+//!
+//!     // Input Polygon
+//!     let mut path = agg::Path::new();
+//!     path.move_to( 10.0,  10.0);
+//!     path.line_to(100.0,  10.0);
+//!     path.line_to( 50.0, 100.0);
+//!     path.close_polygon();
+//!
+//!     // Halo, 5 pixels around the polygon
+//!     let mut halo = agg::Contour::new(&path);
+//!     halo.width(5.0);
+//!     halo.line_join(agg::LineJoin::Round);
+//!
+//!     // Inset, 2 pixels inside the polygon
+//!     let mut inset = agg::Contour::new(&path);
+//!     inset.width(-2.0);
+//!
+//!     // Draw
+//!     // let mut ras = agg::RasterizerScanline::new();
+//!     // ras.add_path(&halo);
+
+use crate::VertexSource;
+use crate::Vertices;
+use crate::curves::flatten;
+use crate::paths::PathCommand;
+use crate::paths::PathOrientation;
+use crate::paths::Vertex;
+use crate::paths::len;
+use crate::paths::preceive_polygon_orientation;
+use crate::paths::sub_paths;
+use crate::stroke::InnerJoin;
+use crate::stroke::LineJoin;
+use crate::stroke::MathStroke;
+
+/// Contour for Paths and Vertex Sources
+///
+/// Every sub-path is treated as a closed polygon
+#[derive(Debug)]
+pub struct Contour<T: VertexSource> {
+  /// Source of Verticies
+  source: T,
+  /// Joins
+  math: MathStroke,
+  /// Offset distance, positive is outside, 1.0
+  width: f64,
+  /// Detect orientation of each polygon, true
+  auto_detect: bool,
+}
+
+impl<T> VertexSource for Contour<T>
+where
+  T: VertexSource,
+{
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    self.vertices().collect()
+  }
+  fn vertices(&self) -> Vertices<'_> {
+    // Flatten any curves, then offset one sub-path at a time
+    Box::new(sub_paths(flatten(self.source.vertices(), self.math.approx_scale)).flat_map(move |v| self.contour(&v)))
+  }
+}
+
+impl<T> Contour<T>
+where
+  T: VertexSource,
+{
+  /// Create a new Contour from a Vertex Source
+  pub fn new(source: T) -> Self {
+    Self {
+      source,
+      math: MathStroke::new(),
+      width: 1.0,
+      auto_detect: true,
+    }
+  }
+  /// Set the offset distance
+  ///
+  /// Positive values grow the polygons, negative values shrink them
+  pub fn width(&mut self, width: f64) {
+    self.width = width;
+  }
+  /// Set Line Join style, see [`Stroke::line_join`](crate::Stroke::line_join)
+  pub fn line_join(&mut self, line_join: LineJoin) {
+    self.math.line_join(line_join);
+  }
+  /// Set Inner Join style, see [`Stroke::inner_join`](crate::Stroke::inner_join)
+  pub fn inner_join(&mut self, inner_join: InnerJoin) {
+    self.math.inner_join(inner_join);
+  }
+  /// Set miter limit
+  pub fn miter_limit(&mut self, miter_limit: f64) {
+    self.math.miter_limit(miter_limit);
+  }
+  /// Set inner miter limit
+  pub fn inner_miter_limit(&mut self, inner_miter_limit: f64) {
+    self.math.inner_miter_limit(inner_miter_limit);
+  }
+  /// Set approximation scale, of curves in the source and round joins
+  ///
+  /// Use the scale of any later transform, see [`Stroke::transform`](crate::Stroke::transform)
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.math.approximation_scale(scale);
+  }
+  /// Detect the orientation of each polygon
+  ///
+  /// When disabled, polygons are assumed to be counter-clockwise, and a
+  ///   positive width offsets clockwise polygons inwards
+  pub fn auto_detect_orientation(&mut self, auto_detect: bool) {
+    self.auto_detect = auto_detect;
+  }
+  /// Offset a sub-path
+  fn contour(&self, v0: &[Vertex<f64>]) -> Vec<Vertex<f64>> {
+    // Remove repeated vertices, including a last one on top of the first
+    let mut v: Vec<Vertex<f64>> = vec![];
+    for p in v0.iter().filter(|p| p.cmd.is_vertex()) {
+      if v.last().is_none_or(|last| len(last, p) >= 1e-6) {
+        v.push(*p);
+      }
+    }
+    while v.len() > 1 && len(&v[0], &v[v.len() - 1]) < 1e-6 {
+      v.pop();
+    }
+    let n = v.len();
+    if n < 3 {
+      return vec![];
+    }
+    let mut math = self.math;
+    let ccw = !self.auto_detect || preceive_polygon_orientation(&v) == PathOrientation::CounterClockwise;
+    math.width(if ccw { 2.0 * self.width } else { -2.0 * self.width });
+
+    let mut out = vec![];
+    for i in 0..n {
+      out.extend(math.calc_join(&v[(i + n - 1) % n], &v[i], &v[(i + 1) % n]));
+    }
+    if out.is_empty() {
+      return out;
+    }
+    out[0].cmd = PathCommand::MoveTo;
+    let last = out[out.len() - 1];
+    out.push(Vertex::close_polygon(last.x, last.y));
+    out
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Path;
+  use crate::paths::bounding_rect;
+
+  fn square(ccw: bool) -> Path {
+    let mut pts = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+    if !ccw {
+      pts.reverse();
+    }
+    let mut path = Path::new();
+    path.move_to(pts[0].0, pts[0].1);
+    for &(x, y) in &pts[1..] {
+      path.line_to(x, y);
+    }
+    path.close_polygon();
+    path
+  }
+
+  fn bounds<T: VertexSource>(c: &Contour<T>) -> (f64, f64, f64, f64) {
+    let r = bounding_rect(c).unwrap();
+    (r.x1(), r.y1(), r.x2(), r.y2())
+  }
+
+  #[test]
+  fn outset_and_inset() {
+    for ccw in [true, false] {
+      let mut c = Contour::new(square(ccw));
+      c.width(2.0);
+      assert_eq!(bounds(&c), (-2.0, -2.0, 12.0, 12.0));
+      c.width(-2.0);
+      assert_eq!(bounds(&c), (2.0, 2.0, 8.0, 8.0));
+    }
+  }
+
+  #[test]
+  fn fixed_orientation() {
+    let mut c = Contour::new(square(false));
+    c.auto_detect_orientation(false);
+    c.width(2.0);
+    assert_eq!(bounds(&c), (2.0, 2.0, 8.0, 8.0));
+  }
+
+  #[test]
+  fn output_is_closed_polygon() {
+    let mut c = Contour::new(square(true));
+    c.width(1.0);
+    let v = c.xconvert();
+    assert_eq!(v.len(), 5);
+    assert_eq!(v[0].cmd, PathCommand::MoveTo);
+    assert_eq!(v[4].cmd, PathCommand::Close);
+  }
+}
@@ -0,0 +1,810 @@
+//! Bezier Curves
+//!
+//! Quadratic ([`Curve3`]) and cubic ([`Curve4`]) Bezier curves, and the
+//! [`ConvCurve`] converter which flattens the `Curve3` / `Curve4` commands
+//! of a [`Path`](crate::Path) into line segments.
+//!
+//! # Example
+//!
+//!     // Input Path with a quadratic and a cubic curve
+//!     let mut path = agg::Path::new();
+//!     path.move_to(10.0, 10.0);
+//!     path.curve3(50.0, 90.0, 90.0, 10.0);
+//!     path.curve4(120.0, -30.0, 150.0, 50.0, 190.0, 10.0);
+//!
+//!     // Flatten the curves into line segments
+//!     let mut curve = agg::ConvCurve::new(path);
+//!     curve.approximation_scale(2.0);
+//!
+//!     // Draw
+//!     let mut ras: agg::RasterizerScanline = agg::RasterizerScanline::new();
+//!     ras.add_path(&curve);
+
+use crate::VertexSource;
+use crate::Vertices;
+use crate::paths::PathCommand;
+use crate::paths::Vertex;
+
+use std::f64::consts::PI;
+
+const CURVE_COLLINEARITY_EPSILON: f64 = 1e-30;
+const CURVE_ANGLE_TOLERANCE_EPSILON: f64 = 0.01;
+const CURVE_RECURSION_LIMIT: usize = 32;
+
+/// Curve Approximation Method
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum CurveApproximationMethod {
+  /// Incremental, fixed number of steps based on the curve length
+  Inc,
+  /// Adaptive, recursive subdivision
+  #[default]
+  Div,
+}
+
+/// Squared distance between two points
+fn calc_sq_distance(x1: f64, y1: f64, x2: f64, y2: f64) -> f64 {
+  let dx = x2 - x1;
+  let dy = y2 - y1;
+  dx * dx + dy * dy
+}
+
+/// Absolute difference between two angles, within [0, PI]
+fn angle_diff(a1: f64, a2: f64) -> f64 {
+  let da = (a1 - a2).abs();
+  if da >= PI { 2.0 * PI - da } else { da }
+}
+
+/// Convert a set of points to a MoveTo followed by LineTo's
+fn to_vertices(pts: Vec<(f64, f64)>) -> Vec<Vertex<f64>> {
+  pts
+    .into_iter()
+    .enumerate()
+    .map(|(i, (x, y))| {
+      if i == 0 {
+        Vertex::move_to(x, y)
+      } else {
+        Vertex::line_to(x, y)
+      }
+    })
+    .collect()
+}
+
+/// Quadratic Bezier Curve
+///
+/// Defined by a start point, a control point and an end point
+#[derive(Debug, Copy, Clone)]
+pub struct Curve3 {
+  /// Start, control and end points
+  pts: [f64; 6],
+  /// Approximation method, Div
+  approximation_method: CurveApproximationMethod,
+  /// Approximation scale, 1.0
+  approximation_scale: f64,
+  /// Angle tolerance in radians, 0.0
+  angle_tolerance: f64,
+}
+
+impl Default for Curve3 {
+  fn default() -> Self {
+    Self::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0)
+  }
+}
+
+impl VertexSource for Curve3 {
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    to_vertices(self.points())
+  }
+}
+
+impl Curve3 {
+  /// Create a new Quadratic Curve
+  ///
+  /// (`x1`,`y1`) is the start, (`x2`,`y2`) is the control point and
+  /// (`x3`,`y3`) is the end
+  pub fn new(x1: f64, y1: f64, x2: f64, y2: f64, x3: f64, y3: f64) -> Self {
+    Self {
+      pts: [x1, y1, x2, y2, x3, y3],
+      approximation_method: CurveApproximationMethod::Div,
+      approximation_scale: 1.0,
+      angle_tolerance: 0.0,
+    }
+  }
+  /// Set the start, control and end points of the curve
+  pub fn init(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, x3: f64, y3: f64) {
+    self.pts = [x1, y1, x2, y2, x3, y3];
+  }
+  /// Set approximation method
+  pub fn approximation_method(&mut self, method: CurveApproximationMethod) {
+    self.approximation_method = method;
+  }
+  /// Set approximation scale
+  ///
+  /// Should be set to the scale from world to screen coordinates
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.approximation_scale = scale;
+  }
+  /// Set angle tolerance, in radians
+  ///
+  /// A value of 0.0 disables the angle checks, only used by the `Div` method
+  pub fn angle_tolerance(&mut self, angle: f64) {
+    self.angle_tolerance = angle;
+  }
+  /// Compute points along the curve, including the start and end points
+  fn points(&self) -> Vec<(f64, f64)> {
+    match self.approximation_method {
+      CurveApproximationMethod::Inc => self.inc_points(),
+      CurveApproximationMethod::Div => self.div_points(),
+    }
+  }
+  /// Incremental, forward differencing, approximation
+  fn inc_points(&self) -> Vec<(f64, f64)> {
+    let [x1, y1, x2, y2, x3, y3] = self.pts;
+    let dx1 = x2 - x1;
+    let dy1 = y2 - y1;
+    let dx2 = x3 - x2;
+    let dy2 = y3 - y2;
+    let len = (dx1 * dx1 + dy1 * dy1).sqrt() + (dx2 * dx2 + dy2 * dy2).sqrt();
+    let num_steps = ((len * 0.25 * self.approximation_scale).round() as usize).max(4);
+
+    let step = 1.0 / num_steps as f64;
+    let step2 = step * step;
+    let tmpx = (x1 - x2 * 2.0 + x3) * step2;
+    let tmpy = (y1 - y2 * 2.0 + y3) * step2;
+
+    let (mut fx, mut fy) = (x1, y1);
+    let mut dfx = tmpx + (x2 - x1) * (2.0 * step);
+    let mut dfy = tmpy + (y2 - y1) * (2.0 * step);
+    let ddfx = tmpx * 2.0;
+    let ddfy = tmpy * 2.0;
+
+    let mut out = Vec::with_capacity(num_steps + 1);
+    out.push((x1, y1));
+    for _ in 1..num_steps {
+      fx += dfx;
+      fy += dfy;
+      dfx += ddfx;
+      dfy += ddfy;
+      out.push((fx, fy));
+    }
+    out.push((x3, y3));
+    out
+  }
+  /// Adaptive subdivision approximation
+  fn div_points(&self) -> Vec<(f64, f64)> {
+    let [x1, y1, x2, y2, x3, y3] = self.pts;
+    let tol = (0.5 / self.approximation_scale).powi(2);
+    let mut out = vec![(x1, y1)];
+    self.recursive_bezier(&mut out, tol, x1, y1, x2, y2, x3, y3, 0);
+    out.push((x3, y3));
+    out
+  }
+  fn recursive_bezier(
+    &self,
+    out: &mut Vec<(f64, f64)>,
+    tol: f64,
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+    x3: f64,
+    y3: f64,
+    level: usize,
+  ) {
+    if level > CURVE_RECURSION_LIMIT {
+      return;
+    }
+    // Mid-points of the line segments
+    let x12 = (x1 + x2) / 2.0;
+    let y12 = (y1 + y2) / 2.0;
+    let x23 = (x2 + x3) / 2.0;
+    let y23 = (y2 + y3) / 2.0;
+    let x123 = (x12 + x23) / 2.0;
+    let y123 = (y12 + y23) / 2.0;
+
+    let dx = x3 - x1;
+    let dy = y3 - y1;
+    let mut d = ((x2 - x3) * dy - (y2 - y3) * dx).abs();
+
+    if d > CURVE_COLLINEARITY_EPSILON {
+      // Regular case
+      if d * d <= tol * (dx * dx + dy * dy) {
+        // If the curvature doesn't exceed the distance tolerance
+        // we tend to finish subdivisions
+        if self.angle_tolerance < CURVE_ANGLE_TOLERANCE_EPSILON {
+          out.push((x123, y123));
+          return;
+        }
+        // Angle & Cusp Condition
+        let da = angle_diff((y3 - y2).atan2(x3 - x2), (y2 - y1).atan2(x2 - x1));
+        if da < self.angle_tolerance {
+          // Finally we can stop the recursion
+          out.push((x123, y123));
+          return;
+        }
+      }
+    } else {
+      // Collinear case
+      let da = dx * dx + dy * dy;
+      if da == 0.0 {
+        d = calc_sq_distance(x1, y1, x2, y2);
+      } else {
+        d = ((x2 - x1) * dx + (y2 - y1) * dy) / da;
+        if d > 0.0 && d < 1.0 {
+          // Simple collinear case, 1---2---3
+          // We can leave just two endpoints
+          return;
+        }
+        d = if d <= 0.0 {
+          calc_sq_distance(x2, y2, x1, y1)
+        } else if d >= 1.0 {
+          calc_sq_distance(x2, y2, x3, y3)
+        } else {
+          calc_sq_distance(x2, y2, x1 + d * dx, y1 + d * dy)
+        };
+      }
+      if d < tol {
+        out.push((x2, y2));
+        return;
+      }
+    }
+    // Continue subdivision
+    self.recursive_bezier(out, tol, x1, y1, x12, y12, x123, y123, level + 1);
+    self.recursive_bezier(out, tol, x123, y123, x23, y23, x3, y3, level + 1);
+  }
+}
+
+/// Cubic Bezier Curve
+///
+/// Defined by a start point, two control points and an end point
+#[derive(Debug, Copy, Clone)]
+pub struct Curve4 {
+  /// Start, two control and end points
+  pts: [f64; 8],
+  /// Approximation method, Div
+  approximation_method: CurveApproximationMethod,
+  /// Approximation scale, 1.0
+  approximation_scale: f64,
+  /// Angle tolerance in radians, 0.0
+  angle_tolerance: f64,
+  /// Cusp limit in radians, 0.0
+  cusp_limit: f64,
+}
+
+impl Default for Curve4 {
+  fn default() -> Self {
+    Self::new(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0)
+  }
+}
+
+impl VertexSource for Curve4 {
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    to_vertices(self.points())
+  }
+}
+
+impl Curve4 {
+  /// Create a new Cubic Curve
+  ///
+  /// (`x1`,`y1`) is the start, (`x2`,`y2`) and (`x3`,`y3`) are the control
+  /// points and (`x4`,`y4`) is the end
+  pub fn new(x1: f64, y1: f64, x2: f64, y2: f64, x3: f64, y3: f64, x4: f64, y4: f64) -> Self {
+    Self {
+      pts: [x1, y1, x2, y2, x3, y3, x4, y4],
+      approximation_method: CurveApproximationMethod::Div,
+      approximation_scale: 1.0,
+      angle_tolerance: 0.0,
+      cusp_limit: 0.0,
+    }
+  }
+  /// Set the start, control and end points of the curve
+  pub fn init(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, x3: f64, y3: f64, x4: f64, y4: f64) {
+    self.pts = [x1, y1, x2, y2, x3, y3, x4, y4];
+  }
+  /// Set approximation method
+  pub fn approximation_method(&mut self, method: CurveApproximationMethod) {
+    self.approximation_method = method;
+  }
+  /// Set approximation scale
+  ///
+  /// Should be set to the scale from world to screen coordinates
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.approximation_scale = scale;
+  }
+  /// Set angle tolerance, in radians
+  ///
+  /// A value of 0.0 disables the angle checks, only used by the `Div` method
+  pub fn angle_tolerance(&mut self, angle: f64) {
+    self.angle_tolerance = angle;
+  }
+  /// Set cusp limit, in radians
+  ///
+  /// A value of 0.0 disables the cusp checks, only used by the `Div` method
+  pub fn cusp_limit(&mut self, angle: f64) {
+    self.cusp_limit = if angle == 0.0 { 0.0 } else { PI - angle };
+  }
+  /// Compute points along the curve, including the start and end points
+  fn points(&self) -> Vec<(f64, f64)> {
+    match self.approximation_method {
+      CurveApproximationMethod::Inc => self.inc_points(),
+      CurveApproximationMethod::Div => self.div_points(),
+    }
+  }
+  /// Incremental, forward differencing, approximation
+  fn inc_points(&self) -> Vec<(f64, f64)> {
+    let [x1, y1, x2, y2, x3, y3, x4, y4] = self.pts;
+    let dx1 = x2 - x1;
+    let dy1 = y2 - y1;
+    let dx2 = x3 - x2;
+    let dy2 = y3 - y2;
+    let dx3 = x4 - x3;
+    let dy3 = y4 - y3;
+    let len = ((dx1 * dx1 + dy1 * dy1).sqrt() + (dx2 * dx2 + dy2 * dy2).sqrt() + (dx3 * dx3 + dy3 * dy3).sqrt())
+      * 0.25
+      * self.approximation_scale;
+    let num_steps = (len.round() as usize).max(4);
+
+    let step = 1.0 / num_steps as f64;
+    let step2 = step * step;
+    let step3 = step * step * step;
+
+    let pre1 = 3.0 * step;
+    let pre2 = 3.0 * step2;
+    let pre4 = 6.0 * step2;
+    let pre5 = 6.0 * step3;
+
+    let tmp1x = x1 - x2 * 2.0 + x3;
+    let tmp1y = y1 - y2 * 2.0 + y3;
+    let tmp2x = (x2 - x3) * 3.0 - x1 + x4;
+    let tmp2y = (y2 - y3) * 3.0 - y1 + y4;
+
+    let (mut fx, mut fy) = (x1, y1);
+    let mut dfx = (x2 - x1) * pre1 + tmp1x * pre2 + tmp2x * step3;
+    let mut dfy = (y2 - y1) * pre1 + tmp1y * pre2 + tmp2y * step3;
+    let mut ddfx = tmp1x * pre4 + tmp2x * pre5;
+    let mut ddfy = tmp1y * pre4 + tmp2y * pre5;
+    let dddfx = tmp2x * pre5;
+    let dddfy = tmp2y * pre5;
+
+    let mut out = Vec::with_capacity(num_steps + 1);
+    out.push((x1, y1));
+    for _ in 1..num_steps {
+      fx += dfx;
+      fy += dfy;
+      dfx += ddfx;
+      dfy += ddfy;
+      ddfx += dddfx;
+      ddfy += dddfy;
+      out.push((fx, fy));
+    }
+    out.push((x4, y4));
+    out
+  }
+  /// Adaptive subdivision approximation
+  fn div_points(&self) -> Vec<(f64, f64)> {
+    let [x1, y1, x2, y2, x3, y3, x4, y4] = self.pts;
+    let tol = (0.5 / self.approximation_scale).powi(2);
+    let mut out = vec![(x1, y1)];
+    self.recursive_bezier(&mut out, tol, x1, y1, x2, y2, x3, y3, x4, y4, 0);
+    out.push((x4, y4));
+    out
+  }
+  fn recursive_bezier(
+    &self,
+    out: &mut Vec<(f64, f64)>,
+    tol: f64,
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+    x3: f64,
+    y3: f64,
+    x4: f64,
+    y4: f64,
+    level: usize,
+  ) {
+    if level > CURVE_RECURSION_LIMIT {
+      return;
+    }
+    // Mid-points of the line segments
+    let x12 = (x1 + x2) / 2.0;
+    let y12 = (y1 + y2) / 2.0;
+    let x23 = (x2 + x3) / 2.0;
+    let y23 = (y2 + y3) / 2.0;
+    let x34 = (x3 + x4) / 2.0;
+    let y34 = (y3 + y4) / 2.0;
+    let x123 = (x12 + x23) / 2.0;
+    let y123 = (y12 + y23) / 2.0;
+    let x234 = (x23 + x34) / 2.0;
+    let y234 = (y23 + y34) / 2.0;
+    let x1234 = (x123 + x234) / 2.0;
+    let y1234 = (y123 + y234) / 2.0;
+
+    // Try to approximate the full cubic curve by a single straight line
+    let dx = x4 - x1;
+    let dy = y4 - y1;
+
+    let mut d2 = ((x2 - x4) * dy - (y2 - y4) * dx).abs();
+    let mut d3 = ((x3 - x4) * dy - (y3 - y4) * dx).abs();
+
+    match (d2 > CURVE_COLLINEARITY_EPSILON, d3 > CURVE_COLLINEARITY_EPSILON) {
+      (false, false) => {
+        // All collinear OR p1==p4
+        let k = dx * dx + dy * dy;
+        if k == 0.0 {
+          d2 = calc_sq_distance(x1, y1, x2, y2);
+          d3 = calc_sq_distance(x4, y4, x3, y3);
+        } else {
+          let k = 1.0 / k;
+          d2 = k * ((x2 - x1) * dx + (y2 - y1) * dy);
+          d3 = k * ((x3 - x1) * dx + (y3 - y1) * dy);
+          if d2 > 0.0 && d2 < 1.0 && d3 > 0.0 && d3 < 1.0 {
+            // Simple collinear case, 1---2---3---4
+            // We can leave just two endpoints
+            return;
+          }
+          d2 = if d2 <= 0.0 {
+            calc_sq_distance(x2, y2, x1, y1)
+          } else if d2 >= 1.0 {
+            calc_sq_distance(x2, y2, x4, y4)
+          } else {
+            calc_sq_distance(x2, y2, x1 + d2 * dx, y1 + d2 * dy)
+          };
+          d3 = if d3 <= 0.0 {
+            calc_sq_distance(x3, y3, x1, y1)
+          } else if d3 >= 1.0 {
+            calc_sq_distance(x3, y3, x4, y4)
+          } else {
+            calc_sq_distance(x3, y3, x1 + d3 * dx, y1 + d3 * dy)
+          };
+        }
+        if d2 > d3 {
+          if d2 < tol {
+            out.push((x2, y2));
+            return;
+          }
+        } else if d3 < tol {
+          out.push((x3, y3));
+          return;
+        }
+      }
+      (false, true) => {
+        // p1,p2,p4 are collinear, p3 is significant
+        if d3 * d3 <= tol * (dx * dx + dy * dy) {
+          if self.angle_tolerance < CURVE_ANGLE_TOLERANCE_EPSILON {
+            out.push((x23, y23));
+            return;
+          }
+          // Angle Condition
+          let da1 = angle_diff((y4 - y3).atan2(x4 - x3), (y3 - y2).atan2(x3 - x2));
+          if da1 < self.angle_tolerance {
+            out.push((x2, y2));
+            out.push((x3, y3));
+            return;
+          }
+          if self.cusp_limit != 0.0 && da1 > self.cusp_limit {
+            out.push((x3, y3));
+            return;
+          }
+        }
+      }
+      (true, false) => {
+        // p1,p3,p4 are collinear, p2 is significant
+        if d2 * d2 <= tol * (dx * dx + dy * dy) {
+          if self.angle_tolerance < CURVE_ANGLE_TOLERANCE_EPSILON {
+            out.push((x23, y23));
+            return;
+          }
+          // Angle Condition
+          let da1 = angle_diff((y3 - y2).atan2(x3 - x2), (y2 - y1).atan2(x2 - x1));
+          if da1 < self.angle_tolerance {
+            out.push((x2, y2));
+            out.push((x3, y3));
+            return;
+          }
+          if self.cusp_limit != 0.0 && da1 > self.cusp_limit {
+            out.push((x2, y2));
+            return;
+          }
+        }
+      }
+      (true, true) => {
+        // Regular case
+        if (d2 + d3) * (d2 + d3) <= tol * (dx * dx + dy * dy) {
+          // If the curvature doesn't exceed the distance tolerance
+          // we tend to finish subdivisions
+          if self.angle_tolerance < CURVE_ANGLE_TOLERANCE_EPSILON {
+            out.push((x23, y23));
+            return;
+          }
+          // Angle & Cusp Condition
+          let k = (y3 - y2).atan2(x3 - x2);
+          let da1 = angle_diff(k, (y2 - y1).atan2(x2 - x1));
+          let da2 = angle_diff((y4 - y3).atan2(x4 - x3), k);
+          if da1 + da2 < self.angle_tolerance {
+            // Finally we can stop the recursion
+            out.push((x23, y23));
+            return;
+          }
+          if self.cusp_limit != 0.0 {
+            if da1 > self.cusp_limit {
+              out.push((x2, y2));
+              return;
+            }
+            if da2 > self.cusp_limit {
+              out.push((x3, y3));
+              return;
+            }
+          }
+        }
+      }
+    }
+    // Continue subdivision
+    self.recursive_bezier(out, tol, x1, y1, x12, y12, x123, y123, x1234, y1234, level + 1);
+    self.recursive_bezier(out, tol, x1234, y1234, x234, y234, x34, y34, x4, y4, level + 1);
+  }
+}
+
+/// Curve Converter
+///
+/// Flattens `Curve3` and `Curve4` commands from a Vertex Source into
+/// `LineTo` commands, all other commands are passed through unchanged
+///
+/// A `Curve3` command is the control point and is followed by the end
+/// point.  A `Curve4` command is the first control point and is followed
+/// by the second control point and the end point.
+#[derive(Debug)]
+pub struct ConvCurve<T: VertexSource> {
+  /// Source of Vertices
+  source: T,
+  /// Quadratic curve approximation settings
+  curve3: Curve3,
+  /// Cubic curve approximation settings
+  curve4: Curve4,
+}
+
+impl<T> VertexSource for ConvCurve<T>
+where
+  T: VertexSource,
+{
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    self.convert()
+  }
+  fn vertices(&self) -> Vertices<'_> {
+    Box::new(CurveVertices {
+      src: self.source.vertices(),
+      ahead: vec![],
+      pending: vec![].into_iter(),
+      curve3: self.curve3,
+      curve4: self.curve4,
+      x0: 0.0,
+      y0: 0.0,
+    })
+  }
+}
+
+impl<T> ConvCurve<T>
+where
+  T: VertexSource,
+{
+  /// Create a new Curve Converter from a Vertex Source
+  pub fn new(source: T) -> Self {
+    Self {
+      source,
+      curve3: Curve3::default(),
+      curve4: Curve4::default(),
+    }
+  }
+  /// Set approximation method
+  pub fn approximation_method(&mut self, method: CurveApproximationMethod) {
+    self.curve3.approximation_method(method);
+    self.curve4.approximation_method(method);
+  }
+  /// Set approximation scale
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.curve3.approximation_scale(scale);
+    self.curve4.approximation_scale(scale);
+  }
+  /// Set angle tolerance, in radians
+  pub fn angle_tolerance(&mut self, angle: f64) {
+    self.curve3.angle_tolerance(angle);
+    self.curve4.angle_tolerance(angle);
+  }
+  /// Set cusp limit, in radians
+  pub fn cusp_limit(&mut self, angle: f64) {
+    self.curve4.cusp_limit(angle);
+  }
+  /// Flatten the curves of the Vertex Source
+  fn convert(&self) -> Vec<Vertex<f64>> {
+    self.vertices().collect()
+  }
+}
+
+/// Flatten the curves of a stream of vertices, with an approximation scale
+///   and otherwise default settings
+pub(crate) fn flatten(src: Vertices<'_>, scale: f64) -> Vertices<'_> {
+  let (mut curve3, mut curve4) = (Curve3::default(), Curve4::default());
+  curve3.approximation_scale(scale);
+  curve4.approximation_scale(scale);
+  Box::new(CurveVertices {
+    src,
+    ahead: vec![],
+    pending: vec![].into_iter(),
+    curve3,
+    curve4,
+    x0: 0.0,
+    y0: 0.0,
+  })
+}
+
+/// Lazily flattened vertices of a [`ConvCurve`]
+struct CurveVertices<'a> {
+  /// Source vertices
+  src: Vertices<'a>,
+  /// Source vertices read ahead, in reverse order
+  ahead: Vec<Vertex<f64>>,
+  /// Flattened vertices of the current curve
+  pending: std::vec::IntoIter<Vertex<f64>>,
+  /// Quadratic curve approximation settings
+  curve3: Curve3,
+  /// Cubic curve approximation settings
+  curve4: Curve4,
+  /// Last vertex position, start of the next curve
+  x0: f64,
+  y0: f64,
+}
+
+impl CurveVertices<'_> {
+  fn pull(&mut self) -> Option<Vertex<f64>> {
+    self.ahead.pop().or_else(|| self.src.next())
+  }
+  /// Queue a flattened curve, without its starting point
+  fn flatten(&mut self, v: Vec<Vertex<f64>>, end: &Vertex<f64>) -> Option<Vertex<f64>> {
+    self.pending = v.into_iter();
+    self.pending.next();
+    (self.x0, self.y0) = (end.x, end.y);
+    self.pending.next()
+  }
+}
+
+impl Iterator for CurveVertices<'_> {
+  type Item = Vertex<f64>;
+  fn next(&mut self) -> Option<Vertex<f64>> {
+    if let Some(v) = self.pending.next() {
+      return Some(v);
+    }
+    let v = self.pull()?;
+    let out = match v.cmd {
+      PathCommand::Curve3 => match self.pull() {
+        Some(end) => {
+          let mut curve = self.curve3;
+          curve.init(self.x0, self.y0, v.x, v.y, end.x, end.y);
+          return self.flatten(curve.xconvert(), &end).or_else(|| self.next());
+        }
+        None => Vertex::line_to(v.x, v.y),
+      },
+      PathCommand::Curve4 => match (self.pull(), self.pull()) {
+        (Some(c2), Some(end)) => {
+          let mut curve = self.curve4;
+          curve.init(self.x0, self.y0, v.x, v.y, c2.x, c2.y, end.x, end.y);
+          return self.flatten(curve.xconvert(), &end).or_else(|| self.next());
+        }
+        (c2, _) => {
+          // Incomplete curve, use the remaining points as a line
+          self.ahead.extend(c2);
+          Vertex::line_to(v.x, v.y)
+        }
+      },
+      PathCommand::MoveTo | PathCommand::LineTo => v,
+      PathCommand::Close | PathCommand::Stop => return Some(v),
+    };
+    (self.x0, self.y0) = (out.x, out.y);
+    Some(out)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Path;
+
+  /// Distance from point (x,y) to the quadratic curve, by sampling
+  fn dist_curve3(pts: [f64; 6], x: f64, y: f64) -> f64 {
+    let [x1, y1, x2, y2, x3, y3] = pts;
+    (0..=2000)
+      .map(|i| {
+        let t = f64::from(i) / 2000.0;
+        let mt = 1.0 - t;
+        let cx = mt * mt * x1 + 2.0 * mt * t * x2 + t * t * x3;
+        let cy = mt * mt * y1 + 2.0 * mt * t * y2 + t * t * y3;
+        ((cx - x).powi(2) + (cy - y).powi(2)).sqrt()
+      })
+      .fold(f64::MAX, f64::min)
+  }
+
+  #[test]
+  fn test_curve3_methods() {
+    let pts = [0.0, 0.0, 50.0, 100.0, 100.0, 0.0];
+    for method in [CurveApproximationMethod::Inc, CurveApproximationMethod::Div] {
+      let mut c = Curve3::new(pts[0], pts[1], pts[2], pts[3], pts[4], pts[5]);
+      c.approximation_method(method);
+      let v = c.xconvert();
+      assert!(v.len() > 4, "{method:?}");
+      assert_eq!(v[0].cmd, PathCommand::MoveTo);
+      assert_eq!((v[0].x, v[0].y), (0.0, 0.0));
+      let last = v[v.len() - 1];
+      assert_eq!((last.x, last.y), (100.0, 0.0));
+      for p in &v {
+        assert!(dist_curve3(pts, p.x, p.y) < 0.1, "{method:?} {p:?}");
+      }
+    }
+  }
+
+  #[test]
+  fn test_curve4_scale() {
+    let mut c = Curve4::new(0.0, 0.0, 0.0, 100.0, 100.0, 100.0, 100.0, 0.0);
+    let n1 = c.xconvert().len();
+    c.approximation_scale(4.0);
+    let n4 = c.xconvert().len();
+    assert!(n4 > n1);
+    // Straight line collapses to the end points
+    let c = Curve4::new(0.0, 0.0, 10.0, 0.0, 20.0, 0.0, 30.0, 0.0);
+    assert_eq!(c.xconvert().len(), 2);
+  }
+
+  #[test]
+  fn test_conv_curve() {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.curve3(50.0, 100.0, 100.0, 0.0);
+    path.curve3_smooth(200.0, 0.0);
+    path.line_to(200.0, 50.0);
+    path.curve4(150.0, 100.0, 50.0, 100.0, 0.0, 50.0);
+    path.close_polygon();
+    let v = ConvCurve::new(&path).xconvert();
+    assert!(v.len() > path.vertices.len());
+    assert_eq!(v[0].cmd, PathCommand::MoveTo);
+    assert_eq!(v[v.len() - 1].cmd, PathCommand::Close);
+    assert!(
+      v[1..v.len() - 1].iter().all(|v| v.cmd == PathCommand::LineTo),
+      "only line segments remain"
+    );
+    // Smooth curve reflects the previous control point
+    assert_eq!((path.vertices[3].x, path.vertices[3].y), (150.0, -100.0));
+    // End points of the curves are preserved
+    assert!(v.iter().any(|p| p.x == 100.0 && p.y == 0.0));
+    assert!(v.iter().any(|p| p.x == 0.0 && p.y == 50.0));
+  }
+
+  /// Only streams its vertices, building the vector is an error
+  struct Streamed(Path);
+  impl VertexSource for Streamed {
+    fn xconvert(&self) -> Vec<Vertex<f64>> {
+      panic!("vertices were collected")
+    }
+    fn vertices(&self) -> Vertices<'_> {
+      self.0.vertices()
+    }
+  }
+
+  #[test]
+  fn test_conv_curve_streaming() {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.curve4(0.0, 100.0, 100.0, 100.0, 100.0, 0.0);
+    path.curve3(150.0, -50.0, 200.0, 0.0);
+    path.line_to(200.0, 50.0);
+    // Incomplete curve at the end
+    path.vertices.push(Vertex::curve4(150.0, 100.0));
+    path.vertices.push(Vertex::curve4(50.0, 100.0));
+    let eager = ConvCurve::new(&path).xconvert();
+    let lazy: Vec<_> = ConvCurve::new(Streamed(path.clone())).vertices().collect();
+    assert_eq!(eager.len(), lazy.len());
+    for (a, b) in eager.iter().zip(&lazy) {
+      assert_eq!((a.x, a.y, a.cmd), (b.x, b.y, b.cmd));
+    }
+    assert_eq!(lazy[lazy.len() - 1].cmd, PathCommand::LineTo);
+    // Rasterizers pull the vertices
+    let mut ras: crate::RasterizerScanline = crate::RasterizerScanline::new();
+    ras.add_path(&Streamed(path));
+  }
+}
@@ -1,6 +1,6 @@
 use fixed::types::I28F4;
 
-use crate::{LineInterpolator, PixelLike, Position, SubPixel, Transform};
+use crate::{LineInterpolator, PixelLike, Position, SubPixel, Transform, Transformer};
 
 pub trait GradientCalculation {
   fn calculate<P: PixelLike>(&self, x: P, y: P, d2: P) -> P;
@@ -22,27 +22,54 @@ impl GradientCalculation for GradientY {
   }
 }
 
+/// Number of pixels between exact transforms, for non-affine transforms
+const SUBDIV: usize = 16;
+
 #[derive(Debug)]
-struct Interpolator<P> {
+struct Interpolator<P, Tr = Transform> {
   li_x: LineInterpolator<P>,
   li_y: LineInterpolator<P>,
-  trans: Transform,
+  trans: Tr,
+  /// Source position of the end of the current subdivision
+  x: f64,
+  y: f64,
+  /// Pixels left in the current subdivision
+  count: usize,
+  /// Pixels left after the current subdivision
+  len: usize,
 }
-impl<P: PixelLike> Interpolator<P> {
-  pub fn new(trans: Transform, x: f64, y: f64, len: usize) -> Self {
+impl<P: PixelLike, Tr: Transformer> Interpolator<P, Tr> {
+  pub fn new(trans: Tr, x: f64, y: f64, len: usize) -> Self {
     let (tx, ty) = trans.transform(x, y);
     let x1 = P::from_f64_rounded(tx);
     let y1 = P::from_f64_rounded(ty);
-
-    let (tx, ty) = trans.transform(x + len as f64, y);
+    let mut interp = Self {
+      li_x: LineInterpolator::new(x1, x1, 0),
+      li_y: LineInterpolator::new(y1, y1, 0),
+      trans,
+      x,
+      y,
+      count: 0,
+      len,
+    };
+    interp.subdivide(x1, y1);
+    interp
+  }
+  /// Start the next subdivision, the whole span for affine transforms
+  fn subdivide(&mut self, x1: P, y1: P) {
+    let n = if self.trans.is_affine() {
+      self.len
+    } else {
+      self.len.min(SUBDIV)
+    };
+    self.x += n as f64;
+    self.len -= n;
+    self.count = n;
+    let (tx, ty) = self.trans.transform(self.x, self.y);
     let x2 = P::from_f64_rounded(tx);
     let y2 = P::from_f64_rounded(ty);
-
-    Self {
-      trans,
-      li_x: LineInterpolator::new(x1, x2, len as i64),
-      li_y: LineInterpolator::new(y1, y2, len as i64),
-    }
+    self.li_x = LineInterpolator::new(x1, x2, n as i64);
+    self.li_y = LineInterpolator::new(y1, y2, n as i64);
   }
   pub fn dec(&mut self) {
     self.li_x.dec();
@@ -51,13 +78,18 @@ impl<P: PixelLike> Interpolator<P> {
   pub fn inc(&mut self) {
     self.li_x.inc();
     self.li_y.inc();
+    self.count = self.count.saturating_sub(1);
+    if self.count == 0 && self.len > 0 {
+      let (x1, y1) = self.coordinates();
+      self.subdivide(x1, y1);
+    }
   }
   pub fn coordinates(&self) -> (P, P) {
     (self.li_x.y, self.li_y.y)
   }
 }
 
-impl<P: PixelLike> Iterator for Interpolator<P> {
+impl<P: PixelLike, Tr: Transformer> Iterator for Interpolator<P, Tr> {
   type Item = (P, P);
   fn next(&mut self) -> Option<Self::Item> {
     let result = self.coordinates();
@@ -89,10 +121,13 @@ pub trait Gradient {
 ///   [`Interpolator`] to apply `trans` and subpixel precision. The implementation
 ///   guards against a zero-length color range (`d2 - d1`) by treating it as at
 ///   least 1, so callers do not need to perform that check.
+/// - `trans` may be any [`Transformer`], non-affine ones like
+///   [`TransPerspective`](crate::TransPerspective) are evaluated exactly
+///   every 16 pixels and interpolated in between.
 /// - [`Self::color`] should contain at least one entry; otherwise indexing will panic.
 ///   The subpixel shift used by this struct is `4` (see [`Self::subpixel_shift`]).
 #[derive(Debug)]
-pub struct SpanGradient<G, C, P4 = I28F4> {
+pub struct SpanGradient<G, C, P4 = I28F4, Tr = Transform> {
   /// sub-pixel index of gradient start
   ///
   /// These determine the range used when mapping the computed gradient value to the color stops.
@@ -106,10 +141,10 @@ pub struct SpanGradient<G, C, P4 = I28F4> {
   /// color stop array
   color: Vec<C>,
   /// transform applied to coordinates before gradient evaluation
-  trans: Transform,
+  trans: Tr,
 }
 
-impl<G, C: Clone, P4: PixelLike> SpanGradient<G, C, P4> {
+impl<G, C: Clone, P4: PixelLike, Tr: Transformer + Copy> SpanGradient<G, C, P4, Tr> {
   #[inline]
   pub fn subpixel_shift(&self) -> i64 {
     4
@@ -118,7 +153,7 @@ impl<G, C: Clone, P4: PixelLike> SpanGradient<G, C, P4> {
   pub fn subpixel_scale(&self) -> i64 {
     1 << self.subpixel_shift()
   }
-  pub fn new(trans: Transform, gradient: G, color: &[C], d1: f64, d2: f64) -> Self {
+  pub fn new(trans: Tr, gradient: G, color: &[C], d1: f64, d2: f64) -> Self {
     Self {
       d1: P4::from_f64_rounded(d1),
       d2: P4::from_f64_rounded(d2),
@@ -136,7 +171,7 @@ impl<G, C: Clone, P4: PixelLike> SpanGradient<G, C, P4> {
   pub fn prepare(&mut self) {}
 }
 
-impl<G: GradientCalculation, C: Clone, P4: PixelLike> Gradient for SpanGradient<G, C, P4> {
+impl<G: GradientCalculation, C: Clone, P4: PixelLike, Tr: Transformer + Copy> Gradient for SpanGradient<G, C, P4, Tr> {
   type Color = C;
   fn generate(&self, x: Position, y: Position, len: usize) -> Vec<Self::Color> {
     // let downscale_shift = interp.subpixel_shift() - self.subpixel_shift();
@@ -147,7 +182,7 @@ impl<G: GradientCalculation, C: Clone, P4: PixelLike> Gradient for SpanGradient<
     }
     let ncolors = self.color.len();
 
-    let interp = Interpolator::<SubPixel>::new(self.trans, x as f64 + 0.5, y as f64 + 0.5, len);
+    let interp = Interpolator::<SubPixel, Tr>::new(self.trans, x as f64 + 0.5, y as f64 + 0.5, len);
 
     interp
       .take(len)
@@ -203,6 +238,30 @@ mod tests {
     assert_eq!(coords, expected.iter().map(|(x, y)| (SubPixel::from_f64_ceiled(*x), SubPixel::from_f64_ceiled(*y))).collect::<Vec<_>>());
   }
 
+  #[test]
+  fn test_interpolator_perspective() {
+    let quad = [(0.0, 0.0), (100.0, 0.0), (80.0, 100.0), (20.0, 100.0)];
+    let persp = crate::TransPerspective::rect_to_quad(0.0, 0.0, 100.0, 100.0, &quad).unwrap();
+    // Exact every 16 pixels, close in between
+    let coords = Interpolator::<SubPixel, _>::new(persp, 0.0, 70.0, 40)
+      .take(41)
+      .collect::<Vec<_>>();
+    for (i, (x, y)) in coords.iter().enumerate() {
+      let (tx, ty) = persp.transform(i as f64, 70.0);
+      assert!((x.to_f64() - tx).abs() < 0.1, "{i}");
+      assert!((y.to_f64() - ty).abs() < 0.1, "{i}");
+    }
+    // Affine perspectives interpolate the whole span at once
+    let m = Transform::new().then_scale(1.6, 1.0);
+    let a = Interpolator::<SubPixel, _>::new(m, 0.0, 0.0, 40)
+      .take(41)
+      .collect::<Vec<_>>();
+    let b = Interpolator::<SubPixel, _>::new(crate::TransPerspective::from(m), 0.0, 0.0, 40)
+      .take(41)
+      .collect::<Vec<_>>();
+    assert_eq!(a, b);
+  }
+
   #[test]
   fn test_span_gradient() {
     let colors = vec![
@@ -0,0 +1,341 @@
+//! Markers
+//!
+//! Place a shape, like an arrowhead, at the ends of polylines, port of AGG's
+//!   `vcgen_markers_term`, `conv_marker` and `conv_shorten_path`
+//!
+//! # Example
+//!
+//!     // Polyline
+//!     let mut path = agg::Path::new();
+//!     path.move_to( 10.0, 10.0);
+//!     path.line_to(100.0, 50.0);
+//!
+//!     // Arrowhead with its tip at the origin, pointing along +x
+//!     let mut head = agg::Path::new();
+//!     head.move_to(  0.0,  0.0);
+//!     head.line_to(-10.0,  4.0);
+//!     head.line_to(-10.0, -4.0);
+//!     head.close_polygon();
+//!
+//!     // Arrowheads at both ends
+//!     let ends = agg::MarkersTerm::new(&path);
+//!     let arrows = agg::ConvMarker::new(&ends, &head);
+//!
+//!     // Line, stopping under the arrowheads
+//!     let mut stroke = agg::Stroke::new(&path);
+//!     stroke.width(2.0);
+//!     stroke.shorten(8.0);
+//!     stroke.shorten_start(8.0);
+//!
+//!     // Draw
+//!     // let mut ras = agg::RasterizerScanline::new();
+//!     // ras.add_path(&stroke);
+//!     // ras.add_path(&arrows);
+
+use crate::Transform;
+use crate::VertexSource;
+use crate::Vertices;
+use crate::curves::ConvCurve;
+use crate::paths::PathCommand;
+use crate::paths::Vertex;
+use crate::paths::len;
+use crate::paths::shorten_path;
+use crate::paths::split;
+
+/// Open sub-paths of a Vertex Source, flattened and without repeated vertices
+fn polylines<VS: VertexSource>(source: &VS, scale: f64) -> Vec<Vec<Vertex<f64>>> {
+  let mut curve = ConvCurve::new(source);
+  curve.approximation_scale(scale);
+  let v0 = curve.xconvert();
+  let mut out = vec![];
+  for (m1, m2) in split(&v0) {
+    let v = &v0[m1..=m2];
+    if v.iter().any(|v| v.cmd == PathCommand::Close) {
+      continue;
+    }
+    let mut p: Vec<Vertex<f64>> = vec![];
+    for v in v.iter().filter(|v| v.cmd.is_vertex()) {
+      if p.last().is_none_or(|last| len(last, v) > 1e-6) {
+        p.push(*v);
+      }
+    }
+    if p.len() >= 2 {
+      out.push(p);
+    }
+  }
+  out
+}
+
+/// Terminal Markers of Paths and Vertex Sources
+///
+/// Locates the start and end of every open sub-path, pointing outwards along
+///   the first and last segments. Closed sub-paths have no ends.
+///
+/// As a Vertex Source, each marker is a `MoveTo` at its position followed
+///   by a `LineTo` one unit away in its direction, the input of a
+///   [`ConvMarker`].
+#[derive(Debug)]
+pub struct MarkersTerm<T: VertexSource> {
+  /// Source of Vertices
+  source: T,
+  /// Approximation scale of curves in the source, 1.0
+  approx_scale: f64,
+}
+
+impl<T> VertexSource for MarkersTerm<T>
+where
+  T: VertexSource,
+{
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    let mut out = vec![];
+    for (x, y, a) in self.positions() {
+      out.push(Vertex::move_to(x, y));
+      out.push(Vertex::line_to(x + a.cos(), y + a.sin()));
+    }
+    out
+  }
+}
+
+impl<T> MarkersTerm<T>
+where
+  T: VertexSource,
+{
+  /// Create a new Terminal Markers generator from a Vertex Source
+  pub fn new(source: T) -> Self {
+    Self {
+      source,
+      approx_scale: 1.0,
+    }
+  }
+  /// Set the approximation scale of curves in the source
+  ///
+  /// Curves ending a sub-path give the direction of its marker from their
+  ///   last flattened segment
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.approx_scale = scale;
+  }
+  /// Position and angle, in radians, of the start and end of each open sub-path
+  ///
+  /// The angle at the start points backwards along the first segment, the
+  ///   one at the end forwards along the last segment
+  pub fn positions(&self) -> Vec<(f64, f64, f64)> {
+    let mut out = vec![];
+    for p in polylines(&self.source, self.approx_scale) {
+      let n = p.len();
+      let (a, b) = (p[0], p[1]);
+      out.push((a.x, a.y, (a.y - b.y).atan2(a.x - b.x)));
+      let (a, b) = (p[n - 2], p[n - 1]);
+      out.push((b.x, b.y, (b.y - a.y).atan2(b.x - a.x)));
+    }
+    out
+  }
+}
+
+/// Marker Converter
+///
+/// Places a copy of a marker shape at every location given by a locator.
+///   The locator is a Vertex Source of pairs: a `MoveTo` at the position
+///   followed by a `LineTo` giving the direction, like the output of
+///   [`MarkersTerm`].
+///
+/// Marker shapes are drawn around the origin, pointing along +x. Each one is
+///   transformed by the marker transform, rotated to its direction, then
+///   moved to its position.
+#[derive(Debug)]
+pub struct ConvMarker<L: VertexSource, M: VertexSource> {
+  /// Positions and directions of the markers
+  locator: L,
+  /// Shape of the markers
+  marker: M,
+  /// Transform of the shape before placing, identity
+  trans: Transform,
+}
+
+impl<L, M> VertexSource for ConvMarker<L, M>
+where
+  L: VertexSource,
+  M: VertexSource,
+{
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    self.vertices().collect()
+  }
+  fn vertices(&self) -> Vertices<'_> {
+    Box::new(self.transforms().into_iter().flat_map(move |t| {
+      self.marker.vertices().map(move |v| {
+        let (x, y) = t.transform(v.x, v.y);
+        Vertex::new(x, y, v.cmd)
+      })
+    }))
+  }
+}
+
+impl<L, M> ConvMarker<L, M>
+where
+  L: VertexSource,
+  M: VertexSource,
+{
+  /// Create a new Marker Converter from a locator and a marker shape
+  pub fn new(locator: L, marker: M) -> Self {
+    Self {
+      locator,
+      marker,
+      trans: Transform::new(),
+    }
+  }
+  /// Set the transform of the marker shape, applied before placing it
+  ///
+  /// Used to size the markers, or to flip them to point inwards
+  pub fn transform(&mut self, trans: Transform) {
+    self.trans = trans;
+  }
+  /// Get the transform of the marker shape
+  pub fn get_transform(&self) -> Transform {
+    self.trans
+  }
+  /// Complete transform of each marker, from the locator pairs
+  pub fn transforms(&self) -> Vec<Transform> {
+    let mut out = vec![];
+    let mut start: Option<Vertex<f64>> = None;
+    for v in self.locator.vertices() {
+      match (v.cmd, start) {
+        (PathCommand::MoveTo, _) => start = Some(v),
+        (PathCommand::LineTo, Some(p)) => {
+          let angle = (v.y - p.y).atan2(v.x - p.x);
+          out.push(self.trans.then_rotate(angle).then_translate(p.x, p.y));
+          start = None;
+        }
+        _ => {}
+      }
+    }
+    out
+  }
+}
+
+/// Path Shortening for Paths and Vertex Sources
+///
+/// Removes a length from the start and end of every open sub-path, for
+///   example under arrowheads before dashing. Curves are flattened, closed
+///   sub-paths are unchanged. See also [`Stroke::shorten`](crate::Stroke::shorten).
+#[derive(Debug)]
+pub struct ConvShortenPath<T: VertexSource> {
+  /// Source of Vertices
+  source: T,
+  /// Length removed from the start, 0.0
+  shorten_start: f64,
+  /// Length removed from the end, 0.0
+  shorten: f64,
+  /// Approximation scale of curves in the source, 1.0
+  approx_scale: f64,
+}
+
+impl<T> VertexSource for ConvShortenPath<T>
+where
+  T: VertexSource,
+{
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    let mut curve = ConvCurve::new(&self.source);
+    curve.approximation_scale(self.approx_scale);
+    let v0 = curve.xconvert();
+    let mut out = vec![];
+    for (m1, m2) in split(&v0) {
+      let mut v: Vec<_> = v0[m1..=m2]
+        .iter()
+        .filter(|v| v.cmd != PathCommand::Stop)
+        .copied()
+        .collect();
+      if !v.iter().any(|v| v.cmd == PathCommand::Close) {
+        shorten_path(&mut v, self.shorten_start, self.shorten);
+      }
+      out.extend(v);
+    }
+    out
+  }
+}
+
+impl<T> ConvShortenPath<T>
+where
+  T: VertexSource,
+{
+  /// Create a new Path Shortener from a Vertex Source
+  pub fn new(source: T) -> Self {
+    Self {
+      source,
+      shorten_start: 0.0,
+      shorten: 0.0,
+      approx_scale: 1.0,
+    }
+  }
+  /// Shorten the end of open sub-paths
+  pub fn shorten(&mut self, shorten: f64) {
+    self.shorten = shorten;
+  }
+  /// Shorten the start of open sub-paths
+  pub fn shorten_start(&mut self, shorten: f64) {
+    self.shorten_start = shorten;
+  }
+  /// Set the approximation scale of curves in the source
+  ///
+  /// Use the scale of any later transform, see [`Stroke::transform`](crate::Stroke::transform)
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.approx_scale = scale;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Path;
+  use std::f64::consts::{FRAC_PI_2, PI};
+
+  fn elbow() -> Path {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(10.0, 0.0);
+    path.line_to(10.0, 10.0);
+    path
+  }
+
+  #[test]
+  fn terminal_positions() {
+    let mut path = elbow();
+    // Closed sub-paths have no ends
+    path.move_to(20.0, 0.0);
+    path.line_to(30.0, 0.0);
+    path.line_to(30.0, 10.0);
+    path.close_polygon();
+    let m = MarkersTerm::new(&path);
+    assert_eq!(m.positions(), [(0.0, 0.0, PI), (10.0, 10.0, FRAC_PI_2)]);
+    let v = m.xconvert();
+    assert_eq!(v.len(), 4);
+    assert_eq!((v[2].cmd, v[2].x, v[2].y), (PathCommand::MoveTo, 10.0, 10.0));
+    assert_eq!((v[3].cmd, v[3].x, v[3].y), (PathCommand::LineTo, 10.0, 11.0));
+  }
+
+  #[test]
+  fn markers_are_placed() {
+    let path = elbow();
+    let mut tick = Path::new();
+    tick.move_to(0.0, 0.0);
+    tick.line_to(1.0, 0.0);
+    let mut marker = ConvMarker::new(MarkersTerm::new(&path), &tick);
+    marker.transform(Transform::scaling(2.0, 2.0));
+    assert_eq!(marker.transforms().len(), 2);
+    let v = marker.xconvert();
+    let xy: Vec<_> = v.iter().map(|v| (v.x.round(), v.y.round())).collect();
+    assert_eq!(xy, [(0.0, 0.0), (-2.0, 0.0), (10.0, 10.0), (10.0, 12.0)]);
+  }
+
+  #[test]
+  fn shorten() {
+    let mut s = ConvShortenPath::new(elbow());
+    s.shorten(4.0);
+    s.shorten_start(12.0);
+    let v = s.xconvert();
+    let xy: Vec<_> = v.iter().map(|v| (v.x, v.y)).collect();
+    assert_eq!(xy, [(10.0, 2.0), (10.0, 6.0)]);
+    assert_eq!(v[0].cmd, PathCommand::MoveTo);
+    // Too short
+    s.shorten(8.0);
+    assert!(s.xconvert().is_empty());
+  }
+}
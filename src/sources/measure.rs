@@ -0,0 +1,264 @@
+//! Path Measurement
+//!
+//! Arc length along the sub-paths of a Vertex Source, for placing labels
+//!   and markers
+//!
+//! # Example
+//!
+//!     let mut path = agg::Path::new();
+//!     path.move_to( 0.0, 0.0);
+//!     path.line_to(30.0, 0.0);
+//!     path.line_to(30.0, 40.0);
+//!
+//!     let measure = agg::PathMeasure::new(&path);
+//!     assert_eq!(measure.length(0), 70.0);
+//!
+//!     // Half way, on the vertical segment going up
+//!     let (x, y, angle) = measure.point_at(0, 35.0).unwrap();
+//!     assert_eq!((x, y), (30.0, 5.0));
+//!     assert_eq!(angle, std::f64::consts::FRAC_PI_2);
+//!
+//!     // Part of the path, as a new path
+//!     let part = measure.sub_path(0, 20.0, 40.0);
+//!     assert_eq!(part.vertices.len(), 3);
+
+use crate::VertexSource;
+use crate::curves::ConvCurve;
+use crate::paths::Path;
+use crate::paths::PathCommand;
+use crate::paths::Vertex;
+use crate::paths::len;
+use crate::paths::split;
+
+/// Flattened sub-path with cumulative distances
+#[derive(Debug, Clone)]
+struct Measured {
+  /// Vertices, a closed sub-path repeats the first vertex at the end
+  points: Vec<Vertex<f64>>,
+  /// Distance from the start to each vertex
+  dist: Vec<f64>,
+  /// Sub-path is closed
+  closed: bool,
+}
+
+impl Measured {
+  fn length(&self) -> f64 {
+    self.dist[self.dist.len() - 1]
+  }
+  /// Index of the segment containing the distance, and the distance along it
+  ///
+  /// The distance must be within the sub-path; vertices belong to the
+  ///   segment that follows them, except the last one
+  fn locate(&self, d: f64) -> (usize, f64) {
+    let n = self.points.len() - 1;
+    let i = self.dist.partition_point(|&di| di <= d).clamp(1, n);
+    (i - 1, d - self.dist[i - 1])
+  }
+  /// Position and direction at a distance within the sub-path
+  fn point_at(&self, d: f64) -> (f64, f64, f64) {
+    let (i, t) = self.locate(d);
+    let (a, b) = (self.points[i], self.points[i + 1]);
+    let k = t / len(&a, &b);
+    (
+      a.x + (b.x - a.x) * k,
+      a.y + (b.y - a.y) * k,
+      (b.y - a.y).atan2(b.x - a.x),
+    )
+  }
+}
+
+/// Arc Length Measurement of a Vertex Source
+///
+/// Curves are flattened and repeated vertices removed when created.
+///   Closed sub-paths include the closing segment and wrap around, so
+///   distances beyond their length continue from the start; open sub-paths
+///   clamp distances to their ends.
+#[derive(Debug, Clone)]
+pub struct PathMeasure {
+  sub_paths: Vec<Measured>,
+}
+
+impl PathMeasure {
+  /// Measure a Vertex Source
+  pub fn new<VS: VertexSource>(source: &VS) -> Self {
+    Self::with_approximation_scale(source, 1.0)
+  }
+  /// Measure a Vertex Source, flattening curves with an approximation scale
+  pub fn with_approximation_scale<VS: VertexSource>(source: &VS, scale: f64) -> Self {
+    let mut curve = ConvCurve::new(source);
+    curve.approximation_scale(scale);
+    let v0 = curve.xconvert();
+    let mut sub_paths = vec![];
+    for (m1, m2) in split(&v0) {
+      let v = &v0[m1..=m2];
+      let closed = v.iter().any(|v| v.cmd == PathCommand::Close);
+      let mut points: Vec<Vertex<f64>> = vec![];
+      for p in v.iter().filter(|p| p.cmd.is_vertex()) {
+        if points.last().is_none_or(|last| len(last, p) > 1e-6) {
+          points.push(Vertex::line_to(p.x, p.y));
+        }
+      }
+      if points.len() < 2 {
+        continue;
+      }
+      if closed && len(&points[0], &points[points.len() - 1]) > 1e-6 {
+        points.push(points[0]);
+      }
+      let mut dist = Vec::with_capacity(points.len());
+      dist.push(0.0);
+      for w in points.windows(2) {
+        dist.push(dist[dist.len() - 1] + len(&w[0], &w[1]));
+      }
+      points[0].cmd = PathCommand::MoveTo;
+      sub_paths.push(Measured { points, dist, closed });
+    }
+    Self { sub_paths }
+  }
+  /// Number of sub-paths with a non-zero length
+  pub fn num_paths(&self) -> usize {
+    self.sub_paths.len()
+  }
+  /// Length of a sub-path, 0.0 if it does not exist
+  pub fn length(&self, path_id: usize) -> f64 {
+    self.sub_paths.get(path_id).map(|s| s.length()).unwrap_or(0.0)
+  }
+  /// Lengths of all sub-paths
+  pub fn lengths(&self) -> Vec<f64> {
+    self.sub_paths.iter().map(|s| s.length()).collect()
+  }
+  /// Total length of all sub-paths
+  pub fn total_length(&self) -> f64 {
+    self.sub_paths.iter().map(|s| s.length()).sum()
+  }
+  /// Check if a sub-path is closed
+  pub fn is_closed(&self, path_id: usize) -> bool {
+    self.sub_paths.get(path_id).is_some_and(|s| s.closed)
+  }
+  /// Position and tangent angle, in radians, at a distance along a sub-path
+  pub fn point_at(&self, path_id: usize, distance: f64) -> Option<(f64, f64, f64)> {
+    let s = self.sub_paths.get(path_id)?;
+    let d = if s.closed {
+      distance.rem_euclid(s.length())
+    } else {
+      distance.clamp(0.0, s.length())
+    };
+    Some(s.point_at(d))
+  }
+  /// Part of a sub-path between two distances
+  ///
+  /// On closed sub-paths the part may cross the starting point, and an
+  ///   `end` before `start` goes around; on open sub-paths the distances are
+  ///   clamped and swapped if needed. The result is always an open path.
+  pub fn sub_path(&self, path_id: usize, start: f64, end: f64) -> Path {
+    let mut out = Path::new();
+    let Some(s) = self.sub_paths.get(path_id) else {
+      return out;
+    };
+    let total = s.length();
+    let (d0, d1) = if s.closed {
+      let d0 = start.rem_euclid(total);
+      let span = end - start;
+      let span = if span.abs() >= total {
+        total
+      } else {
+        span.rem_euclid(total)
+      };
+      (d0, d0 + span)
+    } else {
+      let (a, b) = (start.clamp(0.0, total), end.clamp(0.0, total));
+      (a.min(b), a.max(b))
+    };
+    // Walk the sub-path, more than once around if closed
+    let lap = |d: f64| {
+      if s.closed && total > 0.0 {
+        (d / total).floor()
+      } else {
+        0.0
+      }
+    };
+    let point = |d: f64| {
+      let base = lap(d) * total;
+      let (x, y, _) = s.point_at((d - base).min(total));
+      (x, y)
+    };
+    let (x, y) = point(d0);
+    out.move_to(x, y);
+    let (lap0, lap1) = (lap(d0) as usize, lap(d1) as usize);
+    // The closing vertex is the first one of the next lap
+    let n = if s.closed { s.points.len() - 1 } else { s.points.len() };
+    for k in lap0..=lap1 {
+      let base = k as f64 * total;
+      for (p, &dp) in s.points[..n].iter().zip(&s.dist) {
+        let d = base + dp;
+        if d > d0 && d < d1 {
+          out.line_to(p.x, p.y);
+        }
+      }
+    }
+    let (x, y) = point(d1);
+    out.line_to(x, y);
+    out
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn square() -> Path {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(10.0, 0.0);
+    path.line_to(10.0, 10.0);
+    path.line_to(0.0, 10.0);
+    path.close_polygon();
+    path.move_to(20.0, 0.0);
+    path.line_to(20.0, 5.0);
+    path
+  }
+
+  fn xy(p: &Path) -> Vec<(f64, f64)> {
+    p.vertices.iter().map(|v| (v.x, v.y)).collect()
+  }
+
+  #[test]
+  fn lengths() {
+    let m = PathMeasure::new(&square());
+    assert_eq!(m.num_paths(), 2);
+    assert_eq!(m.lengths(), [40.0, 5.0]);
+    assert_eq!(m.total_length(), 45.0);
+    assert!(m.is_closed(0));
+    assert!(!m.is_closed(1));
+    assert_eq!(m.length(2), 0.0);
+  }
+
+  #[test]
+  fn point_and_tangent() {
+    let m = PathMeasure::new(&square());
+    assert_eq!(m.point_at(0, 5.0), Some((5.0, 0.0, 0.0)));
+    // Vertices take the direction of the following segment
+    assert_eq!(m.point_at(0, 10.0), Some((10.0, 0.0, std::f64::consts::FRAC_PI_2)));
+    // Closing segment, and wrapping around
+    assert_eq!(m.point_at(0, 35.0), Some((0.0, 5.0, -std::f64::consts::FRAC_PI_2)));
+    assert_eq!(m.point_at(0, 45.0), Some((5.0, 0.0, 0.0)));
+    // Open paths clamp
+    assert_eq!(m.point_at(1, 10.0), Some((20.0, 5.0, std::f64::consts::FRAC_PI_2)));
+    assert_eq!(m.point_at(1, -1.0), Some((20.0, 0.0, std::f64::consts::FRAC_PI_2)));
+    assert_eq!(m.point_at(2, 0.0), None);
+  }
+
+  #[test]
+  fn sub_paths() {
+    let m = PathMeasure::new(&square());
+    assert_eq!(xy(&m.sub_path(0, 5.0, 25.0)), [(5.0, 0.0), (10.0, 0.0), (10.0, 10.0), (5.0, 10.0)]);
+    // Across the start of a closed path
+    assert_eq!(xy(&m.sub_path(0, 35.0, 5.0)), [(0.0, 5.0), (0.0, 0.0), (5.0, 0.0)]);
+    // Whole loop
+    assert_eq!(m.sub_path(0, 5.0, 45.0).vertices.len(), 6);
+    // Open path, swapped and clamped
+    assert_eq!(xy(&m.sub_path(1, 4.0, -2.0)), [(20.0, 0.0), (20.0, 4.0)]);
+    let v = m.sub_path(0, 0.0, 10.0).vertices;
+    assert_eq!(v[0].cmd, PathCommand::MoveTo);
+    assert_eq!(v[1].cmd, PathCommand::LineTo);
+  }
+}
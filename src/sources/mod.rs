@@ -1,23 +1,109 @@
+pub mod boolean;
 pub mod clip;
+pub mod clipper;
+pub mod contour;
+pub mod curves;
 pub mod gradient;
+pub mod markers;
+pub mod measure;
 pub mod paths;
+pub mod perspective;
+pub mod segmentator;
+pub mod shapes;
+pub mod smooth;
+pub mod spline;
+pub mod storage;
 pub mod stroke;
 pub mod text;
+pub mod trans_path;
 pub mod transform;
 
+pub use boolean::*;
 pub use clip::*;
+pub use clipper::*;
+pub use contour::*;
+pub use curves::*;
 pub use gradient::*;
+pub use markers::*;
+pub use measure::*;
 pub use paths::*;
+pub use perspective::*;
+pub use segmentator::*;
+pub use shapes::*;
+pub use smooth::*;
+pub use spline::*;
+pub use storage::*;
 pub use stroke::*;
 pub use text::*;
+pub use trans_path::*;
 pub use transform::*;
 
+/// Vertices pulled one at a time from a [`VertexSource`]
+pub type Vertices<'a> = Box<dyn Iterator<Item = Vertex<f64>> + 'a>;
+
 /// Source of vertex points
 pub trait VertexSource {
   /// Rewind the vertex source (unused)
   fn rewind(&self) {}
   /// Get values from the source
-  ///
-  /// This could be turned into an iterator
   fn xconvert(&self) -> Vec<Vertex<f64>>;
+  /// Iterate over the values from the source
+  ///
+  /// By default the values of [`xconvert`](VertexSource::xconvert) are
+  ///   collected first. Converters override this to pull from their own
+  ///   source lazily, so a chain of them streams into the rasterizer
+  ///   without building intermediate vectors.
+  fn vertices(&self) -> Vertices<'_> {
+    Box::new(self.xconvert().into_iter())
+  }
+  /// Apply a transform, see [`ConvTransform`]
+  fn transformed<Tr: Transformer>(self, trans: Tr) -> ConvTransform<Self, Tr>
+  where
+    Self: Sized,
+  {
+    ConvTransform::new(self, trans)
+  }
+  /// Stroke with a line width, see [`Stroke`]
+  ///
+  /// Other stroke settings can be changed on the result
+  fn stroked(self, width: f64) -> Stroke<Self>
+  where
+    Self: Sized,
+  {
+    let mut stroke = Stroke::new(self);
+    stroke.width(width);
+    stroke
+  }
+  /// Cut into dashes, given as pairs of dash and gap lengths, see [`Dash`]
+  fn dashed(self, dashes: &[(f64, f64)]) -> Dash<Self>
+  where
+    Self: Sized,
+  {
+    let mut dash = Dash::new(self);
+    for &(length, gap) in dashes {
+      dash.add_dash(length, gap);
+    }
+    dash
+  }
+  /// Offset closed polygons by a distance, see [`Contour`]
+  fn contoured(self, width: f64) -> Contour<Self>
+  where
+    Self: Sized,
+  {
+    let mut contour = Contour::new(self);
+    contour.width(width);
+    contour
+  }
+}
+
+impl<T: VertexSource + ?Sized> VertexSource for &T {
+  fn rewind(&self) {
+    (**self).rewind()
+  }
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    (**self).xconvert()
+  }
+  fn vertices(&self) -> Vertices<'_> {
+    (**self).vertices()
+  }
 }
@@ -1,5 +1,8 @@
+use crate::FillingRule;
 use crate::VertexSource;
+use crate::Vertices;
 use crate::clip::Rectangle;
+use crate::curves::ConvCurve;
 
 #[derive(Debug, Copy, Clone, PartialEq, Default)]
 pub enum PathCommand {
@@ -8,8 +11,10 @@ pub enum PathCommand {
   MoveTo,
   LineTo,
   Close,
-  //Curve3,
-  //Curve4,
+  /// Quadratic Bezier, control point followed by the end point
+  Curve3,
+  /// Cubic Bezier, two control points followed by the end point
+  Curve4,
   //CurveN,
   //Catrom,
   //UBSpline,
@@ -55,6 +60,34 @@ impl<T> Vertex<T> {
       cmd: PathCommand::Close,
     }
   }
+  pub fn curve3(x: T, y: T) -> Self {
+    Self {
+      x,
+      y,
+      cmd: PathCommand::Curve3,
+    }
+  }
+  pub fn curve4(x: T, y: T) -> Self {
+    Self {
+      x,
+      y,
+      cmd: PathCommand::Curve4,
+    }
+  }
+}
+
+impl PathCommand {
+  /// Command is a Curve3 or Curve4
+  pub fn is_curve(&self) -> bool {
+    matches!(self, PathCommand::Curve3 | PathCommand::Curve4)
+  }
+  /// Command carries a vertex position: MoveTo, LineTo, Curve3 or Curve4
+  pub fn is_vertex(&self) -> bool {
+    matches!(
+      self,
+      PathCommand::MoveTo | PathCommand::LineTo | PathCommand::Curve3 | PathCommand::Curve4
+    )
+  }
 }
 
 /// Compute length between two points
@@ -74,8 +107,51 @@ pub fn cross(p1: &Vertex<f64>, p2: &Vertex<f64>, p: &Vertex<f64>) -> f64 {
   (p.x - p2.x) * (p2.y - p1.y) - (p.y - p2.y) * (p2.x - p1.x)
 }
 
+/// Shorten an open polyline by distances at its start and end
+///
+/// Vertices are moved along the polyline or removed, it is emptied if it is
+///   not longer than both distances together. The first vertex stays a
+///   `MoveTo`.
+pub(crate) fn shorten_path(v: &mut Vec<Vertex<f64>>, start: f64, end: f64) {
+  let (start, end) = (start.max(0.0), end.max(0.0));
+  if start == 0.0 && end == 0.0 {
+    return;
+  }
+  let total: f64 = v.windows(2).map(|w| len(&w[0], &w[1])).sum();
+  if start + end >= total {
+    v.clear();
+    return;
+  }
+  // Remove a distance from the end
+  let trim = |v: &mut Vec<Vertex<f64>>, mut s: f64| {
+    while s > 0.0 && v.len() > 1 {
+      let n = v.len();
+      let d = len(&v[n - 2], &v[n - 1]);
+      if d > s {
+        let k = s / d;
+        v[n - 1].x -= (v[n - 1].x - v[n - 2].x) * k;
+        v[n - 1].y -= (v[n - 1].y - v[n - 2].y) * k;
+        break;
+      }
+      s -= d;
+      v.pop();
+    }
+  };
+  trim(v, end);
+  v.reverse();
+  trim(v, start);
+  v.reverse();
+  for (i, p) in v.iter_mut().enumerate() {
+    p.cmd = if i == 0 {
+      PathCommand::MoveTo
+    } else {
+      PathCommand::LineTo
+    };
+  }
+}
+
 //  typedef path_base<vertex_block_storage<double> > path_storage;
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Path {
   pub vertices: Vec<Vertex<f64>>,
 }
@@ -84,6 +160,9 @@ impl VertexSource for Path {
   fn xconvert(&self) -> Vec<Vertex<f64>> {
     self.vertices.clone()
   }
+  fn vertices(&self) -> Vertices<'_> {
+    Box::new(self.vertices.iter().copied())
+  }
 }
 
 impl Path {
@@ -101,13 +180,104 @@ impl Path {
     //self.vertices.push( Vertex::new(x,y, PathCommand::LineTo) );
     self.vertices.push(Vertex::line_to(x, y));
   }
+  /// Quadratic Bezier curve from the current point to (`x_to`,`y_to`)
+  pub fn curve3(&mut self, x_ctrl: f64, y_ctrl: f64, x_to: f64, y_to: f64) {
+    self.vertices.push(Vertex::curve3(x_ctrl, y_ctrl));
+    self.vertices.push(Vertex::curve3(x_to, y_to));
+  }
+  /// Smooth Quadratic Bezier curve from the current point to (`x_to`,`y_to`)
+  ///
+  /// The control point is the reflection of the previous control point about
+  /// the current point, or the current point if the previous segment was not
+  /// a curve. After a Close the current point is the start of the sub-path.
+  pub fn curve3_smooth(&mut self, x_to: f64, y_to: f64) {
+    if let Some((x_ctrl, y_ctrl)) = self.reflected_control() {
+      self.curve3(x_ctrl, y_ctrl, x_to, y_to);
+    }
+  }
+  /// Cubic Bezier curve from the current point to (`x_to`,`y_to`)
+  pub fn curve4(&mut self, x_ctrl1: f64, y_ctrl1: f64, x_ctrl2: f64, y_ctrl2: f64, x_to: f64, y_to: f64) {
+    self.vertices.push(Vertex::curve4(x_ctrl1, y_ctrl1));
+    self.vertices.push(Vertex::curve4(x_ctrl2, y_ctrl2));
+    self.vertices.push(Vertex::curve4(x_to, y_to));
+  }
+  /// Smooth Cubic Bezier curve from the current point to (`x_to`,`y_to`)
+  ///
+  /// The first control point is the reflection of the previous control point
+  /// about the current point, or the current point if the previous segment
+  /// was not a curve. After a Close the current point is the start of the
+  /// sub-path.
+  pub fn curve4_smooth(&mut self, x_ctrl2: f64, y_ctrl2: f64, x_to: f64, y_to: f64) {
+    if let Some((x_ctrl1, y_ctrl1)) = self.reflected_control() {
+      self.curve4(x_ctrl1, y_ctrl1, x_ctrl2, y_ctrl2, x_to, y_to);
+    }
+  }
+  /// Elliptical arc from the current point to (`x`,`y`)
+  ///
+  /// Uses the SVG endpoint parameterization, see [`BezierArcSvg`]; the arc is
+  /// stored as `Curve4` commands. `x_axis_rotation` is in radians.
+  ///
+  /// Radii too small to reach (`x`,`y`) are scaled up. A zero radius draws a
+  /// line and an end point equal to the current point draws nothing. Without
+  /// a current point this is a MoveTo.
+  pub fn arc_to(&mut self, rx: f64, ry: f64, x_axis_rotation: f64, large_arc: bool, sweep: bool, x: f64, y: f64) {
+    let Some((x0, y0)) = self.current_point() else {
+      self.move_to(x, y);
+      return;
+    };
+    let (rx, ry) = (rx.abs(), ry.abs());
+    let line = rx < ARC_EPSILON || ry < ARC_EPSILON;
+    if !line && (x - x0).hypot(y - y0) < ARC_EPSILON {
+      return;
+    }
+    self.reopen();
+    if line {
+      self.line_to(x, y);
+      return;
+    }
+    let arc = BezierArcSvg::init(x0, y0, rx, ry, x_axis_rotation, large_arc, sweep, x, y);
+    // First vertex is the current point
+    self.vertices.extend_from_slice(&arc.xconvert()[1..]);
+  }
+  /// Current point, the start of the sub-path after a Close as in SVG
+  fn current_point(&self) -> Option<(f64, f64)> {
+    let last = self.vertices.last()?;
+    if last.cmd.is_vertex() {
+      return Some((last.x, last.y));
+    }
+    if last.cmd != PathCommand::Close {
+      return None;
+    }
+    let start = self.vertices.iter().rev().find(|v| v.cmd == PathCommand::MoveTo)?;
+    Some((start.x, start.y))
+  }
+  /// Start a new sub-path at the current point if the last one was closed
+  fn reopen(&mut self) {
+    if self.vertices.last().is_some_and(|v| v.cmd == PathCommand::Close)
+      && let Some((x, y)) = self.current_point()
+    {
+      self.move_to(x, y);
+    }
+  }
+  /// Control point for a smooth curve, None if there is no current point
+  fn reflected_control(&mut self) -> Option<(f64, f64)> {
+    self.current_point()?;
+    self.reopen();
+    let n = self.vertices.len();
+    let last = self.vertices[n - 1];
+    match n.checked_sub(2).map(|i| self.vertices[i]) {
+      Some(prev) if prev.cmd.is_curve() => Some((last.x + last.x - prev.x, last.y + last.y - prev.y)),
+      _ => Some((last.x, last.y)),
+    }
+  }
   pub fn close_polygon(&mut self) {
     if self.vertices.is_empty() {
       return;
     }
     let n = self.vertices.len();
     let last = self.vertices[n - 1];
-    if last.cmd == PathCommand::LineTo {
+    // A lone MoveTo is closed too, a sub-path of zero length
+    if last.cmd.is_vertex() {
       self.vertices.push(Vertex::close_polygon(last.x, last.y));
     }
   }
@@ -132,13 +302,13 @@ pub fn split(path: &[Vertex<f64>]) -> Vec<(usize, usize)> {
         PathCommand::MoveTo => {
           start = Some(i);
         }
-        PathCommand::LineTo | PathCommand::Close | PathCommand::Stop => {}
+        PathCommand::LineTo | PathCommand::Curve3 | PathCommand::Curve4 | PathCommand::Close | PathCommand::Stop => {}
       },
       (Some(_), None) => match v.cmd {
         PathCommand::MoveTo => {
           start = Some(i);
         }
-        PathCommand::LineTo => {
+        PathCommand::LineTo | PathCommand::Curve3 | PathCommand::Curve4 => {
           end = Some(i);
         }
         PathCommand::Close | PathCommand::Stop => end = Some(i),
@@ -149,7 +319,9 @@ pub fn split(path: &[Vertex<f64>]) -> Vec<(usize, usize)> {
           start = Some(i);
           end = None;
         }
-        PathCommand::LineTo | PathCommand::Close | PathCommand::Stop => end = Some(i),
+        PathCommand::LineTo | PathCommand::Curve3 | PathCommand::Curve4 | PathCommand::Close | PathCommand::Stop => {
+          end = Some(i)
+        }
       },
       (None, Some(_)) => unreachable!("oh on bad state!"),
     }
@@ -160,6 +332,39 @@ pub fn split(path: &[Vertex<f64>]) -> Vec<(usize, usize)> {
   pairs
 }
 
+/// Sub-paths of a stream of vertices, one at a time, as given by [`split`]
+pub(crate) fn sub_paths(src: Vertices<'_>) -> SubPaths<'_> {
+  SubPaths { src, start: None }
+}
+
+/// Iterator over the sub-paths of a stream of vertices, see [`sub_paths`]
+pub(crate) struct SubPaths<'a> {
+  /// Source vertices
+  src: Vertices<'a>,
+  /// `MoveTo` starting the next sub-path, read at the end of the last one
+  start: Option<Vertex<f64>>,
+}
+
+impl Iterator for SubPaths<'_> {
+  type Item = Vec<Vertex<f64>>;
+  fn next(&mut self) -> Option<Self::Item> {
+    let mut sub: Vec<_> = self.start.take().into_iter().collect();
+    for v in self.src.by_ref() {
+      if v.cmd == PathCommand::MoveTo {
+        if sub.len() > 1 {
+          self.start = Some(v);
+          return Some(sub);
+        }
+        // Repeated MoveTo, the last one starts the sub-path
+        sub = vec![v];
+      } else if !sub.is_empty() {
+        sub.push(v);
+      }
+    }
+    (sub.len() > 1).then_some(sub)
+  }
+}
+
 fn arrange_orientations(path: &mut Path, dir: PathOrientation) {
   let pairs = split(&path.vertices);
   for (s, e) in pairs {
@@ -214,6 +419,72 @@ pub fn bounding_rect<VS: VertexSource>(path: &VS) -> Option<Rectangle<f64>> {
     Some(r)
   }
 }
+/// Check if the point (x,y) is inside the filled Vertex Source
+///
+/// Sub-paths are closed implicitly, as when rasterized, and curves are
+///   flattened. Points exactly on an edge may be either inside or outside.
+pub fn contains<VS: VertexSource>(path: &VS, x: f64, y: f64, rule: FillingRule) -> bool {
+  filled(winding(&edges(path), x, y), rule)
+}
+
+/// Edge from (x1,y1) to (x2,y2)
+pub(crate) type Edge = (f64, f64, f64, f64);
+
+/// Edges of all closed contours, curves are flattened
+pub(crate) fn edges<VS: VertexSource>(vs: &VS) -> Vec<Edge> {
+  let mut out = vec![];
+  let mut contour: Vec<(f64, f64)> = vec![];
+  let mut close = |contour: &mut Vec<(f64, f64)>| {
+    if contour.len() > 2 {
+      for (k, &(x1, y1)) in contour.iter().enumerate() {
+        let (x2, y2) = contour[(k + 1) % contour.len()];
+        if (x1, y1) != (x2, y2) {
+          out.push((x1, y1, x2, y2));
+        }
+      }
+    }
+    contour.clear();
+  };
+  for v in ConvCurve::new(vs).vertices() {
+    match v.cmd {
+      PathCommand::MoveTo => {
+        close(&mut contour);
+        contour.push((v.x, v.y));
+      }
+      PathCommand::LineTo => contour.push((v.x, v.y)),
+      PathCommand::Close => close(&mut contour),
+      PathCommand::Stop => {}
+      PathCommand::Curve3 | PathCommand::Curve4 => unreachable!("curves are flattened"),
+    }
+  }
+  close(&mut contour);
+  out
+}
+
+/// Winding number of the edges around (x,y)
+pub(crate) fn winding(edges: &[Edge], x: f64, y: f64) -> i32 {
+  let mut w = 0;
+  for &(x1, y1, x2, y2) in edges {
+    let side = (x2 - x1) * (y - y1) - (x - x1) * (y2 - y1);
+    if y1 <= y {
+      if y2 > y && side > 0.0 {
+        w += 1;
+      }
+    } else if y2 <= y && side < 0.0 {
+      w -= 1;
+    }
+  }
+  w
+}
+
+/// If a winding number is inside with the filling rule
+pub(crate) fn filled(winding: i32, rule: FillingRule) -> bool {
+  match rule {
+    FillingRule::NonZero => winding != 0,
+    FillingRule::EvenOdd => winding % 2 != 0,
+  }
+}
+
 #[derive(Debug, Default)]
 pub struct Ellipse {
   x: f64,
@@ -231,6 +502,9 @@ impl VertexSource for Ellipse {
   fn xconvert(&self) -> Vec<Vertex<f64>> {
     self.vertices.clone()
   }
+  fn vertices(&self) -> Vertices<'_> {
+    Box::new(self.vertices.iter().copied())
+  }
 }
 
 use std::f64::consts::PI;
@@ -295,6 +569,9 @@ impl VertexSource for RoundedRect {
   fn xconvert(&self) -> Vec<Vertex<f64>> {
     self.vertices.clone()
   }
+  fn vertices(&self) -> Vertices<'_> {
+    Box::new(self.vertices.iter().copied())
+  }
 }
 
 impl RoundedRect {
@@ -439,3 +716,289 @@ impl Arc {
     self.end = a2;
   }
 }
+
+/// Smallest radius or distance considered by [`Path::arc_to`]
+const ARC_EPSILON: f64 = 1e-30;
+
+/// Sections are extended to cover a remaining sweep below this angle
+const BEZIER_ARC_ANGLE_EPSILON: f64 = 0.01;
+
+/// Convert an elliptical arc section to a cubic bezier, four points
+fn arc_to_bezier(cx: f64, cy: f64, rx: f64, ry: f64, start: f64, sweep: f64) -> [Vertex<f64>; 4] {
+  let x0 = (sweep / 2.0).cos();
+  let y0 = (sweep / 2.0).sin();
+  let tx = (1.0 - x0) * 4.0 / 3.0;
+  let ty = y0 - tx * x0 / y0;
+  let px = [x0, x0 + tx, x0 + tx, x0];
+  let py = [-y0, -ty, ty, y0];
+  let sn = (start + sweep / 2.0).sin();
+  let cs = (start + sweep / 2.0).cos();
+  std::array::from_fn(|i| Vertex::curve4(cx + rx * (px[i] * cs - py[i] * sn), cy + ry * (px[i] * sn + py[i] * cs)))
+}
+
+/// Elliptical arc approximated with cubic Bezier curves
+///
+/// The arc is split into sections of at most 90 degrees, a full ellipse is
+/// four curves. A MoveTo to the start of the arc is followed by `Curve4`
+/// commands, or a single LineTo if the sweep is negligible.
+#[derive(Debug, Clone)]
+pub struct BezierArc {
+  vertices: Vec<Vertex<f64>>,
+}
+
+impl VertexSource for BezierArc {
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    self.vertices.clone()
+  }
+}
+
+impl BezierArc {
+  /// Arc with center (`x`,`y`) from `start` sweeping through `sweep` radians
+  ///
+  /// Positive sweep angles are counter-clockwise in a y-up coordinate system
+  pub fn init(x: f64, y: f64, rx: f64, ry: f64, start: f64, sweep: f64) -> Self {
+    let mut start = start % (2.0 * PI);
+    let sweep = sweep.clamp(-2.0 * PI, 2.0 * PI);
+    if sweep.abs() < 1e-10 {
+      let vertices = vec![
+        Vertex::move_to(x + rx * start.cos(), y + ry * start.sin()),
+        Vertex::line_to(x + rx * (start + sweep).cos(), y + ry * (start + sweep).sin()),
+      ];
+      return Self { vertices };
+    }
+    let mut vertices = vec![];
+    let mut total = 0.0;
+    let step = PI * 0.5 * sweep.signum();
+    loop {
+      let prev = total;
+      total += step;
+      let done = (total - sweep).abs() <= BEZIER_ARC_ANGLE_EPSILON || (total - sweep) * step.signum() > 0.0;
+      let local = if done { sweep - prev } else { step };
+      let curve = arc_to_bezier(x, y, rx, ry, start, local);
+      if vertices.is_empty() {
+        vertices.push(Vertex::move_to(curve[0].x, curve[0].y));
+      }
+      vertices.extend_from_slice(&curve[1..]);
+      start += local;
+      if done || vertices.len() >= 13 {
+        break;
+      }
+    }
+    Self { vertices }
+  }
+}
+
+/// Elliptical arc using SVG endpoint parameterization
+///
+/// The arc runs from (`x0`,`y0`) to (`x2`,`y2`) on an ellipse with radii
+/// `rx` and `ry`, rotated by `angle` radians. Of the four possible arcs,
+/// `large_arc` selects the one sweeping more than 180 degrees and `sweep`
+/// the one running in the positive angle direction. Radii too small to
+/// connect the points are scaled up. The arc ends exactly at the end points.
+/// Zero radii or identical end points give a straight line between them.
+///
+/// See [SVG Arc Implementation Notes](https://www.w3.org/TR/SVG/implnote.html#ArcImplementationNotes)
+#[derive(Debug, Clone)]
+pub struct BezierArcSvg {
+  arc: BezierArc,
+}
+
+impl VertexSource for BezierArcSvg {
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    self.arc.xconvert()
+  }
+}
+
+impl BezierArcSvg {
+  pub fn init(x0: f64, y0: f64, rx: f64, ry: f64, angle: f64, large_arc: bool, sweep: bool, x2: f64, y2: f64) -> Self {
+    let mut rx = rx.abs();
+    let mut ry = ry.abs();
+    // No ellipse through the points, as in Path::arc_to
+    if rx < ARC_EPSILON || ry < ARC_EPSILON || (x2 - x0).hypot(y2 - y0) < ARC_EPSILON {
+      let vertices = vec![Vertex::move_to(x0, y0), Vertex::line_to(x2, y2)];
+      return Self {
+        arc: BezierArc { vertices },
+      };
+    }
+    // Midpoint between the end points, in the rotated frame
+    let dx2 = (x0 - x2) / 2.0;
+    let dy2 = (y0 - y2) / 2.0;
+    let (sin_a, cos_a) = angle.sin_cos();
+    let x1 = cos_a * dx2 + sin_a * dy2;
+    let y1 = -sin_a * dx2 + cos_a * dy2;
+    // Ensure radii are large enough
+    let (px1, py1) = (x1 * x1, y1 * y1);
+    let check = px1 / (rx * rx) + py1 / (ry * ry);
+    if check > 1.0 {
+      rx *= check.sqrt();
+      ry *= check.sqrt();
+    }
+    let (prx, pry) = (rx * rx, ry * ry);
+    // Center of the ellipse
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let sq = (prx * pry - prx * py1 - pry * px1) / (prx * py1 + pry * px1);
+    let coef = sign * sq.max(0.0).sqrt();
+    let cx1 = coef * ((rx * y1) / ry);
+    let cy1 = coef * -((ry * x1) / rx);
+    let cx = (x0 + x2) / 2.0 + (cos_a * cx1 - sin_a * cy1);
+    let cy = (y0 + y2) / 2.0 + (sin_a * cx1 + cos_a * cy1);
+    // Start and sweep angles
+    let ux = (x1 - cx1) / rx;
+    let uy = (y1 - cy1) / ry;
+    let vx = (-x1 - cx1) / rx;
+    let vy = (-y1 - cy1) / ry;
+    let sign = if uy < 0.0 { -1.0 } else { 1.0 };
+    let start = sign * (ux / ux.hypot(uy)).clamp(-1.0, 1.0).acos();
+    let n = ((ux * ux + uy * uy) * (vx * vx + vy * vy)).sqrt();
+    let sign = if ux * vy - uy * vx < 0.0 { -1.0 } else { 1.0 };
+    let mut dangle = sign * ((ux * vx + uy * vy) / n).clamp(-1.0, 1.0).acos();
+    if !sweep && dangle > 0.0 {
+      dangle -= 2.0 * PI;
+    } else if sweep && dangle < 0.0 {
+      dangle += 2.0 * PI;
+    }
+    let mut arc = BezierArc::init(0.0, 0.0, rx, ry, start, dangle);
+    for v in &mut arc.vertices {
+      (v.x, v.y) = (cx + cos_a * v.x - sin_a * v.y, cy + sin_a * v.x + cos_a * v.y);
+    }
+    // End points must coincide exactly with the input
+    let n = arc.vertices.len();
+    (arc.vertices[0].x, arc.vertices[0].y) = (x0, y0);
+    (arc.vertices[n - 1].x, arc.vertices[n - 1].y) = (x2, y2);
+    Self { arc }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn xy(p: &Path) -> Vec<(f64, f64)> {
+    p.vertices.iter().map(|v| (v.x, v.y)).collect()
+  }
+
+  #[test]
+  fn test_sub_paths() {
+    // Leading LineTo, repeated MoveTo, vertices after a Close and a lone MoveTo
+    let v = vec![
+      Vertex::line_to(9.0, 9.0),
+      Vertex::move_to(0.0, 0.0),
+      Vertex::move_to(1.0, 0.0),
+      Vertex::line_to(2.0, 0.0),
+      Vertex::close_polygon(2.0, 0.0),
+      Vertex::line_to(3.0, 0.0),
+      Vertex::move_to(4.0, 0.0),
+      Vertex::line_to(5.0, 0.0),
+      Vertex::move_to(6.0, 0.0),
+    ];
+    let streamed: Vec<Vec<f64>> = sub_paths(Box::new(v.clone().into_iter()))
+      .map(|s| s.iter().map(|v| v.x).collect())
+      .collect();
+    let split: Vec<Vec<f64>> = split(&v)
+      .into_iter()
+      .map(|(m1, m2)| v[m1..=m2].iter().map(|v| v.x).collect())
+      .collect();
+    assert_eq!(streamed, split);
+    assert_eq!(streamed, [vec![1.0, 2.0, 2.0, 3.0], vec![4.0, 5.0]]);
+  }
+
+  #[test]
+  fn test_bezier_arc() {
+    // Full circle is four curves
+    let a = BezierArc::init(0.0, 0.0, 1.0, 1.0, 0.0, 2.0 * PI).xconvert();
+    assert_eq!(a.len(), 13);
+    assert_eq!(a[0].cmd, PathCommand::MoveTo);
+    assert!(a[1..].iter().all(|v| v.cmd == PathCommand::Curve4));
+    assert!((a[12].x - 1.0).abs() < 1e-9 && a[12].y.abs() < 1e-9);
+    // Remaining sweep is a shorter section, negative sweeps go clockwise
+    let a = BezierArc::init(0.0, 0.0, 2.0, 1.0, 0.0, -0.75 * PI).xconvert();
+    assert_eq!(a.len(), 7);
+    assert!((a[3].x.abs() < 1e-9) && (a[3].y + 1.0).abs() < 1e-9);
+    // Negligible sweep is a line
+    let a = BezierArc::init(0.0, 0.0, 1.0, 1.0, 0.0, 0.0).xconvert();
+    assert_eq!(a.len(), 2);
+    assert_eq!(a[1].cmd, PathCommand::LineTo);
+  }
+
+  #[test]
+  fn test_arc_to() {
+    // Quarter of a circle, sweep in the positive angle direction
+    let mut p = Path::new();
+    p.move_to(10.0, 0.0);
+    p.arc_to(10.0, 10.0, 0.0, false, true, 0.0, 10.0);
+    assert_eq!(p.vertices.len(), 4);
+    assert_eq!(xy(&p)[3], (0.0, 10.0));
+    let k = 10.0 * 4.0 / 3.0 * (2f64.sqrt() - 1.0);
+    assert!((p.vertices[1].x - 10.0).abs() < 1e-9 && (p.vertices[1].y - k).abs() < 1e-9);
+
+    // Large arc takes the long way round the other center, three quarters
+    let mut p = Path::new();
+    p.move_to(10.0, 0.0);
+    p.arc_to(10.0, 10.0, 0.0, true, true, 0.0, 10.0);
+    assert_eq!(p.vertices.len(), 10);
+    assert!(
+      xy(&p)
+        .iter()
+        .any(|&(x, y)| (x - 20.0).abs() < 1e-9 && (y - 10.0).abs() < 1e-9)
+    );
+
+    // Rotated ellipse, radii scaled up so the points are on a diameter
+    let mut p = Path::new();
+    p.move_to(0.0, 0.0);
+    p.arc_to(1.0, 0.5, PI / 2.0, false, false, 0.0, 20.0);
+    assert_eq!(xy(&p)[p.vertices.len() - 1], (0.0, 20.0));
+    let (x, y) = xy(&p)[3];
+    assert!((x + 5.0).abs() < 1e-9 && (y - 10.0).abs() < 1e-9);
+
+    // Degenerate cases
+    let mut p = Path::new();
+    p.arc_to(1.0, 1.0, 0.0, false, false, 5.0, 5.0);
+    assert_eq!(p.vertices[0].cmd, PathCommand::MoveTo);
+    p.arc_to(1.0, 1.0, 0.0, false, false, 5.0, 5.0);
+    assert_eq!(p.vertices.len(), 1);
+    p.arc_to(0.0, 1.0, 0.0, false, false, 6.0, 5.0);
+    assert_eq!(p.vertices[1].cmd, PathCommand::LineTo);
+  }
+
+  #[test]
+  fn test_after_close() {
+    // Current point after a Close is the start of the sub-path, a new one
+    //   starts there
+    let closed = || {
+      let mut p = Path::new();
+      p.move_to(10.0, 0.0);
+      p.line_to(20.0, 0.0);
+      p.line_to(20.0, 10.0);
+      p.close_polygon();
+      p
+    };
+    let mut p = closed();
+    p.curve3_smooth(0.0, 0.0);
+    assert_eq!(p.vertices[4].cmd, PathCommand::MoveTo);
+    assert_eq!(xy(&p)[4..], [(10.0, 0.0), (10.0, 0.0), (0.0, 0.0)]);
+    let mut p = closed();
+    p.curve4_smooth(5.0, 5.0, 0.0, 0.0);
+    assert_eq!(xy(&p)[4..], [(10.0, 0.0), (10.0, 0.0), (5.0, 5.0), (0.0, 0.0)]);
+    let mut p = closed();
+    p.arc_to(10.0, 10.0, 0.0, false, true, 0.0, 10.0);
+    assert_eq!(p.vertices.len(), 8);
+    assert_eq!(p.vertices[4].cmd, PathCommand::MoveTo);
+    assert_eq!(xy(&p)[4], (10.0, 0.0));
+    assert_eq!(xy(&p)[7], (0.0, 10.0));
+    // Nothing to draw, no new sub-path
+    let mut p = closed();
+    p.arc_to(1.0, 1.0, 0.0, false, false, 10.0, 0.0);
+    assert_eq!(p.vertices.len(), 4);
+  }
+
+  #[test]
+  fn test_bezier_arc_svg_degenerate() {
+    // Identical end points and zero radii give a line, without NaN
+    let a = BezierArcSvg::init(0.0, 0.0, 1.0, 1.0, 0.0, false, false, 0.0, 0.0).xconvert();
+    assert_eq!(a.len(), 2);
+    assert_eq!((a[1].cmd, a[1].x, a[1].y), (PathCommand::LineTo, 0.0, 0.0));
+    let a = BezierArcSvg::init(0.0, 0.0, 0.0, 1.0, 0.0, true, true, 5.0, 5.0).xconvert();
+    assert_eq!(a.len(), 2);
+    assert_eq!((a[1].cmd, a[1].x, a[1].y), (PathCommand::LineTo, 5.0, 5.0));
+  }
+}
@@ -0,0 +1,331 @@
+//! Non-linear Transformations
+//!
+//! Perspective and bilinear maps between quadrilaterals, ports of AGG's
+//!   `trans_perspective` and `trans_bilinear`
+//!
+//! # Example
+//!
+//!     let mut path = agg::Path::new();
+//!     path.move_to(  0.0,   0.0);
+//!     path.line_to(100.0,   0.0);
+//!     path.line_to(100.0, 100.0);
+//!     path.line_to(  0.0, 100.0);
+//!     path.close_polygon();
+//!
+//!     // Map the square onto a trapezoid
+//!     let quad = [(20.0, 80.0), (80.0, 80.0), (100.0, 10.0), (0.0, 10.0)];
+//!     let persp = agg::TransPerspective::rect_to_quad(0.0, 0.0, 100.0, 100.0, &quad).unwrap();
+//!
+//!     // Subdivide the edges so they bend, then transform
+//!     let mut segments = agg::ConvSegmentator::new(&path);
+//!     segments.approximation_scale(0.25);
+//!     let shape = agg::ConvTransform::new(segments, persp);
+//!
+//!     // Draw
+//!     // let mut ras = agg::RasterizerScanline::new();
+//!     // ras.add_path(&shape);
+
+use crate::Transform;
+use crate::Transformer;
+
+/// Corners of a rectangle, in the order used for quadrilaterals
+fn rect_corners(x1: f64, y1: f64, x2: f64, y2: f64) -> [(f64, f64); 4] {
+  [(x1, y1), (x2, y1), (x2, y2), (x1, y2)]
+}
+
+/// Perspective Transformation
+///
+/// A 3x3 matrix, points are divided by `x * w0 + y * w1 + w2`. Straight lines
+///   stay straight, but evenly spaced points do not.
+///
+/// Quadrilaterals are given as four corners, the first one maps to the
+///   top-left corner of a rectangle, going towards increasing x.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TransPerspective {
+  pub sx: f64,
+  pub shy: f64,
+  pub w0: f64,
+  pub shx: f64,
+  pub sy: f64,
+  pub w1: f64,
+  pub tx: f64,
+  pub ty: f64,
+  pub w2: f64,
+}
+
+impl Default for TransPerspective {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl From<Transform> for TransPerspective {
+  fn from(t: Transform) -> Self {
+    Self {
+      sx: t.sx,
+      shy: t.shy,
+      w0: 0.0,
+      shx: t.shx,
+      sy: t.sy,
+      w1: 0.0,
+      tx: t.tx,
+      ty: t.ty,
+      w2: 1.0,
+    }
+  }
+}
+
+impl TransPerspective {
+  /// Creates a new identity Perspective Transform
+  pub fn new() -> Self {
+    Transform::new().into()
+  }
+  /// Map the unit square onto a quadrilateral
+  ///
+  /// Returns `None` if the quadrilateral is degenerate
+  pub fn square_to_quad(q: &[(f64, f64); 4]) -> Option<Self> {
+    let dx = q[0].0 - q[1].0 + q[2].0 - q[3].0;
+    let dy = q[0].1 - q[1].1 + q[2].1 - q[3].1;
+    if dx == 0.0 && dy == 0.0 {
+      // Parallelogram, affine
+      return Some(Self {
+        sx: q[1].0 - q[0].0,
+        shy: q[1].1 - q[0].1,
+        w0: 0.0,
+        shx: q[2].0 - q[1].0,
+        sy: q[2].1 - q[1].1,
+        w1: 0.0,
+        tx: q[0].0,
+        ty: q[0].1,
+        w2: 1.0,
+      });
+    }
+    let dx1 = q[1].0 - q[2].0;
+    let dy1 = q[1].1 - q[2].1;
+    let dx2 = q[3].0 - q[2].0;
+    let dy2 = q[3].1 - q[2].1;
+    let den = dx1 * dy2 - dx2 * dy1;
+    if den == 0.0 {
+      return None;
+    }
+    let u = (dx * dy2 - dy * dx2) / den;
+    let v = (dy * dx1 - dx * dy1) / den;
+    Some(Self {
+      sx: q[1].0 - q[0].0 + u * q[1].0,
+      shy: q[1].1 - q[0].1 + u * q[1].1,
+      w0: u,
+      shx: q[3].0 - q[0].0 + v * q[3].0,
+      sy: q[3].1 - q[0].1 + v * q[3].1,
+      w1: v,
+      tx: q[0].0,
+      ty: q[0].1,
+      w2: 1.0,
+    })
+  }
+  /// Map a quadrilateral onto the unit square
+  pub fn quad_to_square(q: &[(f64, f64); 4]) -> Option<Self> {
+    Self::square_to_quad(q)?.invert()
+  }
+  /// Map one quadrilateral onto another
+  pub fn quad_to_quad(src: &[(f64, f64); 4], dst: &[(f64, f64); 4]) -> Option<Self> {
+    Some(Self::quad_to_square(src)?.then(Self::square_to_quad(dst)?))
+  }
+  /// Map a rectangle onto a quadrilateral
+  pub fn rect_to_quad(x1: f64, y1: f64, x2: f64, y2: f64, q: &[(f64, f64); 4]) -> Option<Self> {
+    Self::quad_to_quad(&rect_corners(x1, y1, x2, y2), q)
+  }
+  /// Map a quadrilateral onto a rectangle
+  pub fn quad_to_rect(q: &[(f64, f64); 4], x1: f64, y1: f64, x2: f64, y2: f64) -> Option<Self> {
+    Self::quad_to_quad(q, &rect_corners(x1, y1, x2, y2))
+  }
+  /// Inverse transform, `None` if the matrix is singular
+  pub fn invert(&self) -> Option<Self> {
+    let d0 = self.sy * self.w2 - self.w1 * self.ty;
+    let d1 = self.w0 * self.ty - self.shy * self.w2;
+    let d2 = self.shy * self.w1 - self.w0 * self.sy;
+    let d = self.sx * d0 + self.shx * d1 + self.tx * d2;
+    if d == 0.0 {
+      return None;
+    }
+    let d = 1.0 / d;
+    let a = self;
+    Some(Self {
+      sx: d * d0,
+      shy: d * d1,
+      w0: d * d2,
+      shx: d * (a.w1 * a.tx - a.shx * a.w2),
+      sy: d * (a.sx * a.w2 - a.w0 * a.tx),
+      w1: d * (a.w0 * a.shx - a.sx * a.w1),
+      tx: d * (a.shx * a.ty - a.sy * a.tx),
+      ty: d * (a.shy * a.tx - a.sx * a.ty),
+      w2: d * (a.sx * a.sy - a.shy * a.shx),
+    })
+  }
+  /// Apply this transform, then another one
+  #[must_use]
+  pub fn then(self, a: Self) -> Self {
+    let b = self;
+    Self {
+      sx: a.sx * b.sx + a.shx * b.shy + a.tx * b.w0,
+      shx: a.sx * b.shx + a.shx * b.sy + a.tx * b.w1,
+      tx: a.sx * b.tx + a.shx * b.ty + a.tx * b.w2,
+      shy: a.shy * b.sx + a.sy * b.shy + a.ty * b.w0,
+      sy: a.shy * b.shx + a.sy * b.sy + a.ty * b.w1,
+      ty: a.shy * b.tx + a.sy * b.ty + a.ty * b.w2,
+      w0: a.w0 * b.sx + a.w1 * b.shy + a.w2 * b.w0,
+      w1: a.w0 * b.shx + a.w1 * b.sy + a.w2 * b.w1,
+      w2: a.w0 * b.tx + a.w1 * b.ty + a.w2 * b.w2,
+    }
+  }
+  /// Perform the transform
+  pub fn transform(&self, x: f64, y: f64) -> (f64, f64) {
+    let m = 1.0 / (x * self.w0 + y * self.w1 + self.w2);
+    (
+      m * (x * self.sx + y * self.shx + self.tx),
+      m * (x * self.shy + y * self.sy + self.ty),
+    )
+  }
+}
+
+impl Transformer for TransPerspective {
+  fn transform(&self, x: f64, y: f64) -> (f64, f64) {
+    TransPerspective::transform(self, x, y)
+  }
+  fn is_affine(&self) -> bool {
+    self.w0 == 0.0 && self.w1 == 0.0
+  }
+}
+
+/// Bilinear Transformation
+///
+/// Maps `x` and `y` through `a + b * x * y + c * x + d * y`. Unlike a
+///   perspective transform, straight lines other than the edges of the
+///   quadrilateral become curves.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TransBilinear {
+  /// Coefficients for the constant, `x * y`, `x` and `y` terms, for each output
+  mtx: [[f64; 2]; 4],
+}
+
+impl TransBilinear {
+  /// Map one quadrilateral onto another
+  ///
+  /// Returns `None` if the system of equations has no solution
+  pub fn quad_to_quad(src: &[(f64, f64); 4], dst: &[(f64, f64); 4]) -> Option<Self> {
+    let left = src.map(|(x, y)| [1.0, x * y, x, y]);
+    let right = dst.map(|(x, y)| [x, y]);
+    solve(left, right).map(|mtx| Self { mtx })
+  }
+  /// Map a rectangle onto a quadrilateral
+  pub fn rect_to_quad(x1: f64, y1: f64, x2: f64, y2: f64, q: &[(f64, f64); 4]) -> Option<Self> {
+    Self::quad_to_quad(&rect_corners(x1, y1, x2, y2), q)
+  }
+  /// Map a quadrilateral onto a rectangle
+  pub fn quad_to_rect(q: &[(f64, f64); 4], x1: f64, y1: f64, x2: f64, y2: f64) -> Option<Self> {
+    Self::quad_to_quad(q, &rect_corners(x1, y1, x2, y2))
+  }
+  /// Perform the transform
+  pub fn transform(&self, x: f64, y: f64) -> (f64, f64) {
+    let xy = x * y;
+    let m = &self.mtx;
+    (
+      m[0][0] + m[1][0] * xy + m[2][0] * x + m[3][0] * y,
+      m[0][1] + m[1][1] * xy + m[2][1] * x + m[3][1] * y,
+    )
+  }
+}
+
+impl Transformer for TransBilinear {
+  fn transform(&self, x: f64, y: f64) -> (f64, f64) {
+    TransBilinear::transform(self, x, y)
+  }
+}
+
+/// Solve a 4x4 system of linear equations, for two right hand sides
+///
+/// Gaussian elimination with partial pivoting on the augmented rows, `None`
+///   if singular
+fn solve(left: [[f64; 4]; 4], right: [[f64; 2]; 4]) -> Option<[[f64; 2]; 4]> {
+  let mut m: [[f64; 6]; 4] = std::array::from_fn(|i| {
+    let (l, r) = (left[i], right[i]);
+    [l[0], l[1], l[2], l[3], r[0], r[1]]
+  });
+  for k in 0..4 {
+    let pivot = (k..4).max_by(|&a, &b| m[a][k].abs().total_cmp(&m[b][k].abs()))?;
+    if m[pivot][k] == 0.0 {
+      return None;
+    }
+    m.swap(k, pivot);
+    let a = m[k][k];
+    let p = m[k].map(|v| v / a);
+    m[k] = p;
+    for row in m.iter_mut().skip(k + 1) {
+      let f = row[k];
+      for (v, pv) in row.iter_mut().zip(&p) {
+        *v -= f * pv;
+      }
+    }
+  }
+  // Back substitution
+  let mut result = [[0.0; 2]; 4];
+  for k in (0..4).rev() {
+    let r = [0, 1].map(|j| m[k][4 + j] - (k + 1..4).map(|i| m[k][i] * result[i][j]).sum::<f64>());
+    result[k] = r;
+  }
+  Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const QUAD: [(f64, f64); 4] = [(20.0, 80.0), (80.0, 80.0), (100.0, 10.0), (0.0, 10.0)];
+
+  fn close(a: (f64, f64), b: (f64, f64)) -> bool {
+    (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9
+  }
+
+  #[test]
+  fn perspective_maps_corners() {
+    let t = TransPerspective::rect_to_quad(0.0, 0.0, 100.0, 100.0, &QUAD).unwrap();
+    for (p, q) in rect_corners(0.0, 0.0, 100.0, 100.0).iter().zip(&QUAD) {
+      assert!(close(t.transform(p.0, p.1), *q), "{p:?}");
+    }
+    // Straight lines stay straight, the centre moves towards the far edge
+    let (x, y) = t.transform(50.0, 50.0);
+    assert!((x - 50.0).abs() < 1e-9);
+    assert!(y > 45.0);
+    assert!(!t.is_affine());
+    // Back again
+    let inv = TransPerspective::quad_to_rect(&QUAD, 0.0, 0.0, 100.0, 100.0).unwrap();
+    assert!(close(inv.transform(x, y), (50.0, 50.0)));
+    assert!(close(t.invert().unwrap().transform(x, y), (50.0, 50.0)));
+  }
+
+  #[test]
+  fn perspective_from_affine() {
+    let m = Transform::rotation(0.3).then_translate(5.0, -2.0);
+    let t = TransPerspective::from(m);
+    assert!(t.is_affine());
+    assert!(close(t.transform(3.0, 4.0), m.transform(3.0, 4.0)));
+    let square = rect_corners(0.0, 0.0, 1.0, 1.0);
+    let parallelogram = square.map(|(x, y)| m.transform(x, y));
+    let t = TransPerspective::square_to_quad(&parallelogram).unwrap();
+    assert!(close(t.transform(0.25, 0.75), m.transform(0.25, 0.75)));
+    // Degenerate
+    assert!(TransPerspective::quad_to_square(&[(0.0, 0.0); 4]).is_none());
+  }
+
+  #[test]
+  fn bilinear_maps_corners() {
+    let t = TransBilinear::rect_to_quad(0.0, 0.0, 100.0, 100.0, &QUAD).unwrap();
+    for (p, q) in rect_corners(0.0, 0.0, 100.0, 100.0).iter().zip(&QUAD) {
+      assert!(close(t.transform(p.0, p.1), *q), "{p:?}");
+    }
+    // Centre of the rectangle is the average of the corners
+    assert!(close(t.transform(50.0, 50.0), (50.0, 45.0)));
+    let inv = TransBilinear::quad_to_rect(&QUAD, 0.0, 0.0, 100.0, 100.0).unwrap();
+    assert!(close(inv.transform(20.0, 80.0), (0.0, 0.0)));
+    assert!(TransBilinear::quad_to_quad(&[(0.0, 0.0); 4], &QUAD).is_none());
+  }
+}
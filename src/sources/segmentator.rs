@@ -0,0 +1,188 @@
+//! Segment Subdivision
+//!
+//! Split long line segments into short ones, so that non-linear transforms
+//!   like [`TransPerspective`](crate::TransPerspective) and
+//!   [`TransBilinear`](crate::TransBilinear) bend them, port of AGG's
+//!   `conv_segmentator`
+//!
+//! # Example
+//!
+//!     let mut path = agg::Path::new();
+//!     path.move_to( 0.0, 0.0);
+//!     path.line_to(10.0, 0.0);
+//!
+//!     // Pieces no longer than 2.5
+//!     let mut segments = agg::ConvSegmentator::new(&path);
+//!     segments.approximation_scale(0.4);
+//!     assert_eq!(agg::VertexSource::xconvert(&segments).len(), 5);
+
+use crate::VertexSource;
+use crate::Vertices;
+use crate::curves::flatten;
+use crate::paths::PathCommand;
+use crate::paths::Vertex;
+
+/// Segment Subdivision for Paths and Vertex Sources
+///
+/// Curves in the source are flattened, and closing segments are subdivided
+///   before the close
+#[derive(Debug)]
+pub struct ConvSegmentator<T: VertexSource> {
+  /// Source of Vertices
+  source: T,
+  /// Number of pieces per unit of length, 1.0
+  approx_scale: f64,
+  /// Approximation scale of curves in the source, 1.0
+  curve_scale: f64,
+}
+
+impl<T> VertexSource for ConvSegmentator<T>
+where
+  T: VertexSource,
+{
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    self.vertices().collect()
+  }
+  fn vertices(&self) -> Vertices<'_> {
+    let scale = self.approx_scale;
+    let (mut start, mut last) = ((0.0, 0.0), (0.0, 0.0));
+    Box::new(flatten(self.source.vertices(), self.curve_scale).flat_map(move |v| {
+      let mut out = vec![];
+      match v.cmd {
+        PathCommand::MoveTo => {
+          start = (v.x, v.y);
+          last = start;
+          out.push(v);
+        }
+        PathCommand::LineTo => {
+          out.extend(subdivide(last, (v.x, v.y), scale).map(|(x, y)| Vertex::line_to(x, y)));
+          out.push(v);
+          last = (v.x, v.y);
+        }
+        PathCommand::Close => {
+          out.extend(subdivide(last, start, scale).map(|(x, y)| Vertex::line_to(x, y)));
+          let (x, y) = out.last().map(|p| (p.x, p.y)).unwrap_or((v.x, v.y));
+          out.push(Vertex::close_polygon(x, y));
+          last = start;
+        }
+        _ => out.push(v),
+      }
+      out
+    }))
+  }
+}
+
+/// Points strictly between two end points, spaced at most `1 / scale` apart
+fn subdivide(a: (f64, f64), b: (f64, f64), scale: f64) -> impl Iterator<Item = (f64, f64)> {
+  let d = (b.0 - a.0).hypot(b.1 - a.1);
+  let n = (d * scale).ceil();
+  let n = if n.is_finite() && n > 1.0 { n as usize } else { 1 };
+  (1..n).map(move |i| {
+    let t = i as f64 / n as f64;
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+  })
+}
+
+impl<T> ConvSegmentator<T>
+where
+  T: VertexSource,
+{
+  /// Create a new Segmentator from a Vertex Source
+  pub fn new(source: T) -> Self {
+    Self {
+      source,
+      approx_scale: 1.0,
+      curve_scale: 1.0,
+    }
+  }
+  /// Set approximation scale
+  ///
+  /// Segments are split into pieces no longer than `1 / scale`
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.approx_scale = scale;
+  }
+  /// Get approximation scale
+  pub fn get_approximation_scale(&self) -> f64 {
+    self.approx_scale
+  }
+  /// Set the approximation scale of curves in the source
+  ///
+  /// Use the largest scale of the following transform, so zoomed curves
+  ///   stay smooth
+  pub fn curve_approximation_scale(&mut self, scale: f64) {
+    self.curve_scale = scale;
+  }
+  /// Get the approximation scale of curves
+  pub fn get_curve_approximation_scale(&self) -> f64 {
+    self.curve_scale
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Path;
+  use crate::TransBilinear;
+  use crate::Transform;
+
+  #[test]
+  fn splits_lines_and_closing_segment() {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(4.0, 0.0);
+    path.line_to(4.0, 3.0);
+    path.close_polygon();
+    let mut seg = ConvSegmentator::new(&path);
+    seg.approximation_scale(0.5);
+    let v = seg.xconvert();
+    let xy: Vec<_> = v.iter().map(|v| ((v.x * 3.0).round() / 3.0, v.y)).collect();
+    assert_eq!(xy, [
+      (0.0, 0.0),
+      (2.0, 0.0),
+      (4.0, 0.0),
+      (4.0, 1.5),
+      (4.0, 3.0),
+      (8.0 / 3.0, 2.0),
+      (4.0 / 3.0, 1.0),
+      (4.0 / 3.0, 1.0),
+    ]);
+    assert_eq!(v[0].cmd, PathCommand::MoveTo);
+    assert_eq!(v[7].cmd, PathCommand::Close);
+  }
+
+  #[test]
+  fn curve_scale() {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.curve3(10.0, 20.0, 20.0, 0.0);
+    // No subdivision, only the flattening of the curve changes
+    let mut seg = ConvSegmentator::new(&path);
+    seg.approximation_scale(0.0);
+    let n = seg.xconvert().len();
+    seg.curve_approximation_scale(10.0);
+    assert_eq!(seg.get_curve_approximation_scale(), 10.0);
+    assert!(seg.xconvert().len() > n);
+  }
+
+  #[test]
+  fn bends_under_bilinear() {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(100.0, 100.0);
+    let quad = [(0.0, 0.0), (100.0, 0.0), (60.0, 100.0), (0.0, 100.0)];
+    let bilinear = TransBilinear::rect_to_quad(0.0, 0.0, 100.0, 100.0, &quad).unwrap();
+    let straight = (&path).transformed(bilinear).xconvert();
+    assert_eq!(straight.len(), 2);
+    let bent = ConvSegmentator::new(&path).transformed(bilinear).xconvert();
+    assert_eq!(bent.len(), 143);
+    // The middle is off the straight line between the transformed ends
+    let mid = bent[71];
+    assert!((mid.x - 40.0).abs() < 1.0 && (mid.y - 50.0).abs() < 1.0);
+    assert!(mid.x > mid.y * 0.6 + 5.0);
+    // Affine transforms give the same vertices, with more of them
+    let a = ConvSegmentator::new(&path)
+      .transformed(Transform::scaling(2.0, 1.0))
+      .xconvert();
+    assert_eq!((a[0].x, a[142].x), (0.0, 200.0));
+  }
+}
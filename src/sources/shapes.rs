@@ -0,0 +1,592 @@
+//! Shapes
+//!
+//! Parametric shape generators: regular polygons, stars, spirals, block
+//!   arrows and rectangles with a radius per corner
+//!
+//! All shapes may be rotated around their center and drawn open, without
+//!   the closing segment, for example to be dashed. Curved shapes are
+//!   flattened with a tolerance set by their approximation scale, as for
+//!   [`Stroke`](crate::Stroke), raise it when the shape is drawn scaled up.
+//!
+//! # Example
+//!
+//!     use std::f64::consts::PI;
+//!
+//!     // Five pointed star, pointing up in a y-down space
+//!     let mut star = agg::Star::new(50.0, 50.0, 40.0, 16.0, 5);
+//!     star.rotation(-PI / 2.0);
+//!
+//!     // Outline of a card, square at the bottom
+//!     let mut card = agg::RoundedBox::new(10.0, 10.0, 90.0, 60.0, [8.0, 8.0, 0.0, 0.0]);
+//!     card.approximation_scale(2.0);
+//!
+//!     // Draw
+//!     // let mut ras = agg::RasterizerScanline::new();
+//!     // ras.add_path(&star);
+//!     // ras.add_path(&card);
+
+use crate::VertexSource;
+use crate::paths::Vertex;
+use std::f64::consts::PI;
+
+/// Angle between points of a flattened arc of radius `r`
+///
+/// Same tolerance as [`Ellipse`](crate::Ellipse), 1/8 of a unit, divided
+///   by the approximation scale
+fn arc_step(r: f64, scale: f64) -> f64 {
+  let r = r.abs().max(1e-10);
+  (r / (r + 0.125 / scale)).acos() * 2.0
+}
+
+/// Rotate points around (x,y) and build the vertices of the outline
+fn outline(points: &[(f64, f64)], x: f64, y: f64, rotation: f64, closed: bool) -> Vec<Vertex<f64>> {
+  if points.is_empty() {
+    return vec![];
+  }
+  let (sn, cs) = rotation.sin_cos();
+  let mut out: Vec<_> = points
+    .iter()
+    .enumerate()
+    .map(|(i, &(px, py))| {
+      let (dx, dy) = (px - x, py - y);
+      let (px, py) = (x + dx * cs - dy * sn, y + dx * sn + dy * cs);
+      if i == 0 {
+        Vertex::move_to(px, py)
+      } else {
+        Vertex::line_to(px, py)
+      }
+    })
+    .collect();
+  if closed {
+    let last = out[out.len() - 1];
+    out.push(Vertex::close_polygon(last.x, last.y));
+  }
+  out
+}
+
+/// Regular Polygon
+///
+/// Corners are on a circle, the first one at angle 0.0 before rotation
+#[derive(Debug, Clone)]
+pub struct RegularPolygon {
+  /// Center x
+  x: f64,
+  /// Center y
+  y: f64,
+  /// Radius of the corners
+  r: f64,
+  /// Number of corners
+  n: usize,
+  /// Rotation in radians, 0.0
+  rotation: f64,
+  /// Draw the closing segment, true
+  closed: bool,
+}
+
+impl VertexSource for RegularPolygon {
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    if self.n < 2 {
+      return vec![];
+    }
+    let points: Vec<_> = (0..self.n)
+      .map(|i| {
+        let a = 2.0 * PI * i as f64 / self.n as f64;
+        (self.x + self.r * a.cos(), self.y + self.r * a.sin())
+      })
+      .collect();
+    outline(&points, self.x, self.y, self.rotation, self.closed)
+  }
+}
+
+impl RegularPolygon {
+  /// Create a new Regular Polygon with `n` corners
+  pub fn new(x: f64, y: f64, r: f64, n: usize) -> Self {
+    Self {
+      x,
+      y,
+      r,
+      n,
+      rotation: 0.0,
+      closed: true,
+    }
+  }
+  /// Set the rotation around the center, in radians
+  pub fn rotation(&mut self, rotation: f64) {
+    self.rotation = rotation;
+  }
+  /// Get the rotation
+  pub fn get_rotation(&self) -> f64 {
+    self.rotation
+  }
+  /// Draw the closing segment, or leave the outline open
+  pub fn closed(&mut self, closed: bool) {
+    self.closed = closed;
+  }
+  /// Get if the outline is closed
+  pub fn get_closed(&self) -> bool {
+    self.closed
+  }
+}
+
+/// Star
+///
+/// Points alternate between an outer and an inner radius, the first outer
+///   point at angle 0.0 before rotation
+#[derive(Debug, Clone)]
+pub struct Star {
+  /// Center x
+  x: f64,
+  /// Center y
+  y: f64,
+  /// Radius of the tips
+  r1: f64,
+  /// Radius between the tips
+  r2: f64,
+  /// Number of tips
+  n: usize,
+  /// Rotation in radians, 0.0
+  rotation: f64,
+  /// Draw the closing segment, true
+  closed: bool,
+}
+
+impl VertexSource for Star {
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    if self.n < 2 {
+      return vec![];
+    }
+    let points: Vec<_> = (0..2 * self.n)
+      .map(|i| {
+        let a = PI * i as f64 / self.n as f64;
+        let r = if i % 2 == 0 { self.r1 } else { self.r2 };
+        (self.x + r * a.cos(), self.y + r * a.sin())
+      })
+      .collect();
+    outline(&points, self.x, self.y, self.rotation, self.closed)
+  }
+}
+
+impl Star {
+  /// Create a new Star with `n` tips at radius `r1`, and radius `r2` between
+  pub fn new(x: f64, y: f64, r1: f64, r2: f64, n: usize) -> Self {
+    Self {
+      x,
+      y,
+      r1,
+      r2,
+      n,
+      rotation: 0.0,
+      closed: true,
+    }
+  }
+  /// Set the rotation around the center, in radians
+  pub fn rotation(&mut self, rotation: f64) {
+    self.rotation = rotation;
+  }
+  /// Get the rotation
+  pub fn get_rotation(&self) -> f64 {
+    self.rotation
+  }
+  /// Draw the closing segment, or leave the outline open
+  pub fn closed(&mut self, closed: bool) {
+    self.closed = closed;
+  }
+  /// Get if the outline is closed
+  pub fn get_closed(&self) -> bool {
+    self.closed
+  }
+}
+
+/// Spiral
+///
+/// The radius grows evenly with the angle, from `r1` at angle 0.0 before
+///   rotation to `r2` after a number of turns. Positive turns run towards
+///   positive angles. Open by default.
+#[derive(Debug, Clone)]
+pub struct Spiral {
+  /// Center x
+  x: f64,
+  /// Center y
+  y: f64,
+  /// Start radius
+  r1: f64,
+  /// End radius
+  r2: f64,
+  /// Number of turns, may be fractional
+  turns: f64,
+  /// Rotation in radians, 0.0
+  rotation: f64,
+  /// Draw the closing segment, false
+  closed: bool,
+  /// Approximation scale, 1.0
+  approx_scale: f64,
+}
+
+impl VertexSource for Spiral {
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    let sweep = 2.0 * PI * self.turns;
+    let mut points = vec![];
+    let mut a = 0.0_f64;
+    loop {
+      // Fraction of the sweep, the angle grows the same way for negative turns
+      let t = if sweep == 0.0 { 1.0 } else { (a / sweep.abs()).min(1.0) };
+      let r = self.r1 + (self.r2 - self.r1) * t;
+      let angle = a * sweep.signum();
+      points.push((self.x + r * angle.cos(), self.y + r * angle.sin()));
+      if t >= 1.0 {
+        break;
+      }
+      a += arc_step(r, self.approx_scale).min(sweep.abs() - a).max(1e-3);
+      a = a.min(sweep.abs());
+    }
+    outline(&points, self.x, self.y, self.rotation, self.closed)
+  }
+}
+
+impl Spiral {
+  /// Create a new Spiral from radius `r1` to `r2`
+  pub fn new(x: f64, y: f64, r1: f64, r2: f64, turns: f64) -> Self {
+    Self {
+      x,
+      y,
+      r1,
+      r2,
+      turns,
+      rotation: 0.0,
+      closed: false,
+      approx_scale: 1.0,
+    }
+  }
+  /// Set the rotation around the center, in radians
+  pub fn rotation(&mut self, rotation: f64) {
+    self.rotation = rotation;
+  }
+  /// Get the rotation
+  pub fn get_rotation(&self) -> f64 {
+    self.rotation
+  }
+  /// Draw the closing segment, from the end back to the start
+  pub fn closed(&mut self, closed: bool) {
+    self.closed = closed;
+  }
+  /// Get if the outline is closed
+  pub fn get_closed(&self) -> bool {
+    self.closed
+  }
+  /// Set approximation scale
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.approx_scale = scale;
+  }
+  /// Get approximation scale
+  pub fn get_approximation_scale(&self) -> f64 {
+    self.approx_scale
+  }
+}
+
+/// Block Arrow
+///
+/// A shaft from the tail (x1,y1) ending in a triangular head with its tip
+///   at (x2,y2). The head is shortened to fit short arrows. Rotation is
+///   around the middle of the arrow.
+#[derive(Debug, Clone)]
+pub struct BlockArrow {
+  /// Tail x
+  x1: f64,
+  /// Tail y
+  y1: f64,
+  /// Tip x
+  x2: f64,
+  /// Tip y
+  y2: f64,
+  /// Width of the shaft, 2.0
+  shaft_width: f64,
+  /// Width of the head, 6.0
+  head_width: f64,
+  /// Length of the head, 6.0
+  head_length: f64,
+  /// Rotation in radians, 0.0
+  rotation: f64,
+  /// Draw the closing segment, true
+  closed: bool,
+}
+
+impl VertexSource for BlockArrow {
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    let (dx, dy) = (self.x2 - self.x1, self.y2 - self.y1);
+    let d = dx.hypot(dy);
+    if d < 1e-10 {
+      return vec![];
+    }
+    // Along and across the arrow
+    let (ux, uy) = (dx / d, dy / d);
+    let at = |s: f64, t: f64| (self.x1 + ux * s - uy * t, self.y1 + uy * s + ux * t);
+    let head = self.head_length.clamp(0.0, d);
+    let (sw, hw) = (self.shaft_width / 2.0, self.head_width / 2.0);
+    let points = [
+      at(0.0, -sw),
+      at(d - head, -sw),
+      at(d - head, -hw),
+      at(d, 0.0),
+      at(d - head, hw),
+      at(d - head, sw),
+      at(0.0, sw),
+    ];
+    let (cx, cy) = at(d / 2.0, 0.0);
+    outline(&points, cx, cy, self.rotation, self.closed)
+  }
+}
+
+impl BlockArrow {
+  /// Create a new Block Arrow from the tail (x1,y1) to the tip (x2,y2)
+  pub fn new(x1: f64, y1: f64, x2: f64, y2: f64) -> Self {
+    Self {
+      x1,
+      y1,
+      x2,
+      y2,
+      shaft_width: 2.0,
+      head_width: 6.0,
+      head_length: 6.0,
+      rotation: 0.0,
+      closed: true,
+    }
+  }
+  /// Set the width of the shaft
+  pub fn shaft_width(&mut self, width: f64) {
+    self.shaft_width = width;
+  }
+  /// Get the width of the shaft
+  pub fn get_shaft_width(&self) -> f64 {
+    self.shaft_width
+  }
+  /// Set the width and length of the head
+  pub fn head(&mut self, width: f64, length: f64) {
+    self.head_width = width;
+    self.head_length = length;
+  }
+  /// Get the width and length of the head
+  pub fn get_head(&self) -> (f64, f64) {
+    (self.head_width, self.head_length)
+  }
+  /// Set the rotation around the middle, in radians
+  pub fn rotation(&mut self, rotation: f64) {
+    self.rotation = rotation;
+  }
+  /// Get the rotation
+  pub fn get_rotation(&self) -> f64 {
+    self.rotation
+  }
+  /// Draw the closing segment, or leave the outline open
+  pub fn closed(&mut self, closed: bool) {
+    self.closed = closed;
+  }
+  /// Get if the outline is closed
+  pub fn get_closed(&self) -> bool {
+    self.closed
+  }
+}
+
+/// Rectangle with a Radius per Corner
+///
+/// Radii are given for the corners at (x1,y1), (x2,y1), (x2,y2) and
+///   (x1,y2), and scaled down together when they do not fit. Unlike
+///   [`RoundedRect`](crate::RoundedRect) the outline is computed when
+///   drawn. Rotation is around the center.
+#[derive(Debug, Clone)]
+pub struct RoundedBox {
+  /// Minimum and maximum x
+  x: [f64; 2],
+  /// Minimum and maximum y
+  y: [f64; 2],
+  /// Radius of each corner
+  r: [f64; 4],
+  /// Rotation in radians, 0.0
+  rotation: f64,
+  /// Draw the closing segment, true
+  closed: bool,
+  /// Approximation scale, 1.0
+  approx_scale: f64,
+}
+
+impl VertexSource for RoundedBox {
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    let [x1, x2] = self.x;
+    let [y1, y2] = self.y;
+    let r = self.radii();
+    // Corner centers and start angles, going from +x towards +y
+    let corners = [
+      (x1 + r[0], y1 + r[0], PI),
+      (x2 - r[1], y1 + r[1], 1.5 * PI),
+      (x2 - r[2], y2 - r[2], 0.0),
+      (x1 + r[3], y2 - r[3], 0.5 * PI),
+    ];
+    let mut points = vec![];
+    for (&(cx, cy, a0), &r) in corners.iter().zip(r.iter()) {
+      if r <= 0.0 {
+        points.push((cx, cy));
+        continue;
+      }
+      let n = ((0.5 * PI / arc_step(r, self.approx_scale)).ceil() as usize).max(1);
+      for i in 0..=n {
+        let a = a0 + 0.5 * PI * i as f64 / n as f64;
+        points.push((cx + r * a.cos(), cy + r * a.sin()));
+      }
+    }
+    let (cx, cy) = ((x1 + x2) / 2.0, (y1 + y2) / 2.0);
+    outline(&points, cx, cy, self.rotation, self.closed)
+  }
+}
+
+impl RoundedBox {
+  /// Create a new Rounded Box with a radius per corner
+  pub fn new(x1: f64, y1: f64, x2: f64, y2: f64, radii: [f64; 4]) -> Self {
+    Self {
+      x: [x1.min(x2), x1.max(x2)],
+      y: [y1.min(y2), y1.max(y2)],
+      r: radii,
+      rotation: 0.0,
+      closed: true,
+      approx_scale: 1.0,
+    }
+  }
+  /// Radii, scaled down so neighboring corners fit along each side
+  pub fn radii(&self) -> [f64; 4] {
+    let w = self.x[1] - self.x[0];
+    let h = self.y[1] - self.y[0];
+    let r = self.r.map(|r| r.max(0.0));
+    let k = [
+      w / (r[0] + r[1]),
+      h / (r[1] + r[2]),
+      w / (r[2] + r[3]),
+      h / (r[3] + r[0]),
+    ]
+    .into_iter()
+    .fold(1.0_f64, f64::min);
+    r.map(|r| r * k)
+  }
+  /// Set the rotation around the center, in radians
+  pub fn rotation(&mut self, rotation: f64) {
+    self.rotation = rotation;
+  }
+  /// Get the rotation
+  pub fn get_rotation(&self) -> f64 {
+    self.rotation
+  }
+  /// Draw the closing segment, or leave the outline open
+  pub fn closed(&mut self, closed: bool) {
+    self.closed = closed;
+  }
+  /// Get if the outline is closed
+  pub fn get_closed(&self) -> bool {
+    self.closed
+  }
+  /// Set approximation scale
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.approx_scale = scale;
+  }
+  /// Get approximation scale
+  pub fn get_approximation_scale(&self) -> f64 {
+    self.approx_scale
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::paths::PathCommand;
+  use crate::paths::bounding_rect;
+
+  fn xy(v: &[Vertex<f64>]) -> Vec<(f64, f64)> {
+    v.iter()
+      .map(|v| ((v.x * 1e6).round() / 1e6, (v.y * 1e6).round() / 1e6))
+      .collect()
+  }
+
+  #[test]
+  fn regular_polygon() {
+    let mut p = RegularPolygon::new(0.0, 0.0, 10.0, 4);
+    let v = p.xconvert();
+    assert_eq!(v.len(), 5);
+    assert_eq!(v[4].cmd, PathCommand::Close);
+    assert_eq!(xy(&v)[..4], [(10.0, 0.0), (0.0, 10.0), (-10.0, 0.0), (0.0, -10.0)]);
+    p.rotation(PI / 2.0);
+    p.closed(false);
+    let v = p.xconvert();
+    assert_eq!(v.len(), 4);
+    assert_eq!(xy(&v)[0], (0.0, 10.0));
+    assert!(v.iter().all(|v| v.cmd != PathCommand::Close));
+  }
+
+  #[test]
+  fn star() {
+    let s = Star::new(0.0, 0.0, 10.0, 5.0, 4);
+    let v = s.xconvert();
+    assert_eq!(v.len(), 9);
+    let r: Vec<_> = v[..8].iter().map(|v| v.x.hypot(v.y).round()).collect();
+    assert_eq!(r, [10.0, 5.0, 10.0, 5.0, 10.0, 5.0, 10.0, 5.0]);
+  }
+
+  #[test]
+  fn spiral() {
+    let mut s = Spiral::new(0.0, 0.0, 10.0, 30.0, 2.0);
+    let v = s.xconvert();
+    assert_eq!(xy(&v)[0], (10.0, 0.0));
+    assert_eq!(xy(&v)[v.len() - 1], (30.0, 0.0));
+    assert!(v.iter().all(|v| v.cmd != PathCommand::Close));
+    // Radius grows with the angle
+    let r: Vec<_> = v.iter().map(|v| v.x.hypot(v.y)).collect();
+    assert!(r.windows(2).all(|w| w[0] < w[1]));
+    // Finer with a larger scale
+    s.approximation_scale(4.0);
+    assert!(s.xconvert().len() > v.len());
+  }
+
+  #[test]
+  fn spiral_negative_turns() {
+    let v = Spiral::new(0.0, 0.0, 10.0, 30.0, 2.0).xconvert();
+    let w = Spiral::new(0.0, 0.0, 10.0, 30.0, -2.0).xconvert();
+    // Mirrored across the x axis
+    assert_eq!(w.len(), v.len());
+    for (v, w) in v.iter().zip(&w) {
+      assert!((v.x - w.x).abs() < 1e-9 && (v.y + w.y).abs() < 1e-9);
+    }
+    // First step runs towards negative angles
+    assert!(w[1].y < 0.0);
+  }
+
+  #[test]
+  fn block_arrow() {
+    let mut a = BlockArrow::new(0.0, 0.0, 20.0, 0.0);
+    a.shaft_width(4.0);
+    a.head(10.0, 5.0);
+    let v = a.xconvert();
+    assert_eq!(xy(&v)[..7], [
+      (0.0, -2.0),
+      (15.0, -2.0),
+      (15.0, -5.0),
+      (20.0, 0.0),
+      (15.0, 5.0),
+      (15.0, 2.0),
+      (0.0, 2.0)
+    ]);
+    assert_eq!(v[7].cmd, PathCommand::Close);
+    // Pointing down
+    a.rotation(PI / 2.0);
+    assert_eq!(xy(&a.xconvert())[3], (10.0, 10.0));
+  }
+
+  #[test]
+  fn rounded_box() {
+    let mut b = RoundedBox::new(0.0, 0.0, 100.0, 40.0, [10.0, 0.0, 40.0, 40.0]);
+    // Left side is 40 high, all radii are scaled to fit it
+    assert_eq!(b.radii(), [8.0, 0.0, 32.0, 32.0]);
+    let r = bounding_rect(&b).unwrap();
+    assert_eq!((r.x1(), r.y1(), r.x2(), r.y2()), (0.0, 0.0, 100.0, 40.0));
+    let v = b.xconvert();
+    assert!(xy(&v).contains(&(100.0, 0.0)));
+    assert!(!xy(&v).contains(&(0.0, 0.0)));
+    let n = v.len();
+    b.approximation_scale(4.0);
+    assert!(b.xconvert().len() > n);
+  }
+}
@@ -0,0 +1,211 @@
+//! Polygon Smoothing
+//!
+//! Replace the straight edges of a polygon with cubic Bezier curves through
+//!   its vertices, port of AGG's `vcgen_smooth_poly1`
+//!
+//! # Example
+//!
+//!     // Coarse outline
+//!     let mut path = agg::Path::new();
+//!     path.move_to( 10.0, 10.0);
+//!     path.line_to(100.0, 20.0);
+//!     path.line_to( 80.0, 90.0);
+//!     path.line_to( 20.0, 70.0);
+//!     path.close_polygon();
+//!
+//!     // Smooth curves, flattened by the rasterizer or a ConvCurve
+//!     let mut smooth = agg::ConvSmoothPoly1::new(path);
+//!     smooth.smooth_value(1.0);
+//!
+//!     // Draw
+//!     // let mut ras = agg::RasterizerScanline::new();
+//!     // ras.add_path(&smooth);
+
+use crate::VertexSource;
+use crate::Vertices;
+use crate::curves::ConvCurve;
+use crate::curves::flatten;
+use crate::paths::PathCommand;
+use crate::paths::Vertex;
+use crate::paths::len;
+use crate::paths::sub_paths;
+
+/// Polygon Smoothing for Paths and Vertex Sources
+///
+/// Closed polygons become a loop of `Curve4` segments, open polylines start
+///   and end with a `Curve3` segment. Curves in the source are flattened
+///   first.
+#[derive(Debug)]
+pub struct ConvSmoothPoly1<T: VertexSource> {
+  /// Source of Vertices
+  source: T,
+  /// Smoothness, 0 gives straight edges, 1.0
+  smooth_value: f64,
+  /// Approximation scale of curves in the source, 1.0
+  approx_scale: f64,
+}
+
+/// Smoothed polygons flattened into line segments
+pub type ConvSmoothPoly1Curve<T> = ConvCurve<ConvSmoothPoly1<T>>;
+
+impl<T> VertexSource for ConvSmoothPoly1<T>
+where
+  T: VertexSource,
+{
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    self.vertices().collect()
+  }
+  fn vertices(&self) -> Vertices<'_> {
+    Box::new(sub_paths(flatten(self.source.vertices(), self.approx_scale)).flat_map(move |v| self.smooth(&v)))
+  }
+}
+
+impl<T> ConvSmoothPoly1<T>
+where
+  T: VertexSource,
+{
+  /// Create a new Polygon Smoother from a Vertex Source
+  pub fn new(source: T) -> Self {
+    Self {
+      source,
+      smooth_value: 1.0,
+      approx_scale: 1.0,
+    }
+  }
+  /// Set the smoothness
+  ///
+  /// Usual values are between 0 and 1, larger values produce loops
+  pub fn smooth_value(&mut self, value: f64) {
+    self.smooth_value = value;
+  }
+  /// Get the smoothness
+  pub fn get_smooth_value(&self) -> f64 {
+    self.smooth_value
+  }
+  /// Set the approximation scale of curves in the source
+  ///
+  /// Use the scale of any later transform, see [`Stroke::transform`](crate::Stroke::transform)
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.approx_scale = scale;
+  }
+  /// Control points of the segment from v1 to v2
+  fn calculate(&self, v0: &Vertex<f64>, v1: &Vertex<f64>, v2: &Vertex<f64>, v3: &Vertex<f64>) -> [(f64, f64); 2] {
+    let (d0, d1, d2) = (len(v0, v1), len(v1, v2), len(v2, v3));
+    let k1 = d0 / (d0 + d1);
+    let k2 = d1 / (d1 + d2);
+    let xm1 = v0.x + (v2.x - v0.x) * k1;
+    let ym1 = v0.y + (v2.y - v0.y) * k1;
+    let xm2 = v1.x + (v3.x - v1.x) * k2;
+    let ym2 = v1.y + (v3.y - v1.y) * k2;
+    let s = self.smooth_value * 0.5;
+    [
+      (v1.x + s * (v2.x - xm1), v1.y + s * (v2.y - ym1)),
+      (v2.x + s * (v1.x - xm2), v2.y + s * (v1.y - ym2)),
+    ]
+  }
+  /// Smooth a sub-path
+  fn smooth(&self, v: &[Vertex<f64>]) -> Vec<Vertex<f64>> {
+    let mut out = vec![];
+    let closed = v.iter().any(|v| v.cmd == PathCommand::Close);
+    // Remove repeated vertices, the closing vertex repeats the last position
+    let mut p: Vec<Vertex<f64>> = vec![];
+    for v in v.iter().filter(|v| v.cmd.is_vertex()) {
+      if p.last().is_none_or(|last| len(last, v) > 1e-6) {
+        p.push(*v);
+      }
+    }
+    if closed && p.len() > 2 && len(&p[0], &p[p.len() - 1]) <= 1e-6 {
+      p.pop();
+    }
+    let n = p.len();
+    if n < 2 {
+      return out;
+    }
+    out.push(Vertex::move_to(p[0].x, p[0].y));
+    if n == 2 {
+      out.push(Vertex::line_to(p[1].x, p[1].y));
+      if closed {
+        out.push(Vertex::close_polygon(p[1].x, p[1].y));
+      }
+      return out;
+    }
+    let segments = if closed { n } else { n - 1 };
+    for i in 0..segments {
+      let [c1, c2] = self.calculate(&p[(i + n - 1) % n], &p[i], &p[(i + 1) % n], &p[(i + 2) % n]);
+      let end = p[(i + 1) % n];
+      if !closed && i == 0 {
+        out.push(Vertex::curve3(c2.0, c2.1));
+        out.push(Vertex::curve3(end.x, end.y));
+      } else if !closed && i == segments - 1 {
+        out.push(Vertex::curve3(c1.0, c1.1));
+        out.push(Vertex::curve3(end.x, end.y));
+      } else {
+        out.push(Vertex::curve4(c1.0, c1.1));
+        out.push(Vertex::curve4(c2.0, c2.1));
+        out.push(Vertex::curve4(end.x, end.y));
+      }
+    }
+    if closed {
+      out.push(Vertex::close_polygon(p[0].x, p[0].y));
+    }
+    out
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Path;
+
+  fn square() -> Path {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(10.0, 0.0);
+    path.line_to(10.0, 10.0);
+    path.line_to(0.0, 10.0);
+    path.close_polygon();
+    path
+  }
+
+  #[test]
+  fn closed_polygon_becomes_curves() {
+    let v = ConvSmoothPoly1::new(square()).xconvert();
+    assert_eq!(v.len(), 1 + 4 * 3 + 1);
+    assert_eq!(v[0].cmd, PathCommand::MoveTo);
+    assert!(v[1..13].iter().all(|v| v.cmd == PathCommand::Curve4));
+    assert_eq!(v[13].cmd, PathCommand::Close);
+    // First edge, control points pulled along the neighbouring diagonals
+    assert!((v[1].x - 2.5).abs() < 1e-9 && (v[1].y + 2.5).abs() < 1e-9);
+    assert!((v[2].x - 7.5).abs() < 1e-9 && (v[2].y + 2.5).abs() < 1e-9);
+    assert_eq!((v[3].x, v[3].y), (10.0, 0.0));
+  }
+
+  #[test]
+  fn zero_smooth_value_keeps_edges() {
+    let mut smooth = ConvSmoothPoly1::new(square());
+    smooth.smooth_value(0.0);
+    let v = ConvCurve::new(&smooth).xconvert();
+    assert!(
+      v.iter()
+        .all(|v| v.x.abs() < 1e-9 || (v.x - 10.0).abs() < 1e-9 || v.y.abs() < 1e-9 || (v.y - 10.0).abs() < 1e-9)
+    );
+  }
+
+  #[test]
+  fn open_polyline() {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(10.0, 10.0);
+    path.line_to(20.0, 0.0);
+    let v = ConvSmoothPoly1::new(path).xconvert();
+    let cmds: Vec<_> = v.iter().map(|v| v.cmd).collect();
+    assert_eq!(cmds, [
+      PathCommand::MoveTo,
+      PathCommand::Curve3,
+      PathCommand::Curve3,
+      PathCommand::Curve3,
+      PathCommand::Curve3,
+    ]);
+    assert_eq!((v[4].x, v[4].y), (20.0, 0.0));
+  }
+}
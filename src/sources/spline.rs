@@ -0,0 +1,257 @@
+//! Spline Interpolation
+//!
+//! Turn the vertices of each sub-path into control points of a smooth curve,
+//!   either passing through them (Catmull-Rom) or approximating them
+//!   (uniform cubic B-spline)
+//!
+//! # Example
+//!
+//!     // Sampled data
+//!     let mut path = agg::Path::new();
+//!     path.move_to( 10.0, 50.0);
+//!     path.line_to( 40.0, 10.0);
+//!     path.line_to( 70.0, 60.0);
+//!     path.line_to(100.0, 30.0);
+//!
+//!     // Smooth curve through the samples
+//!     let mut spline = agg::ConvSpline::new(path);
+//!     spline.kind(agg::SplineKind::CatmullRom);
+//!     spline.interpolation_step(1.0 / 20.0);
+//!
+//!     // Draw
+//!     let mut stroke = agg::Stroke::new(spline);
+//!     stroke.width(1.5);
+//!     // let mut ras = agg::RasterizerScanline::new();
+//!     // ras.add_path(&stroke);
+
+use crate::VertexSource;
+use crate::Vertices;
+use crate::curves::flatten;
+use crate::paths::PathCommand;
+use crate::paths::Vertex;
+use crate::paths::sub_paths;
+
+/// Spline Type
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum SplineKind {
+  /// Interpolating spline, passing through every vertex
+  #[default]
+  CatmullRom,
+  /// Uniform cubic B-spline, approximating the vertices
+  ///
+  /// Open sub-paths still start and end on their first and last vertices
+  BSpline,
+}
+
+/// Spline Converter for Paths and Vertex Sources
+///
+/// Curves in the source are flattened before interpolation
+#[derive(Debug)]
+pub struct ConvSpline<T: VertexSource> {
+  /// Source of Vertices
+  source: T,
+  /// Spline Type, Catmull-Rom
+  kind: SplineKind,
+  /// Parameter step for each segment between two vertices, 1/50
+  interpolation_step: f64,
+  /// Approximation scale of curves in the source, 1.0
+  approx_scale: f64,
+}
+
+impl<T> VertexSource for ConvSpline<T>
+where
+  T: VertexSource,
+{
+  fn xconvert(&self) -> Vec<Vertex<f64>> {
+    self.vertices().collect()
+  }
+  fn vertices(&self) -> Vertices<'_> {
+    let steps = if self.interpolation_step > 0.0 {
+      (1.0 / self.interpolation_step).ceil().max(1.0) as usize
+    } else {
+      1
+    };
+    Box::new(sub_paths(flatten(self.source.vertices(), self.approx_scale)).flat_map(move |v| self.convert(&v, steps)))
+  }
+}
+
+impl<T> ConvSpline<T>
+where
+  T: VertexSource,
+{
+  /// Create a new Spline Converter from a Vertex Source
+  pub fn new(source: T) -> Self {
+    Self {
+      source,
+      kind: SplineKind::CatmullRom,
+      interpolation_step: 1.0 / 50.0,
+      approx_scale: 1.0,
+    }
+  }
+  /// Set the spline type
+  pub fn kind(&mut self, kind: SplineKind) {
+    self.kind = kind;
+  }
+  /// Set the parameter step for each segment between two vertices
+  ///
+  /// A step of 1/n produces n line segments per source segment
+  pub fn interpolation_step(&mut self, step: f64) {
+    self.interpolation_step = step;
+  }
+  /// Get the parameter step
+  pub fn get_interpolation_step(&self) -> f64 {
+    self.interpolation_step
+  }
+  /// Set the approximation scale of curves in the source
+  ///
+  /// Use the scale of any later transform, see [`Stroke::transform`](crate::Stroke::transform)
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.approx_scale = scale;
+  }
+  /// Evaluate a segment at t, from p1 (t = 0) towards p2 (t = 1)
+  fn eval(&self, p0: (f64, f64), p1: (f64, f64), p2: (f64, f64), p3: (f64, f64), t: f64) -> (f64, f64) {
+    let (t2, t3) = (t * t, t * t * t);
+    let w = match self.kind {
+      SplineKind::CatmullRom => [
+        0.5 * (-t3 + 2.0 * t2 - t),
+        0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+        0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+        0.5 * (t3 - t2),
+      ],
+      SplineKind::BSpline => [
+        (1.0 - t) * (1.0 - t) * (1.0 - t) / 6.0,
+        (3.0 * t3 - 6.0 * t2 + 4.0) / 6.0,
+        (-3.0 * t3 + 3.0 * t2 + 3.0 * t + 1.0) / 6.0,
+        t3 / 6.0,
+      ],
+    };
+    (
+      w[0] * p0.0 + w[1] * p1.0 + w[2] * p2.0 + w[3] * p3.0,
+      w[0] * p0.1 + w[1] * p1.1 + w[2] * p2.1 + w[3] * p3.1,
+    )
+  }
+  /// Interpolate a sub-path, with `steps` line segments between two vertices
+  fn convert(&self, v: &[Vertex<f64>], steps: usize) -> Vec<Vertex<f64>> {
+    let closed = v.iter().any(|v| v.cmd == PathCommand::Close);
+    // Control points, the closing vertex repeats the last position
+    let mut p: Vec<(f64, f64)> = v
+      .iter()
+      .filter(|v| v.cmd != PathCommand::Close && v.cmd != PathCommand::Stop)
+      .map(|v| (v.x, v.y))
+      .collect();
+    p.dedup();
+    if closed && p.len() > 1 && p.first() == p.last() {
+      p.pop();
+    }
+    let n = p.len();
+    if n < 2 {
+      return vec![];
+    }
+    // Control point at any index, wrapping around closed sub-paths and
+    //   reflecting the end points of open ones
+    let at = |i: isize| -> (f64, f64) {
+      if closed {
+        return p[i.rem_euclid(n as isize) as usize];
+      }
+      if i < 0 {
+        (2.0 * p[0].0 - p[1].0, 2.0 * p[0].1 - p[1].1)
+      } else if i as usize >= n {
+        (2.0 * p[n - 1].0 - p[n - 2].0, 2.0 * p[n - 1].1 - p[n - 2].1)
+      } else {
+        p[i as usize]
+      }
+    };
+    let segments = if closed { n } else { n - 1 };
+    let (x, y) = self.eval(at(-1), at(0), at(1), at(2), 0.0);
+    let mut out = vec![Vertex::move_to(x, y)];
+    for i in 0..segments as isize {
+      let last = if closed && i as usize == segments - 1 {
+        steps - 1
+      } else {
+        steps
+      };
+      for k in 1..=last {
+        let t = k as f64 / steps as f64;
+        let (x, y) = self.eval(at(i - 1), at(i), at(i + 1), at(i + 2), t);
+        out.push(Vertex::line_to(x, y));
+      }
+    }
+    if closed {
+      let (x, y) = (out[out.len() - 1].x, out[out.len() - 1].y);
+      out.push(Vertex::close_polygon(x, y));
+    }
+    out
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Path;
+
+  fn zigzag() -> Path {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(10.0, 10.0);
+    path.line_to(20.0, 0.0);
+    path.line_to(30.0, 10.0);
+    path
+  }
+
+  fn contains(v: &[Vertex<f64>], x: f64, y: f64) -> bool {
+    v.iter().any(|v| (v.x - x).abs() < 1e-9 && (v.y - y).abs() < 1e-9)
+  }
+
+  #[test]
+  fn catmull_rom_passes_through_vertices() {
+    let mut spline = ConvSpline::new(zigzag());
+    spline.interpolation_step(0.25);
+    let v = spline.xconvert();
+    assert_eq!(v.len(), 1 + 3 * 4);
+    assert_eq!(v[0].cmd, PathCommand::MoveTo);
+    assert!(v[1..].iter().all(|v| v.cmd == PathCommand::LineTo));
+    for (x, y) in [(0.0, 0.0), (10.0, 10.0), (20.0, 0.0), (30.0, 10.0)] {
+      assert!(contains(&v, x, y), "({x}, {y})");
+    }
+    // Bends away from the chord between the vertices
+    assert!((v[2].x - 5.0).abs() < 1e-9 && v[2].y > 6.0);
+  }
+
+  #[test]
+  fn bspline_approximates_vertices() {
+    let mut spline = ConvSpline::new(zigzag());
+    spline.kind(SplineKind::BSpline);
+    spline.interpolation_step(0.25);
+    let v = spline.xconvert();
+    // End points are kept, inner vertices are pulled in
+    assert!(contains(&v, 0.0, 0.0));
+    assert!(contains(&v, 30.0, 10.0));
+    assert!(!contains(&v, 10.0, 10.0));
+    assert!(v.iter().all(|v| v.y >= -1e-9 && v.y <= 10.0 + 1e-9));
+  }
+
+  #[test]
+  fn closed_sub_paths() {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(10.0, 0.0);
+    path.line_to(10.0, 10.0);
+    path.line_to(0.0, 10.0);
+    path.close_polygon();
+    path.move_to(20.0, 0.0);
+    path.line_to(30.0, 0.0);
+    path.line_to(30.0, 10.0);
+    path.close_polygon();
+    for kind in [SplineKind::CatmullRom, SplineKind::BSpline] {
+      let mut spline = ConvSpline::new(&path);
+      spline.kind(kind);
+      spline.interpolation_step(0.1);
+      let v = spline.xconvert();
+      // 4 and 3 segments of 10 steps, the last vertex is replaced by the close
+      assert_eq!(v.len(), (1 + 39 + 1) + (1 + 29 + 1));
+      assert_eq!(v[40].cmd, PathCommand::Close);
+      assert_eq!(v[41].cmd, PathCommand::MoveTo);
+      assert_eq!(v[71].cmd, PathCommand::Close);
+    }
+  }
+}
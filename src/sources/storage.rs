@@ -0,0 +1,262 @@
+//! Path Storage
+//!
+//! Many paths, each with its own fill and stroke style, drawn together by
+//!   [`render_path_storage`](crate::render_path_storage)
+//!
+//! # Example
+//!
+//!     use agg::prelude::*;
+//!
+//!     let mut storage = agg::PathStorage::new();
+//!
+//!     // Filled triangle
+//!     let path = storage.begin_path(agg::PathAttributes::fill(Rgba8::from_raw(255, 0, 0, 255)));
+//!     path.move_to(10.0, 10.0);
+//!     path.line_to(90.0, 10.0);
+//!     path.line_to(50.0, 90.0);
+//!     path.close_polygon();
+//!
+//!     // Half transparent outline
+//!     let mut outline = agg::PathAttributes::stroke(Rgba8::BLACK, 2.0);
+//!     outline.opacity = 0.5;
+//!     let path = storage.begin_path(outline);
+//!     path.move_to(10.0, 50.0);
+//!     path.line_to(90.0, 50.0);
+//!
+//!     // Draw everything, twice as large
+//!     let pix = Pixfmt::<Rgb8>::create(200, 200);
+//!     let mut ren_base = agg::RenderingBase::new(pix);
+//!     ren_base.clear(Rgb8::WHITE);
+//!     let mut ren = agg::RenderingScanlineAASolid::new(&mut ren_base, Rgb8::BLACK);
+//!     let mut ras = agg::RasterizerScanline::new();
+//!     agg::render_path_storage(&mut ras, &mut ren, &storage, &agg::Transform::scaling(2.0, 2.0));
+
+use crate::FillingRule;
+use crate::FromRaw4;
+use crate::color::Color;
+use crate::color::Rgba8;
+use crate::paths::Path;
+use crate::paths::PathOrientation;
+use crate::paths::bounding_rect;
+use crate::sources::Rectangle;
+
+/// Style of a path in a [`PathStorage`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PathAttributes {
+  /// Fill color, none for no fill
+  pub fill: Option<Rgba8>,
+  /// Stroke color, none for no stroke
+  pub stroke: Option<Rgba8>,
+  /// Stroke width, before the transform, 1.0
+  pub stroke_width: f64,
+  /// Filling Rule of the fill, NonZero
+  pub fill_rule: FillingRule,
+  /// Opacity, multiplied with the alpha of both colors, 1.0
+  pub opacity: f64,
+}
+
+impl Default for PathAttributes {
+  fn default() -> Self {
+    Self {
+      fill: None,
+      stroke: None,
+      stroke_width: 1.0,
+      fill_rule: FillingRule::NonZero,
+      opacity: 1.0,
+    }
+  }
+}
+
+impl PathAttributes {
+  /// Filled, without a stroke
+  pub fn fill(color: Rgba8) -> Self {
+    Self {
+      fill: Some(color),
+      ..Self::default()
+    }
+  }
+  /// Stroked, without a fill
+  pub fn stroke(color: Rgba8, width: f64) -> Self {
+    Self {
+      stroke: Some(color),
+      stroke_width: width,
+      ..Self::default()
+    }
+  }
+  /// Fill color with the opacity applied
+  pub fn fill_color(&self) -> Option<Rgba8> {
+    self.fill.map(|c| self.apply_opacity(c))
+  }
+  /// Stroke color with the opacity applied
+  pub fn stroke_color(&self) -> Option<Rgba8> {
+    self.stroke.map(|c| self.apply_opacity(c))
+  }
+  fn apply_opacity(&self, c: Rgba8) -> Rgba8 {
+    if self.opacity >= 1.0 {
+      return c;
+    }
+    let alpha = (c.alpha64() * self.opacity.max(0.0) * 255.0).round() as u8;
+    Rgba8::from_raw(c.red8(), c.green8(), c.blue8(), alpha)
+  }
+}
+
+/// Path with its style
+#[derive(Debug, Clone)]
+pub struct StyledPath {
+  /// Outline, may hold many sub-paths
+  pub path: Path,
+  /// Style
+  pub attr: PathAttributes,
+}
+
+/// Error while loading a [`PathStorage`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PathStorageError {
+  /// Line number of the error, starting at 1
+  pub line: usize,
+}
+
+impl std::fmt::Display for PathStorageError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "invalid path data on line {}", self.line)
+  }
+}
+
+impl std::error::Error for PathStorageError {}
+
+/// Collection of Paths with Attributes
+///
+/// Paths are drawn in order, see [`render_path_storage`](crate::render_path_storage)
+#[derive(Debug, Clone, Default)]
+pub struct PathStorage {
+  /// Paths in painting order
+  pub paths: Vec<StyledPath>,
+}
+
+impl PathStorage {
+  /// Create an empty storage
+  pub fn new() -> Self {
+    Self::default()
+  }
+  /// Start a new path with attributes, returning it to be drawn into
+  pub fn begin_path(&mut self, attr: PathAttributes) -> &mut Path {
+    self.paths.push(StyledPath {
+      path: Path::new(),
+      attr,
+    });
+    &mut self.paths.last_mut().unwrap().path
+  }
+  /// Add a path with attributes
+  pub fn push(&mut self, path: Path, attr: PathAttributes) {
+    self.paths.push(StyledPath { path, attr });
+  }
+  /// Number of paths
+  pub fn len(&self) -> usize {
+    self.paths.len()
+  }
+  /// Check if there are no paths
+  pub fn is_empty(&self) -> bool {
+    self.paths.is_empty()
+  }
+  /// Bounding box of all paths, without the stroke widths
+  pub fn bounding_rect(&self) -> Option<Rectangle<f64>> {
+    let mut rects = self.paths.iter().filter_map(|p| bounding_rect(&p.path));
+    let mut r = rects.next()?;
+    for rp in rects {
+      r.expand_rect(&rp);
+    }
+    Some(r)
+  }
+  /// Orient all closed sub-paths the same way
+  pub fn arrange_orientations(&mut self, dir: PathOrientation) {
+    for p in &mut self.paths {
+      p.path.arrange_orientations(dir);
+    }
+  }
+  /// Load filled polygons from the lion text format
+  ///
+  /// A line with a hex color `rrggbb` starts a new path with that fill, the
+  ///   following lines hold `M x,y L x,y ...` polygons, each one closed
+  pub fn from_lion(txt: &str) -> Result<Self, PathStorageError> {
+    let mut storage = Self::new();
+    let mut line_to = false;
+    for (i, line) in txt.lines().enumerate() {
+      let err = PathStorageError { line: i + 1 };
+      let v: Vec<_> = line.split_whitespace().collect();
+      if let [hex] = v[..]
+        && hex.len() == 6
+        && let Ok(rgb) = u32::from_str_radix(hex, 16)
+      {
+        let [_, r, g, b] = rgb.to_be_bytes();
+        storage.begin_path(PathAttributes::fill(Rgba8::from_raw(r, g, b, 255)));
+        continue;
+      }
+      let path = match storage.paths.last_mut() {
+        Some(p) => &mut p.path,
+        None if v.is_empty() => continue,
+        None => return Err(err),
+      };
+      for val in v {
+        match val {
+          "M" => line_to = false,
+          "L" => line_to = true,
+          _ => {
+            let (x, y) = val.split_once(',').ok_or(err)?;
+            let x = x.parse::<f64>().map_err(|_| err)?;
+            let y = y.parse::<f64>().map_err(|_| err)?;
+            if line_to {
+              path.line_to(x, y);
+            } else {
+              path.close_polygon();
+              path.move_to(x, y);
+            }
+          }
+        }
+      }
+    }
+    for p in &mut storage.paths {
+      p.path.close_polygon();
+    }
+    storage.paths.retain(|p| !p.path.vertices.is_empty());
+    Ok(storage)
+  }
+}
+
+impl std::str::FromStr for PathStorage {
+  type Err = PathStorageError;
+  /// Parse the lion text format, see [`PathStorage::from_lion`]
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::from_lion(s)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::paths::PathCommand;
+
+  #[test]
+  fn lion_format() {
+    let txt = "ff0000\nM 0,0 L 10,0 L 10,10\nM 20,20 L 30,20 L 30,30\n00ff00\nM 1.5,2 L 3,4 L 5,6\n";
+    let s = PathStorage::from_lion(txt).unwrap();
+    assert_eq!(s.len(), 2);
+    assert_eq!(s.paths[0].attr.fill, Some(Rgba8::from_raw(255, 0, 0, 255)));
+    assert_eq!(s.paths[1].attr.fill, Some(Rgba8::from_raw(0, 255, 0, 255)));
+    let cmds: Vec<_> = s.paths[0].path.vertices.iter().map(|v| v.cmd).collect();
+    assert_eq!(cmds.iter().filter(|&&c| c == PathCommand::Close).count(), 2);
+    assert_eq!(s.paths[1].path.vertices[0].x, 1.5);
+    let r = s.bounding_rect().unwrap();
+    assert_eq!((r.x1(), r.y1(), r.x2(), r.y2()), (0.0, 0.0, 30.0, 30.0));
+
+    assert_eq!(PathStorage::from_lion("M 0,0 L 1,1").unwrap_err().line, 1);
+    assert_eq!(PathStorage::from_lion("ff0000\nM 0,0 L 1;1").unwrap_err().line, 2);
+  }
+
+  #[test]
+  fn opacity() {
+    let mut attr = PathAttributes::fill(Rgba8::from_raw(10, 20, 30, 200));
+    attr.opacity = 0.5;
+    assert_eq!(attr.fill_color(), Some(Rgba8::from_raw(10, 20, 30, 100)));
+    assert_eq!(attr.stroke_color(), None);
+  }
+}
@@ -19,14 +19,49 @@
 //!     // Draw
 //!     // let mut ras = agg::RasterizerScanline::new();
 //!     // ras.add_path(&stroke);
+//!
+//! # Variable Width
+//!
+//!     let mut path = agg::Path::new();
+//!     path.move_to(  0.0,  0.0);
+//!     path.line_to(100.0, 50.0);
+//!     path.line_to(200.0,  0.0);
+//!
+//!     // Taper from 8.0 to 1.0 along the path
+//!     let mut stroke = agg::Stroke::new(&path);
+//!     stroke.width_function(|t| 8.0 - 7.0 * t);
+//!     stroke.line_join(agg::LineJoin::Round);
+//!
+//!     // One width for each vertex
+//!     let mut stroke = agg::Stroke::new(&path);
+//!     stroke.widths(&[1.0, 10.0, 1.0]);
+//!
+//! # Transformed
+//!
+//!     let mut path = agg::Path::new();
+//!     path.move_to( 0.0, 0.0);
+//!     path.line_to(10.0, 5.0);
+//!
+//!     // Width of 1.0 in path units, zoomed in and stretched horizontally,
+//!     //   round caps stay smooth
+//!     let mut stroke = agg::Stroke::new(&path);
+//!     stroke.width(1.0);
+//!     stroke.line_cap(agg::LineCap::Round);
+//!     stroke.transform(agg::Transform::scaling(20.0, 10.0));
 
+use crate::curves::flatten;
 use crate::paths::PathCommand;
+use crate::paths::PathOrientation;
 use crate::paths::Vertex;
 use crate::paths::cross;
 use crate::paths::len;
-use crate::paths::split;
+use crate::paths::preceive_polygon_orientation;
+use crate::paths::shorten_path;
+use crate::paths::sub_paths;
 
+use crate::Transform;
 use crate::VertexSource;
+use crate::Vertices;
 use std::f64::consts::PI;
 
 /// Line End or Cap Style
@@ -56,13 +91,64 @@ pub enum InnerJoin {
   Round,
 }
 
+/// Position of the Stroke on Closed Shapes
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum StrokeAlignment {
+  /// Centered on the outline
+  #[default]
+  Center,
+  /// Inside of the outline
+  Inside,
+  /// Outside of the outline
+  Outside,
+}
+
 /// Stroke for Paths and Vertex Sources
-///
-/// **Missing:** fn shorten() [private]
 #[derive(Debug)]
 pub struct Stroke<T: VertexSource> {
   /// Source of Verticies
   source: T,
+  /// Width, joins and caps
+  math: MathStroke,
+  /// Length removed from the start of open sub-paths, 0.0
+  shorten_start: f64,
+  /// Length removed from the end of open sub-paths, 0.0
+  shorten: f64,
+  /// Width along the path, constant by default
+  profile: WidthProfile,
+  /// Side of closed sub-paths covered, Center
+  alignment: StrokeAlignment,
+  /// Transform of the outline, after stroking
+  trans: Option<Transform>,
+}
+
+/// Width of a [`Stroke`] along the path
+#[derive(Default)]
+enum WidthProfile {
+  /// Width of the stroke geometry everywhere
+  #[default]
+  Constant,
+  /// Width at each vertex, interpolated between them
+  PerVertex(Vec<f64>),
+  /// Width as a function of the normalized arc length of each sub-path
+  Function(Box<dyn Fn(f64) -> f64 + Send + Sync>),
+}
+
+impl std::fmt::Debug for WidthProfile {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Constant => write!(f, "Constant"),
+      Self::PerVertex(w) => f.debug_tuple("PerVertex").field(w).finish(),
+      Self::Function(_) => write!(f, "Function(..)"),
+    }
+  }
+}
+
+/// Stroke Geometry, shared by [`Stroke`] and [`Contour`](crate::Contour)
+///
+/// Computes caps and joins at the vertices of a path
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct MathStroke {
   /// Width of line in pixels, can be negative, 0.5
   width: f64,
   /// Absolute value of the width in pixel, 0.5
@@ -76,7 +162,7 @@ pub struct Stroke<T: VertexSource> {
   /// Maximum Length of the inner miter at segment intersections, 1.01
   inner_miter_limit: f64,
   /// Approximation scale, 1.0
-  approx_scale: f64,
+  pub(crate) approx_scale: f64,
   /// Line Cap Style
   line_cap: LineCap,
   /// Line Join Style
@@ -90,7 +176,25 @@ where
   T: VertexSource,
 {
   fn xconvert(&self) -> Vec<Vertex<f64>> {
-    self.stroke()
+    self.vertices().collect()
+  }
+  fn vertices(&self) -> Vertices<'_> {
+    // Stroke geometry, flattened for the transform
+    let mut base = self.math;
+    if let Some(trans) = &self.trans {
+      base.approximation_scale(base.approx_scale * trans.scale());
+    }
+    // Vertices with a width, for per-vertex widths
+    let mut nwidths = 0;
+    let outline = sub_paths(flatten(self.source.vertices(), base.approx_scale))
+      .flat_map(move |v| self.stroke(&v, base, &mut nwidths));
+    match self.trans {
+      Some(trans) => Box::new(outline.map(move |v| {
+        let (x, y) = trans.transform(v.x, v.y);
+        Vertex::new(x, y, v.cmd)
+      })),
+      None => Box::new(outline),
+    }
   }
 }
 
@@ -118,23 +222,46 @@ where
   pub fn new(source: T) -> Self {
     Stroke {
       source,
-      width: 0.5,
-      width_abs: 0.5,
-      width_eps: 0.5 / 1024.0,
-      width_sign: 1.0,
-      miter_limit: 4.0,
-      inner_miter_limit: 1.01,
-      approx_scale: 1.0,
-      inner_join: InnerJoin::Miter,
-      line_cap: LineCap::Butt,
-      line_join: LineJoin::Miter,
+      math: MathStroke::new(),
+      shorten_start: 0.0,
+      shorten: 0.0,
+      profile: WidthProfile::Constant,
+      alignment: StrokeAlignment::Center,
+      trans: None,
     }
   }
   /// Set the Stroke Width
+  ///
+  /// Replaces any widths set with [`widths`](Self::widths) or
+  ///   [`width_function`](Self::width_function)
   pub fn width(&mut self, width: f64) {
-    self.width = width / 2.0;
-    self.width_abs = self.width.abs();
-    self.width_sign = if self.width < 0.0 { -1.0 } else { 1.0 };
+    self.math.width(width);
+    self.profile = WidthProfile::Constant;
+  }
+  /// Set a Stroke Width at each vertex
+  ///
+  /// Widths are given for the vertices of all sub-paths in order, after
+  ///   curves are flattened, and change linearly between them. Closed
+  ///   sub-paths return to the width of their first vertex. Vertices without
+  ///   a width use the last one.
+  ///
+  /// Joins and caps use the width at their vertex
+  pub fn widths(&mut self, widths: &[f64]) {
+    self.profile = WidthProfile::PerVertex(widths.to_vec());
+  }
+  /// Set the Stroke Width as a function along the path
+  ///
+  /// The function is given the distance along each sub-path divided by its
+  ///   length, from 0.0 to 1.0, and returns the width there. It is sampled
+  ///   at the vertices, so long segments may be split first with
+  ///   [`ConvSegmentator`](crate::ConvSegmentator) to follow it closely.
+  ///
+  /// Joins and caps use the width at their vertex
+  pub fn width_function<F>(&mut self, f: F)
+  where
+    F: Fn(f64) -> f64 + Send + Sync + 'static,
+  {
+    self.profile = WidthProfile::Function(Box::new(f));
   }
   /// Set Line cap style
   ///
@@ -143,7 +270,7 @@ where
   ///   - `Square`
   ///   - `Round`
   pub fn line_cap(&mut self, line_cap: LineCap) {
-    self.line_cap = line_cap;
+    self.math.line_cap(line_cap);
   }
   /// Set Line Join style
   ///
@@ -157,13 +284,7 @@ where
   /// Variants of `MiterAccurate` and `None` are not available and will
   /// be reset to `Miter`
   pub fn line_join(&mut self, line_join: LineJoin) {
-    self.line_join = line_join;
-    if self.line_join == LineJoin::MiterAccurate {
-      self.line_join = LineJoin::Miter;
-    }
-    if self.line_join == LineJoin::None {
-      self.line_join = LineJoin::Miter;
-    }
+    self.math.line_join(line_join);
   }
   /// Set Inner Join style
   ///
@@ -173,11 +294,33 @@ where
   ///   - `Jag`
   ///   - `Round`
   pub fn inner_join(&mut self, inner_join: InnerJoin) {
-    self.inner_join = inner_join;
+    self.math.inner_join(inner_join);
   }
   /// Set miter limit
   pub fn miter_limit(&mut self, miter_limit: f64) {
-    self.miter_limit = miter_limit;
+    self.math.miter_limit(miter_limit);
+  }
+  /// Set the Stroke Alignment of closed sub-paths
+  ///
+  /// Available options are
+  ///   - `Center`, half of the width on each side of the outline
+  ///   - `Inside`, the whole width inside
+  ///   - `Outside`, the whole width outside
+  ///
+  /// Sides are found from the orientation of each sub-path. Open sub-paths
+  ///   are always centered.
+  ///
+  /// The band is bounded by the outline offset by the full width. The offset
+  ///   is exact while the width is less than half of the narrowest part of
+  ///   the shape for `Inside`, or of its narrowest notch for `Outside`.
+  ///   Wider strokes fold the offset outline over itself and may fill past
+  ///   the band.
+  pub fn alignment(&mut self, alignment: StrokeAlignment) {
+    self.alignment = alignment;
+  }
+  /// Get the Stroke Alignment
+  pub fn get_alignment(&self) -> StrokeAlignment {
+    self.alignment
   }
   // Set miter limit theta
   //pub fn miter_limit_theta(&mut self, miter_limit_theta: f64) {
@@ -185,14 +328,281 @@ where
   //}
   /// Set inner miter limit
   pub fn inner_miter_limit(&mut self, inner_miter_limit: f64) {
-    self.inner_miter_limit = inner_miter_limit;
+    self.math.inner_miter_limit(inner_miter_limit);
   }
   /// Set approximation scale
+  ///
+  /// Multiplied by the scale of the [`transform`](Self::transform), if any
   pub fn approximation_scale(&mut self, scale: f64) {
+    self.math.approximation_scale(scale);
+  }
+  /// Transform the outline after stroking
+  ///
+  /// The path is stroked in its own units and the outline is transformed,
+  ///   so the pen is transformed with the path: a non-uniform scale gives
+  ///   an elliptical pen, as in SVG. Curves, round joins and round caps are
+  ///   flattened for the scale of the transform.
+  ///
+  /// Converters before the stroke which flatten curves, like [`Dash`] or
+  ///   [`Contour`](crate::Contour), do not know about the transform, set
+  ///   their `approximation_scale` to its [`scale`](Transform::scale) too.
+  pub fn transform(&mut self, trans: Transform) {
+    self.trans = Some(trans);
+  }
+  /// Get the transform of the outline, identity if not set
+  pub fn get_transform(&self) -> Transform {
+    self.trans.unwrap_or(Transform::identity())
+  }
+  /// Shorten the end of open sub-paths
+  ///
+  /// Keeps the line from poking through an arrowhead, see
+  ///   [`ConvMarker`](crate::ConvMarker)
+  pub fn shorten(&mut self, shorten: f64) {
+    self.shorten = shorten;
+  }
+  /// Shorten the start of open sub-paths
+  pub fn shorten_start(&mut self, shorten: f64) {
+    self.shorten_start = shorten;
+  }
+  /// Stroke a sub-path
+  ///
+  /// There is lots of logic here and probably overly complex
+  fn stroke(&self, v0: &[Vertex<f64>], base: MathStroke, nwidths: &mut usize) -> Vec<Vertex<f64>> {
+    let mut outf = vec![];
+    // Width along the original path
+    let width_at = self.width_at(v0, nwidths);
+    // Clean the current path, return new path
+    let mut v = clean_path(v0);
+    // Check for Closed Path Element
+    let closed = is_path_closed(&v);
+    if !closed {
+      shorten_path(&mut v, self.shorten_start, self.shorten);
+    }
+    // Sub-path of zero length, drawn as a dot by the caps. A lone MoveTo
+    //   is not a sub-path, it needs a LineTo or a Close
+    let dot = if closed {
+      v.iter().filter(|p| p.cmd.is_vertex()).count() == 1
+    } else {
+      v.len() == 1 && v0.iter().filter(|p| p.cmd.is_vertex()).count() > 1
+    };
+    if dot {
+      let mut math = base;
+      if let Some(width_at) = &width_at {
+        math.width(width_at(0.0));
+      }
+      return math.calc_dot(&v[0]);
+    }
+    if v.len() <= 1 {
+      return vec![];
+    }
+    // Ignore Closed Tag Element
+    let n = if closed { v.len() - 1 } else { v.len() };
+    let (n1, n2) = if closed { (0, n) } else { (1, n - 1) };
+    // Stroke geometry at each vertex
+    let math = match width_at {
+      None => vec![base; n],
+      Some(width_at) => {
+        let mut s = if closed { 0.0 } else { self.shorten_start.max(0.0) };
+        (0..n)
+          .map(|i| {
+            if i > 0 {
+              s += len(&v[i - 1], &v[i]);
+            }
+            let mut m = base;
+            m.width(width_at(s));
+            m
+          })
+          .collect()
+      }
+    };
+
+    if closed && self.alignment != StrokeAlignment::Center && n >= 3 {
+      return self.aligned(&v[..n], &math);
+    }
+
+    // Forward Path
+    if !closed {
+      outf.extend(math[0].calc_cap(&v[0], &v[1]));
+    }
+    for i in n1..n2 {
+      // Forward Path
+      outf.extend(math[i].calc_join(&v[prev!(i, n)], &v[curr!(i, n)], &v[next!(i, n)]));
+    }
+    if closed {
+      // Close the polygon
+      let n = outf.len();
+      let last = outf[n - 1];
+      outf.push(Vertex::close_polygon(last.x, last.y));
+    }
+
+    // Backward Path
+    let mut outb = vec![];
+    if !closed {
+      outb.extend(math[n - 1].calc_cap(&v[n - 1], &v[n - 2])); // End Cap
+    }
+    for i in (n1..n2).rev() {
+      // Backward Path
+      outb.extend(math[i].calc_join(&v[next!(i, n)], &v[curr!(i, n)], &v[prev!(i, n)]));
+    }
+    if closed {
+      // Set first point as a MoveTo
+      outb[0].cmd = PathCommand::MoveTo;
+      // Close the polygon, using the last point
+      let n = outb.len();
+      let last = outb[n - 1];
+      outb.push(Vertex::close_polygon(last.x, last.y));
+    } else {
+      // Close the polygon, using the last point
+      let n = outb.len();
+      let last = outb[n - 1];
+      outb.push(Vertex::close_polygon(last.x, last.y));
+    }
+
+    // Set First point as MoveTo
+    outf[0].cmd = PathCommand::MoveTo;
+    // Combine Forward and Backward Paths
+    outf.extend(outb);
+
+    outf
+  }
+  /// Stroke a closed sub-path on one side of its outline
+  ///
+  /// The outline itself, reversed, and its offset by the whole width
+  fn aligned(&self, v: &[Vertex<f64>], math: &[MathStroke]) -> Vec<Vertex<f64>> {
+    let n = v.len();
+    // Positive widths offset counter-clockwise polygons outwards
+    let ccw = preceive_polygon_orientation(v) == PathOrientation::CounterClockwise;
+    let outside = self.alignment == StrokeAlignment::Outside;
+    let sign = if ccw == outside { 1.0 } else { -1.0 };
+    let mut out = vec![];
+    for i in 0..n {
+      let mut m = math[i];
+      // The join geometry offsets by half the width it is given, so twice
+      //   the full width offsets the ring by the full width
+      let full = 2.0 * m.width_abs;
+      m.width(sign * 2.0 * full);
+      // Inner corners of the ring meet exactly, other inner joins would loop
+      //   back to the outline
+      m.inner_join(InnerJoin::Miter);
+      m.inner_miter_limit(f64::INFINITY);
+      out.extend(m.calc_join(&v[prev!(i, n)], &v[curr!(i, n)], &v[next!(i, n)]));
+    }
+    if out.is_empty() {
+      return out;
+    }
+    out[0].cmd = PathCommand::MoveTo;
+    let last = out[out.len() - 1];
+    out.push(Vertex::close_polygon(last.x, last.y));
+    out.push(Vertex::move_to(v[n - 1].x, v[n - 1].y));
+    out.extend(v[..n - 1].iter().rev().map(|p| Vertex::line_to(p.x, p.y)));
+    out.push(Vertex::close_polygon(v[0].x, v[0].y));
+    out
+  }
+  /// Width as a function of the distance along a sub-path
+  ///
+  /// Returns `None` for a constant width. `nwidths` counts the vertices
+  ///   given a width so far, for per-vertex widths.
+  fn width_at(&self, v: &[Vertex<f64>], nwidths: &mut usize) -> Option<Box<dyn Fn(f64) -> f64 + '_>> {
+    // Nothing to interpolate, skip measuring the sub-path
+    if matches!(self.profile, WidthProfile::Constant) {
+      return None;
+    }
+    let closed = is_path_closed(v);
+    let pts: Vec<_> = v.iter().filter(|v| v.cmd.is_vertex()).collect();
+    // Distance along the sub-path to each vertex, and back to the start if closed
+    let mut dist = Vec::with_capacity(pts.len() + 1);
+    for (i, p) in pts.iter().enumerate() {
+      dist.push(if i == 0 { 0.0 } else { dist[i - 1] + len(pts[i - 1], p) });
+    }
+    if closed && let (Some(first), Some(last)) = (pts.first(), pts.last()) {
+      dist.push(dist[dist.len() - 1] + len(last, first));
+    }
+    match &self.profile {
+      WidthProfile::Constant => None,
+      WidthProfile::Function(f) => {
+        let total = dist.last().copied().unwrap_or(0.0);
+        Some(Box::new(move |s| {
+          f(if total > 0.0 { (s / total).clamp(0.0, 1.0) } else { 0.0 })
+        }))
+      }
+      WidthProfile::PerVertex(widths) => {
+        let default = self.math.width * 2.0;
+        let mut w: Vec<f64> = (0..pts.len())
+          .map(|i| widths.get(*nwidths + i).or(widths.last()).copied().unwrap_or(default))
+          .collect();
+        *nwidths += pts.len();
+        if closed && let Some(&w0) = w.first() {
+          w.push(w0);
+        }
+        Some(Box::new(move |s| {
+          let i = dist.partition_point(|&d| d <= s);
+          if i == 0 {
+            return w[0];
+          }
+          if i >= dist.len() {
+            return w[w.len() - 1];
+          }
+          let (d0, d1) = (dist[i - 1], dist[i]);
+          w[i - 1] + (w[i] - w[i - 1]) * (s - d0) / (d1 - d0)
+        }))
+      }
+    }
+  }
+}
+
+impl MathStroke {
+  pub(crate) fn new() -> Self {
+    Self {
+      width: 0.5,
+      width_abs: 0.5,
+      width_eps: 0.5 / 1024.0,
+      width_sign: 1.0,
+      miter_limit: 4.0,
+      inner_miter_limit: 1.01,
+      approx_scale: 1.0,
+      inner_join: InnerJoin::Miter,
+      line_cap: LineCap::Butt,
+      line_join: LineJoin::Miter,
+    }
+  }
+  /// Set the Stroke Width
+  pub(crate) fn width(&mut self, width: f64) {
+    self.width = width / 2.0;
+    self.width_abs = self.width.abs();
+    self.width_sign = if self.width < 0.0 { -1.0 } else { 1.0 };
+  }
+  /// Set Line cap style
+  pub(crate) fn line_cap(&mut self, line_cap: LineCap) {
+    self.line_cap = line_cap;
+  }
+  /// Set Line Join style, `MiterAccurate` and `None` are reset to `Miter`
+  pub(crate) fn line_join(&mut self, line_join: LineJoin) {
+    self.line_join = line_join;
+    if self.line_join == LineJoin::MiterAccurate {
+      self.line_join = LineJoin::Miter;
+    }
+    if self.line_join == LineJoin::None {
+      self.line_join = LineJoin::Miter;
+    }
+  }
+  /// Set Inner Join style
+  pub(crate) fn inner_join(&mut self, inner_join: InnerJoin) {
+    self.inner_join = inner_join;
+  }
+  /// Set miter limit
+  pub(crate) fn miter_limit(&mut self, miter_limit: f64) {
+    self.miter_limit = miter_limit;
+  }
+  /// Set inner miter limit
+  pub(crate) fn inner_miter_limit(&mut self, inner_miter_limit: f64) {
+    self.inner_miter_limit = inner_miter_limit;
+  }
+  /// Set approximation scale
+  pub(crate) fn approximation_scale(&mut self, scale: f64) {
     self.approx_scale = scale;
   }
   /// Calculate Line End Cap
-  fn calc_cap(&self, v0: &Vertex<f64>, v1: &Vertex<f64>) -> Vec<Vertex<f64>> {
+  pub(crate) fn calc_cap(&self, v0: &Vertex<f64>, v1: &Vertex<f64>) -> Vec<Vertex<f64>> {
     let mut out = vec![];
     let dx = v1.x - v0.x;
     let dy = v1.y - v0.y;
@@ -244,10 +654,25 @@ where
     out
   }
 
+  /// Calculate the Caps of a sub-path of zero length at a point
+  ///
+  /// Round caps give a circle, Square caps a square along the axes and
+  ///   Butt caps nothing
+  pub(crate) fn calc_dot(&self, p: &Vertex<f64>) -> Vec<Vertex<f64>> {
+    if self.line_cap == LineCap::Butt {
+      return vec![];
+    }
+    let mut out = self.calc_cap(p, &Vertex::line_to(p.x + 1.0, p.y));
+    out.extend(self.calc_cap(p, &Vertex::line_to(p.x - 1.0, p.y)));
+    out[0].cmd = PathCommand::MoveTo;
+    let last = out[out.len() - 1];
+    out.push(Vertex::close_polygon(last.x, last.y));
+    out
+  }
   /// Calculate an Arc
   ///
   /// Returns Vertices represening the Arc
-  fn calc_arc(&self, x: f64, y: f64, dx1: f64, dy1: f64, dx2: f64, dy2: f64) -> Vec<Vertex<f64>> {
+  pub(crate) fn calc_arc(&self, x: f64, y: f64, dx1: f64, dy1: f64, dx2: f64, dy2: f64) -> Vec<Vertex<f64>> {
     let mut out = vec![];
     // Starting and Ending Angle
     let mut a1 = (dy1 * self.width_sign).atan2(dx1 * self.width_sign);
@@ -298,7 +723,7 @@ where
   /// Calculate a Miter Join
   ///
   /// Return the Miter Join for 3 points
-  fn calc_miter(
+  pub(crate) fn calc_miter(
     &self,
     p0: &Vertex<f64>,
     p1: &Vertex<f64>,
@@ -424,7 +849,7 @@ where
   ///     t = ----------------------------------
   ///          (bx-ax)(dy-cy) - (by-ay)(dx-cx)
   /// ```
-  fn calc_intersection(
+  pub(crate) fn calc_intersection(
     &self,
     ax: f64,
     ay: f64,
@@ -453,7 +878,7 @@ where
   /// Calculate the Join of Two Line Segments
   ///
   /// [SVG Line Joins](https://www.w3.org/TR/SVG/painting.html#LineJoin)
-  fn calc_join(&self, p0: &Vertex<f64>, p1: &Vertex<f64>, p2: &Vertex<f64>) -> Vec<Vertex<f64>> {
+  pub(crate) fn calc_join(&self, p0: &Vertex<f64>, p1: &Vertex<f64>, p2: &Vertex<f64>) -> Vec<Vertex<f64>> {
     let mut out = vec![];
     let len1 = len(p1, p0);
     let len2 = len(p2, p1);
@@ -570,85 +995,36 @@ where
     }
     out
   }
-  /// Stroke the Vertex Source
-  ///
-  /// There is lots of logic here and probably overly complex
-  fn stroke(&self) -> Vec<Vertex<f64>> {
-    let mut all_out = vec![];
-    // Get verticies from Vertex Source
-    let v0 = &self.source.xconvert();
-    // Split and loop along unique paths, ended by MoveTo's
-    let pairs = split(v0);
-    for (m1, m2) in pairs {
-      let mut outf = vec![];
-      // Clean the current path, return new path
-      let v = clean_path(&v0[m1..=m2]);
-      if v.len() <= 1 {
-        continue;
-      }
-      // Check for Closed Path Element
-      let closed = is_path_closed(&v);
-      // Ignore Closed Tag Element
-      let n = if closed { v.len() - 1 } else { v.len() };
-      let (n1, n2) = if closed { (0, n) } else { (1, n - 1) };
-
-      // Forward Path
-      if !closed {
-        outf.extend(self.calc_cap(&v[0], &v[1]));
-      }
-      for i in n1..n2 {
-        // Forward Path
-        outf.extend(self.calc_join(&v[prev!(i, n)], &v[curr!(i, n)], &v[next!(i, n)]));
-      }
-      if closed {
-        // Close the polygon
-        let n = outf.len();
-        let last = outf[n - 1];
-        outf.push(Vertex::close_polygon(last.x, last.y));
-      }
-
-      // Backward Path
-      let mut outb = vec![];
-      if !closed {
-        outb.extend(self.calc_cap(&v[n - 1], &v[n - 2])); // End Cap
-      }
-      for i in (n1..n2).rev() {
-        // Backward Path
-        outb.extend(self.calc_join(&v[next!(i, n)], &v[curr!(i, n)], &v[prev!(i, n)]));
-      }
-      if closed {
-        // Set first point as a MoveTo
-        outb[0].cmd = PathCommand::MoveTo;
-        // Close the polygon, using the last point
-        let n = outb.len();
-        let last = outb[n - 1];
-        outb.push(Vertex::close_polygon(last.x, last.y));
-      } else {
-        // Close the polygon, using the last point
-        let n = outb.len();
-        let last = outb[n - 1];
-        outb.push(Vertex::close_polygon(last.x, last.y));
-      }
-
-      // Set First point as MoveTo
-      outf[0].cmd = PathCommand::MoveTo;
-      // Combine Forward and Backward Paths
-      outf.extend(outb);
-
-      // Add to Path Collection
-      all_out.extend(outf);
-    }
-    all_out
-  }
 }
 
+/// Dashes for Paths and Vertex Sources
+///
+/// Follows SVG `stroke-dasharray` and `stroke-dashoffset`:
+///   - The pattern restarts at the start of each sub-path
+///   - Closed sub-paths are dashed across their closing segment, and a dash
+///     running over the start point is kept in one piece
+///   - Odd length patterns are repeated to make them even
+///   - Zero length dashes are kept as sub-paths of zero length, drawn as
+///     dots by [`Stroke`] with round or square caps
+///   - Negative offsets move the pattern forward
+///   - Empty patterns, or with a negative or zero total length, give the
+///     path undashed
+///
+/// Curves are flattened and the output is a set of open sub-paths,
+///   ready to be stroked
 pub struct Dash<S: VertexSource> {
+  /// Source of Vertices
   source: S,
+  /// Dash and gap lengths, alternating
   dashes: Vec<f64>,
-  total_dash_len: f64,
+  /// Offset into the pattern at the start of each sub-path, 0.0
   dash_start: f64,
+  /// Length removed from the start of open sub-paths, 0.0
+  shorten_start: f64,
+  /// Length removed from the end of open sub-paths, 0.0
   shorten: f64,
-  closed: bool,
+  /// Approximation scale of curves in the source, 1.0
+  approx_scale: f64,
 }
 
 impl<S> VertexSource for Dash<S>
@@ -656,7 +1032,14 @@ where
   S: VertexSource,
 {
   fn xconvert(&self) -> Vec<Vertex<f64>> {
-    self.draw()
+    self.vertices().collect()
+  }
+  fn vertices(&self) -> Vertices<'_> {
+    let pattern = self.pattern();
+    Box::new(
+      sub_paths(flatten(self.source.vertices(), self.approx_scale))
+        .flat_map(move |v| self.draw(&v, pattern.as_deref())),
+    )
   }
 }
 
@@ -664,119 +1047,173 @@ impl<S> Dash<S>
 where
   S: VertexSource,
 {
+  /// Create a new Dash from a Vertex Source, without a pattern
   pub fn new(source: S) -> Self {
     Self {
       dashes: vec![],
       source,
-      total_dash_len: 0.0,
       dash_start: 0.0,
+      shorten_start: 0.0,
       shorten: 0.0,
-      closed: false,
+      approx_scale: 1.0,
     }
   }
+  /// Remove all dashes
   pub fn remove_all_dashed(&mut self) {
     self.dashes.clear();
-    self.total_dash_len = 0.0;
   }
+  /// Add a dash and the gap following it
+  ///
+  /// Either length may be zero, negative lengths are ignored
   pub fn add_dash(&mut self, length: f64, gap: f64) {
-    if length <= 0.0 || gap <= 0.0 {
+    if length < 0.0 || gap < 0.0 {
       return;
     }
-    self.total_dash_len += length + gap;
     self.dashes.push(length);
     self.dashes.push(gap);
   }
+  /// Set the dash pattern, as SVG `stroke-dasharray`
+  ///
+  /// Lengths alternate between dashes and gaps, starting with a dash. An
+  ///   odd number of lengths is repeated once.
+  pub fn dash_array(&mut self, dashes: &[f64]) {
+    self.dashes = dashes.to_vec();
+  }
+  /// Shorten the end of open sub-paths, before dashing
   pub fn shorten(&mut self, shorten: f64) {
     self.shorten = shorten;
   }
+  /// Shorten the start of open sub-paths, before dashing
+  pub fn shorten_start(&mut self, shorten: f64) {
+    self.shorten_start = shorten;
+  }
+  /// Set the distance into the pattern at the start of each sub-path, as
+  ///   SVG `stroke-dashoffset`
+  ///
+  /// Negative values start before the pattern
   pub fn dash_start(&mut self, start: f64) {
     self.dash_start = start;
-    self.calc_dash_start(start.abs());
-  }
-  fn calc_dash_start(&self, ds: f64) -> (f64, usize) {
-    let mut curr_dash = 0;
-    let mut curr_dash_start = 0.0;
-    let mut ds = ds;
-    while ds > 0.0 {
-      if ds > self.dashes[curr_dash] {
-        ds -= self.dashes[curr_dash];
-        curr_dash += 1;
-        curr_dash_start = 0.0;
-        if curr_dash >= self.dashes.len() {
-          curr_dash = 0;
-        }
-      } else {
-        curr_dash_start = ds;
-        ds = 0.0;
-      }
-    }
-    (curr_dash_start, curr_dash)
   }
+  /// Remove all dashes
   pub fn remove_all(&mut self) {
     self.dashes.clear();
-    self.closed = false;
   }
-  fn draw(&self) -> Vec<Vertex<f64>> {
-    let mut out = vec![];
-    let src = self.source.xconvert();
-
-    if src.len() < 2 || self.dashes.len() < 2 {
-      return out;
+  /// Set the approximation scale of curves in the source
+  ///
+  /// Use the scale of any later transform, see [`Stroke::transform`](crate::Stroke::transform)
+  pub fn approximation_scale(&mut self, scale: f64) {
+    self.approx_scale = scale;
+  }
+  /// Dash pattern with an even length, `None` if the path is not dashed
+  fn pattern(&self) -> Option<Vec<f64>> {
+    let total: f64 = self.dashes.iter().sum();
+    if self.dashes.iter().any(|&d| d < 0.0 || !d.is_finite()) || total <= 0.0 {
+      return None;
+    }
+    let mut pattern = self.dashes.clone();
+    if pattern.len() % 2 == 1 {
+      pattern.extend_from_within(..);
     }
+    Some(pattern)
+  }
+  /// Index of the pattern entry at the start of a sub-path, and the length
+  ///   left in it
+  fn pattern_start(&self, pattern: &[f64]) -> (usize, f64) {
+    let total: f64 = pattern.iter().sum();
+    let mut ds = self.dash_start.rem_euclid(total);
     let mut i = 0;
-    let mut v1 = src[i];
-    i += 1;
-    let mut v2 = src[i];
-    let (mut x, mut y) = (v1.x, v1.y);
-    out.push(Vertex::move_to(x, y));
-    // Length of the Current Segment
-    let mut curr_rest = len(&v1, &v2);
-    let (mut curr_dash_start, mut curr_dash) = if self.dash_start >= 0.0 {
-      self.calc_dash_start(self.dash_start)
-    } else {
-      (self.dash_start, 0)
-    };
-    loop {
-      let dash_rest = self.dashes[curr_dash] - curr_dash_start;
-      let cmd = if curr_dash % 2 == 1 {
-        Vertex::move_to
-      } else {
-        Vertex::line_to
-      };
-      if curr_rest > dash_rest {
-        // Dash fits within the line segment
-        curr_rest -= dash_rest;
-        curr_dash += 1;
-        if curr_dash >= self.dashes.len() {
-          curr_dash = 0;
+    // Skip entries ending before the start, zero length ones at the start are kept
+    while ds > pattern[i] || (ds == pattern[i] && ds > 0.0) {
+      ds -= pattern[i];
+      i = (i + 1) % pattern.len();
+    }
+    (i, pattern[i] - ds)
+  }
+  /// Cut a polyline into dashes
+  ///
+  /// Returns the dashes, and if the first one begins at the start and the
+  ///   last one ends at the end
+  fn dash_polyline(&self, pts: &[Vertex<f64>], pattern: &[f64]) -> (Vec<Vec<Vertex<f64>>>, bool, bool) {
+    let (mut i, mut rest) = self.pattern_start(pattern);
+    let starts = i % 2 == 0 && rest > 0.0;
+    let mut dashes = vec![];
+    let mut dash = (i % 2 == 0).then(|| vec![pts[0]]);
+    for w in pts.windows(2) {
+      let (a, b) = (w[0], w[1]);
+      let d = len(&a, &b);
+      let mut t = 0.0;
+      // Pattern entries ending within the segment
+      while rest <= d - t {
+        t += rest;
+        let k = if d > 0.0 { t / d } else { 0.0 };
+        let p = Vertex::line_to(a.x + (b.x - a.x) * k, a.y + (b.y - a.y) * k);
+        if let Some(mut v) = dash.take() {
+          v.push(p);
+          dashes.push(v);
         }
-        curr_dash_start = 0.0;
-        x = v2.x - (v2.x - v1.x) * curr_rest / len(&v1, &v2);
-        y = v2.y - (v2.y - v1.y) * curr_rest / len(&v1, &v2);
-      } else {
-        // Dash is longer than line segment
-        curr_dash_start += curr_rest;
-        x = v2.x;
-        y = v2.y;
-        i += 1;
-        v1 = v2;
-        if self.closed {
-          if i > src.len() {
-            out.push(cmd(x, y));
-            break;
-          } else {
-            let n = if i >= src.len() { 0 } else { i };
-            v2 = src[n];
-          }
-        } else if i >= src.len() {
-          out.push(cmd(x, y));
-          break;
-        } else {
-          v2 = src[i];
+        i = (i + 1) % pattern.len();
+        rest = pattern[i];
+        if i % 2 == 0 {
+          dash = Some(vec![p]);
         }
-        curr_rest = len(&v1, &v2);
       }
-      out.push(cmd(x, y));
+      rest -= d - t;
+      if let Some(v) = dash.as_mut()
+        && d > t
+      {
+        v.push(b);
+      }
+    }
+    // A dash beginning at the very end has no length
+    let ends = dash.as_ref().is_some_and(|v| v.len() > 1);
+    if let Some(v) = dash
+      && ends
+    {
+      dashes.push(v);
+    }
+    (dashes, starts, ends)
+  }
+  /// Dash a sub-path
+  fn draw(&self, v: &[Vertex<f64>], pattern: Option<&[f64]>) -> Vec<Vertex<f64>> {
+    let mut out = vec![];
+    let closed = is_path_closed(v);
+    let mut pts: Vec<_> = v.iter().filter(|v| v.cmd.is_vertex()).copied().collect();
+    if !closed {
+      shorten_path(&mut pts, self.shorten_start, self.shorten);
+    }
+    if pts.len() < 2 {
+      return out;
+    }
+    let Some(pattern) = pattern else {
+      out.push(Vertex::move_to(pts[0].x, pts[0].y));
+      out.extend(pts[1..].iter().map(|p| Vertex::line_to(p.x, p.y)));
+      if closed {
+        let last = pts[pts.len() - 1];
+        out.push(Vertex::close_polygon(last.x, last.y));
+      }
+      return out;
+    };
+    let n = pts.len();
+    if closed {
+      pts.push(pts[0]);
+    }
+    let (mut dashes, starts, ends) = self.dash_polyline(&pts, pattern);
+    if closed && starts && ends {
+      if dashes.len() == 1 {
+        // Not broken anywhere
+        out.push(Vertex::move_to(pts[0].x, pts[0].y));
+        out.extend(pts[1..n].iter().map(|p| Vertex::line_to(p.x, p.y)));
+        let last = pts[n - 1];
+        out.push(Vertex::close_polygon(last.x, last.y));
+        return out;
+      }
+      // Join the dashes meeting at the start
+      let first = dashes.remove(0);
+      dashes.last_mut().unwrap().extend_from_slice(&first[1..]);
+    }
+    for dash in dashes {
+      out.push(Vertex::move_to(dash[0].x, dash[0].y));
+      out.extend(dash[1..].iter().map(|p| Vertex::line_to(p.x, p.y)));
     }
     out
   }
@@ -826,12 +1263,8 @@ fn clean_path(v: &[Vertex<f64>]) -> Vec<Vertex<f64>> {
   }
   // Path is closed
   let first = out[0];
-  loop {
-    // Get Last LineTo Command
-    let i = match last_line_to(&out) {
-      Some(i) => i,
-      None => panic!("Missing Last Line To"),
-    };
+  // Last LineTo Command, none left if the path is a single point
+  while let Some(i) = last_line_to(&out) {
     let last = out[i];
     // If last point and first are **NOT** the same, done
     if len(&first, &last) >= 1e-6 {
@@ -854,3 +1287,429 @@ fn last_line_to(v: &[Vertex<f64>]) -> Option<usize> {
   }
   None
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Path;
+  use crate::paths::split;
+
+  fn line() -> Path {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(50.0, 0.0);
+    path.line_to(100.0, 0.0);
+    path
+  }
+
+  /// Half height of the outline at x
+  fn half_height(v: &[Vertex<f64>], x: f64) -> f64 {
+    v.iter()
+      .filter(|v| (v.x - x).abs() < 1e-9)
+      .map(|v| v.y.abs())
+      .fold(0.0, f64::max)
+  }
+
+  #[test]
+  fn per_vertex_widths() {
+    let mut stroke = Stroke::new(line());
+    stroke.widths(&[2.0, 6.0, 10.0]);
+    let v = stroke.xconvert();
+    assert_eq!(half_height(&v, 0.0), 1.0);
+    assert_eq!(half_height(&v, 50.0), 3.0);
+    assert_eq!(half_height(&v, 100.0), 5.0);
+    // Missing widths repeat the last one
+    stroke.widths(&[2.0]);
+    assert_eq!(half_height(&stroke.xconvert(), 100.0), 1.0);
+    // Back to a constant width
+    stroke.width(4.0);
+    assert_eq!(half_height(&stroke.xconvert(), 100.0), 2.0);
+  }
+
+  #[test]
+  fn width_function() {
+    let mut stroke = Stroke::new(line());
+    stroke.width_function(|t| 2.0 + 8.0 * t);
+    let v = stroke.xconvert();
+    assert_eq!(half_height(&v, 0.0), 1.0);
+    assert_eq!(half_height(&v, 50.0), 3.0);
+    assert_eq!(half_height(&v, 100.0), 5.0);
+    // Shortening keeps the widths where they were along the path
+    stroke.shorten_start(25.0);
+    let v = stroke.xconvert();
+    assert_eq!(half_height(&v, 25.0), 2.0);
+    // A constant function matches a constant width
+    let mut a = Stroke::new(line());
+    a.width_function(|_| 3.0);
+    let mut b = Stroke::new(line());
+    b.width(3.0);
+    let (a, b) = (a.xconvert(), b.xconvert());
+    assert_eq!(a.len(), b.len());
+    assert!(
+      a.iter()
+        .zip(&b)
+        .all(|(a, b)| a.x == b.x && a.y == b.y && a.cmd == b.cmd)
+    );
+  }
+
+  #[test]
+  fn send_sync() {
+    fn is_send_sync<T: Send + Sync>(_: &T) {}
+    let mut stroke = Stroke::new(Path::new());
+    is_send_sync(&stroke);
+    stroke.width_function(|t| 1.0 + t);
+    is_send_sync(&stroke);
+  }
+
+  #[test]
+  fn closed_widths() {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(40.0, 0.0);
+    path.line_to(40.0, 40.0);
+    path.line_to(0.0, 40.0);
+    path.close_polygon();
+    let mut stroke = Stroke::new(&path);
+    stroke.widths(&[2.0, 4.0, 6.0, 8.0]);
+    stroke.line_join(LineJoin::Miter);
+    let v = stroke.xconvert();
+    // Miter corners, outside and inside, at each local width
+    let has = |x: f64, y: f64| v.iter().any(|v| (v.x - x).abs() < 1e-9 && (v.y - y).abs() < 1e-9);
+    assert!(has(-1.0, -1.0) && has(1.0, 1.0));
+    assert!(has(42.0, -2.0) && has(38.0, 2.0));
+    assert!(has(43.0, 43.0) && has(37.0, 37.0));
+    assert!(has(-4.0, 44.0) && has(4.0, 36.0));
+  }
+
+  #[test]
+  fn alignment() {
+    use crate::FillingRule;
+    use crate::paths::bounding_rect;
+    use crate::paths::contains;
+    for ccw in [true, false] {
+      let mut pts = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+      if !ccw {
+        pts.reverse();
+      }
+      let mut path = Path::new();
+      path.move_to(pts[0].0, pts[0].1);
+      for &(x, y) in &pts[1..] {
+        path.line_to(x, y);
+      }
+      path.close_polygon();
+      let mut stroke = Stroke::new(&path);
+      stroke.width(2.0);
+      let bounds = |s: &Stroke<&Path>| {
+        let r = bounding_rect(s).unwrap();
+        (r.x1(), r.y1(), r.x2(), r.y2())
+      };
+      assert_eq!(bounds(&stroke), (-1.0, -1.0, 11.0, 11.0));
+
+      stroke.alignment(StrokeAlignment::Inside);
+      assert_eq!(bounds(&stroke), (0.0, 0.0, 10.0, 10.0));
+      assert!(contains(&stroke, 1.5, 5.0, FillingRule::NonZero));
+      assert!(!contains(&stroke, 2.5, 5.0, FillingRule::NonZero));
+      assert!(!contains(&stroke, -0.5, 5.0, FillingRule::NonZero));
+
+      stroke.alignment(StrokeAlignment::Outside);
+      assert_eq!(bounds(&stroke), (-2.0, -2.0, 12.0, 12.0));
+      assert!(contains(&stroke, -1.5, 5.0, FillingRule::NonZero));
+      assert!(!contains(&stroke, 0.5, 5.0, FillingRule::NonZero));
+      assert!(!contains(&stroke, 5.0, 5.0, FillingRule::NonZero));
+    }
+  }
+
+  #[test]
+  fn alignment_concave() {
+    use crate::FillingRule;
+    use crate::paths::contains;
+    // L-shape, with a reflex corner at (10,10)
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(20.0, 0.0);
+    path.line_to(20.0, 10.0);
+    path.line_to(10.0, 10.0);
+    path.line_to(10.0, 20.0);
+    path.line_to(0.0, 20.0);
+    path.close_polygon();
+    for inner_join in [InnerJoin::Miter, InnerJoin::Round, InnerJoin::Jag, InnerJoin::Bevel] {
+      let mut stroke = Stroke::new(&path);
+      stroke.width(4.0);
+      stroke.inner_join(inner_join);
+      stroke.alignment(StrokeAlignment::Inside);
+      let inside = |x, y| contains(&stroke, x, y, FillingRule::NonZero);
+      // Band along the edges and around both kinds of corners
+      for (x, y) in [
+        (1.0, 10.0),
+        (10.0, 1.0),
+        (19.0, 5.0),
+        (9.0, 15.0),
+        (1.0, 1.0),
+        (19.0, 1.0),
+        (7.0, 7.0),
+      ] {
+        assert!(inside(x, y), "{inner_join:?} ({x},{y})");
+      }
+      // Past the band, and outside of the shape
+      for (x, y) in [
+        (5.0, 5.0),
+        (5.0, 15.0),
+        (15.0, 5.0),
+        (-0.5, -0.5),
+        (-1.0, 10.0),
+        (15.0, 15.0),
+        (11.0, 11.0),
+      ] {
+        assert!(!inside(x, y), "{inner_join:?} ({x},{y})");
+      }
+
+      stroke.alignment(StrokeAlignment::Outside);
+      let inside = |x, y| contains(&stroke, x, y, FillingRule::NonZero);
+      for (x, y) in [
+        (-1.0, 10.0),
+        (10.0, -1.0),
+        (21.0, 5.0),
+        (11.0, 15.0),
+        (-1.0, -1.0),
+        (11.0, 11.0),
+        (13.0, 13.0),
+      ] {
+        assert!(inside(x, y), "{inner_join:?} ({x},{y})");
+      }
+      for (x, y) in [
+        (5.0, 5.0),
+        (1.0, 1.0),
+        (9.0, 9.0),
+        (-5.0, 10.0),
+        (15.0, 15.0),
+        (25.0, 5.0),
+      ] {
+        assert!(!inside(x, y), "{inner_join:?} ({x},{y})");
+      }
+    }
+  }
+
+  #[test]
+  fn alignment_short_edges() {
+    use crate::FillingRule;
+    use crate::paths::contains;
+    // Square with corners cut, edges shorter than the width
+    let mut path = Path::new();
+    path.move_to(0.0, 1.0);
+    for (x, y) in [
+      (1.0, 0.0),
+      (19.0, 0.0),
+      (20.0, 1.0),
+      (20.0, 19.0),
+      (19.0, 20.0),
+      (1.0, 20.0),
+      (0.0, 19.0),
+    ] {
+      path.line_to(x, y);
+    }
+    path.close_polygon();
+    for inner_join in [InnerJoin::Miter, InnerJoin::Round, InnerJoin::Jag, InnerJoin::Bevel] {
+      let mut stroke = Stroke::new(&path);
+      stroke.width(4.0);
+      stroke.inner_join(inner_join);
+      stroke.alignment(StrokeAlignment::Inside);
+      // Inset octagon, without loops at the corners, then the outline
+      let v = stroke.xconvert();
+      let (m1, m2) = split(&v)[0];
+      assert_eq!(m2 - m1 + 1, 9, "{inner_join:?}");
+      let inside = |x, y| contains(&stroke, x, y, FillingRule::NonZero);
+      for (x, y) in [(2.0, 2.0), (3.0, 10.0), (10.0, 3.0), (18.0, 18.0)] {
+        assert!(inside(x, y), "{inner_join:?} ({x},{y})");
+      }
+      for (x, y) in [
+        (0.2, 0.2),
+        (-1.0, 1.0),
+        (1.0, -1.0),
+        (-2.0, 5.0),
+        (5.0, 5.0),
+        (19.9, 19.9),
+      ] {
+        assert!(!inside(x, y), "{inner_join:?} ({x},{y})");
+      }
+    }
+  }
+
+  fn straight(length: f64) -> Path {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(length, 0.0);
+    path
+  }
+
+  /// Start and end x of each dash
+  fn dash_xs<T: VertexSource>(dash: &Dash<T>) -> Vec<(f64, f64)> {
+    let v = dash.xconvert();
+    let mut out = vec![];
+    for (m1, m2) in split(&v) {
+      out.push((v[m1].x, v[m2].x));
+    }
+    out
+  }
+
+  #[test]
+  fn dash_pattern() {
+    let mut dash = Dash::new(straight(10.0));
+    // Nothing to dash
+    assert_eq!(dash_xs(&dash), [(0.0, 10.0)]);
+    // Odd length patterns are repeated
+    dash.dash_array(&[2.0]);
+    assert_eq!(dash_xs(&dash), [(0.0, 2.0), (4.0, 6.0), (8.0, 10.0)]);
+    // Negative offsets move the pattern forward
+    dash.dash_array(&[3.0, 2.0]);
+    dash.dash_start(-1.0);
+    assert_eq!(dash_xs(&dash), [(1.0, 4.0), (6.0, 9.0)]);
+    dash.dash_start(4.0);
+    assert_eq!(dash_xs(&dash), [(1.0, 4.0), (6.0, 9.0)]);
+    // Invalid patterns are ignored
+    dash.dash_array(&[3.0, -2.0]);
+    assert_eq!(dash_xs(&dash), [(0.0, 10.0)]);
+    // Shortened before dashing
+    dash.dash_array(&[]);
+    dash.shorten(2.0);
+    dash.shorten_start(1.0);
+    assert_eq!(dash_xs(&dash), [(1.0, 8.0)]);
+  }
+
+  #[test]
+  fn dash_per_sub_path() {
+    let mut path = straight(10.0);
+    path.move_to(0.0, 5.0);
+    path.line_to(10.0, 5.0);
+    let mut dash = Dash::new(&path);
+    dash.add_dash(4.0, 4.0);
+    assert_eq!(dash_xs(&dash), [(0.0, 4.0), (8.0, 10.0), (0.0, 4.0), (8.0, 10.0)]);
+  }
+
+  #[test]
+  fn dash_dots() {
+    use crate::FillingRule;
+    use crate::paths::contains;
+    let mut dash = Dash::new(straight(10.0));
+    dash.add_dash(0.0, 5.0);
+    assert_eq!(dash_xs(&dash), [(0.0, 0.0), (5.0, 5.0), (10.0, 10.0)]);
+    let mut stroke = Stroke::new(&dash);
+    stroke.width(2.0);
+    stroke.line_cap(LineCap::Round);
+    for x in [0.0, 5.0, 10.0] {
+      assert!(contains(&stroke, x + 0.5, 0.5, FillingRule::NonZero));
+    }
+    assert!(!contains(&stroke, 2.5, 0.0, FillingRule::NonZero));
+    // Nothing to see with butt caps
+    stroke.line_cap(LineCap::Butt);
+    assert!(stroke.xconvert().is_empty());
+  }
+
+  #[test]
+  fn zero_length_closed() {
+    use crate::parse_path;
+    for d in ["M10 10 L10 10", "M10 10 L10 10 Z", "M10 10 Z"] {
+      let mut stroke = Stroke::new(parse_path(d).unwrap());
+      stroke.width(2.0);
+      stroke.line_cap(LineCap::Round);
+      let v = stroke.xconvert();
+      // Round dot around the point
+      assert!(v.len() > 4, "{d}");
+      assert!(v.iter().all(|v| (v.x - 10.0).hypot(v.y - 10.0) <= 1.0 + 1e-9), "{d}");
+      assert_eq!(v[v.len() - 1].cmd, PathCommand::Close, "{d}");
+      stroke.line_cap(LineCap::Butt);
+      assert!(stroke.xconvert().is_empty(), "{d}");
+    }
+  }
+
+  #[test]
+  fn dash_closed() {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(10.0, 0.0);
+    path.line_to(10.0, 10.0);
+    path.line_to(0.0, 10.0);
+    path.close_polygon();
+    let mut dash = Dash::new(&path);
+    dash.add_dash(10.0, 10.0);
+    dash.dash_start(5.0);
+    let v = dash.xconvert();
+    // The dash over the start point is one piece, around the closing corner
+    let xy: Vec<_> = v.iter().map(|v| (v.cmd, v.x, v.y)).collect();
+    assert_eq!(xy, [
+      (PathCommand::MoveTo, 10.0, 5.0),
+      (PathCommand::LineTo, 10.0, 10.0),
+      (PathCommand::LineTo, 5.0, 10.0),
+      (PathCommand::MoveTo, 0.0, 5.0),
+      (PathCommand::LineTo, 0.0, 0.0),
+      (PathCommand::LineTo, 5.0, 0.0),
+    ]);
+    // Longer than the path, left closed
+    dash.dash_array(&[100.0, 1.0]);
+    dash.dash_start(0.0);
+    let v = dash.xconvert();
+    assert_eq!(v.len(), 5);
+    assert_eq!(v[4].cmd, PathCommand::Close);
+  }
+
+  #[test]
+  fn transformed_pen() {
+    use crate::paths::bounding_rect;
+    let bounds = |s: &Stroke<Path>| {
+      let r = bounding_rect(s).unwrap();
+      (r.x1(), r.y1(), r.x2(), r.y2())
+    };
+    let mut stroke = Stroke::new(straight(10.0));
+    stroke.width(2.0);
+    stroke.line_cap(LineCap::Round);
+    let n = stroke.xconvert().len();
+    // Pen is stretched with the path
+    stroke.transform(Transform::scaling(1.0, 3.0));
+    let (x1, y1, x2, y2) = bounds(&stroke);
+    assert_eq!((x1.round(), y1, x2.round(), y2), (-1.0, -3.0, 11.0, 3.0));
+    // Caps are smoother for larger scales
+    stroke.transform(Transform::scaling(10.0, 10.0));
+    assert!(stroke.xconvert().len() > n);
+    assert_eq!(stroke.get_transform(), Transform::scaling(10.0, 10.0));
+    // Same as stroking with the approximation scale, then transforming
+    let mut other = Stroke::new(straight(10.0));
+    other.width(2.0);
+    other.line_cap(LineCap::Round);
+    other.approximation_scale(10.0);
+    let a = stroke.xconvert();
+    let b = other.transformed(Transform::scaling(10.0, 10.0)).xconvert();
+    assert_eq!(a.len(), b.len());
+    assert!(
+      a.iter()
+        .zip(&b)
+        .all(|(a, b)| a.x == b.x && a.y == b.y && a.cmd == b.cmd)
+    );
+  }
+
+  #[test]
+  fn transformed_dash() {
+    // Quarter circle of radius 4, dashed and stroked, zoomed 20 times
+    let k = 4.0 * 0.5523;
+    let mut path = Path::new();
+    path.move_to(4.0, 0.0);
+    path.curve4(4.0, k, k, 4.0, 0.0, 4.0);
+    // Largest distance of the outline from the band of the ideal stroke, in pixels
+    let error = |scale: f64| {
+      let mut dash = Dash::new(&path);
+      dash.dash_array(&[1.0, 0.5]);
+      dash.approximation_scale(scale);
+      let mut stroke = Stroke::new(dash);
+      stroke.width(0.2);
+      stroke.transform(Transform::scaling(20.0, 20.0));
+      stroke
+        .xconvert()
+        .iter()
+        .map(|v| v.x.hypot(v.y))
+        .map(|r| f64::max(r - 82.0, 78.0 - r).max(0.0))
+        .fold(0.0, f64::max)
+    };
+    // Flattened for the source units, faceted once zoomed
+    assert!(error(1.0) > 0.5, "{}", error(1.0));
+    // Flattened for the zoom, within a fraction of a pixel
+    assert!(error(20.0) < 0.2, "{}", error(20.0));
+  }
+}
@@ -0,0 +1,324 @@
+//! Transformations along Paths
+//!
+//! Warp shapes so their x axis follows a guide path, or so they fill the
+//!   band between two guide paths, ports of AGG's `trans_single_path` and
+//!   `trans_double_path`
+//!
+//! # Example
+//!
+//!     // Road
+//!     let mut road = agg::Path::new();
+//!     road.move_to( 10.0, 100.0);
+//!     road.curve3(100.0, 20.0, 190.0, 100.0);
+//!
+//!     // Street name, subdivided so the letters bend
+//!     let mut text = agg::GsvText::new();
+//!     text.size(12.0, 0.0);
+//!     text.text("Main Street");
+//!     text.flip(true);
+//!     let mut segments = agg::ConvSegmentator::new(text);
+//!     segments.approximation_scale(0.5);
+//!
+//!     // Warp along the road, then outline the letters
+//!     let along = agg::TransSinglePath::new(&road);
+//!     let label = agg::ConvTransform::new(segments, &along);
+//!     let mut stroke = agg::Stroke::new(label);
+//!     stroke.width(1.0);
+//!
+//!     // Draw
+//!     // let mut ras = agg::RasterizerScanline::new();
+//!     // ras.add_path(&stroke);
+
+use crate::Transformer;
+use crate::VertexSource;
+use crate::curves::ConvCurve;
+use crate::paths::Vertex;
+use crate::paths::len;
+
+/// Flattened guide path with cumulative distances
+#[derive(Debug, Clone, Default)]
+struct Guide {
+  /// Vertices, without repeated positions
+  points: Vec<Vertex<f64>>,
+  /// Distance from the start to each vertex
+  dist: Vec<f64>,
+}
+
+impl Guide {
+  /// Flatten a Vertex Source, all sub-paths are joined into one
+  fn new<VS: VertexSource>(source: &VS) -> Self {
+    let mut points: Vec<Vertex<f64>> = vec![];
+    for v in ConvCurve::new(source).vertices().filter(|v| v.cmd.is_vertex()) {
+      if points.last().is_none_or(|last| len(last, &v) > 1e-6) {
+        points.push(v);
+      }
+    }
+    let mut dist = Vec::with_capacity(points.len());
+    for (i, p) in points.iter().enumerate() {
+      dist.push(if i == 0 {
+        0.0
+      } else {
+        dist[i - 1] + len(&points[i - 1], p)
+      });
+    }
+    Self { points, dist }
+  }
+  /// At least one segment
+  fn is_valid(&self) -> bool {
+    self.points.len() >= 2
+  }
+  fn length(&self) -> f64 {
+    self.dist.last().copied().unwrap_or(0.0)
+  }
+  /// Point at a distance along the guide, and the unit direction there
+  ///
+  /// Distances beyond the ends extend the first and last segments. Without
+  ///   `preserve_x_scale` every segment covers the same distance.
+  fn at(&self, x: f64, preserve_x_scale: bool) -> (f64, f64, f64, f64) {
+    let n = self.points.len();
+    let (i, d) = if x < 0.0 {
+      (0, x)
+    } else if x > self.length() {
+      (n - 2, x - self.dist[n - 2])
+    } else if preserve_x_scale {
+      let i = self.dist.partition_point(|&di| di <= x).clamp(1, n - 1) - 1;
+      (i, x - self.dist[i])
+    } else {
+      let k = x * (n - 1) as f64 / self.length();
+      let i = (k as usize).min(n - 2);
+      (i, (k - i as f64) * (self.dist[i + 1] - self.dist[i]))
+    };
+    let (a, b) = (self.points[i], self.points[i + 1]);
+    let dd = self.dist[i + 1] - self.dist[i];
+    let (ux, uy) = ((b.x - a.x) / dd, (b.y - a.y) / dd);
+    (a.x + ux * d, a.y + uy * d, ux, uy)
+  }
+}
+
+/// Transformation along a Single Path
+///
+/// The x axis is bent along the guide path and y is the distance from it,
+///   positive to the left of the direction of travel in a y-up space. Shapes
+///   should be subdivided first, see [`ConvSegmentator`](crate::ConvSegmentator).
+///
+/// Points are left unchanged if the guide has no length.
+#[derive(Debug, Clone)]
+pub struct TransSinglePath {
+  /// Guide path
+  guide: Guide,
+  /// Length of x mapped onto the whole guide, 0.0 uses the guide length
+  base_length: f64,
+  /// Keep distances along each guide segment, true
+  preserve_x_scale: bool,
+}
+
+impl TransSinglePath {
+  /// Create a new Transformation from a guide path
+  pub fn new<VS: VertexSource>(guide: &VS) -> Self {
+    Self {
+      guide: Guide::new(guide),
+      base_length: 0.0,
+      preserve_x_scale: true,
+    }
+  }
+  /// Set the length of x that covers the whole guide
+  ///
+  /// Shapes are stretched or squeezed to fit, 0.0 disables scaling
+  pub fn base_length(&mut self, base_length: f64) {
+    self.base_length = base_length;
+  }
+  /// Get the base length
+  pub fn get_base_length(&self) -> f64 {
+    self.base_length
+  }
+  /// Keep the x scale along each segment of the guide
+  ///
+  /// When disabled, every guide segment covers the same range of x, so
+  ///   shapes stretch where the guide has long segments
+  pub fn preserve_x_scale(&mut self, preserve: bool) {
+    self.preserve_x_scale = preserve;
+  }
+  /// Get if the x scale is kept
+  pub fn get_preserve_x_scale(&self) -> bool {
+    self.preserve_x_scale
+  }
+  /// Length of the guide
+  pub fn total_length(&self) -> f64 {
+    if self.base_length > 1e-10 {
+      self.base_length
+    } else {
+      self.guide.length()
+    }
+  }
+  /// Perform the transform
+  pub fn transform(&self, x: f64, y: f64) -> (f64, f64) {
+    if !self.guide.is_valid() {
+      return (x, y);
+    }
+    let x = if self.base_length > 1e-10 {
+      x * self.guide.length() / self.base_length
+    } else {
+      x
+    };
+    let (px, py, ux, uy) = self.guide.at(x, self.preserve_x_scale);
+    (px - y * uy, py + y * ux)
+  }
+}
+
+impl Transformer for TransSinglePath {
+  fn transform(&self, x: f64, y: f64) -> (f64, f64) {
+    TransSinglePath::transform(self, x, y)
+  }
+}
+
+/// Transformation between Two Paths
+///
+/// The x axis follows both guide paths, at the same fraction of their
+///   lengths, and y goes from the first guide at 0.0 to the second one at
+///   the base height. Shapes should be subdivided first, see
+///   [`ConvSegmentator`](crate::ConvSegmentator).
+///
+/// Points are left unchanged if either guide has no length.
+#[derive(Debug, Clone)]
+pub struct TransDoublePath {
+  /// First guide path, at y = 0
+  guide1: Guide,
+  /// Second guide path, at y = base height
+  guide2: Guide,
+  /// Length of x mapped onto the whole guides, 0.0 uses the first guide length
+  base_length: f64,
+  /// Height between the guides, 1.0
+  base_height: f64,
+  /// Keep distances along each guide segment, true
+  preserve_x_scale: bool,
+}
+
+impl TransDoublePath {
+  /// Create a new Transformation from two guide paths
+  pub fn new<VS1: VertexSource, VS2: VertexSource>(guide1: &VS1, guide2: &VS2) -> Self {
+    Self {
+      guide1: Guide::new(guide1),
+      guide2: Guide::new(guide2),
+      base_length: 0.0,
+      base_height: 1.0,
+      preserve_x_scale: true,
+    }
+  }
+  /// Set the length of x that covers the whole guides
+  ///
+  /// Shapes are stretched or squeezed to fit, 0.0 disables scaling
+  pub fn base_length(&mut self, base_length: f64) {
+    self.base_length = base_length;
+  }
+  /// Get the base length
+  pub fn get_base_length(&self) -> f64 {
+    self.base_length
+  }
+  /// Set the height of y that maps onto the second guide
+  pub fn base_height(&mut self, base_height: f64) {
+    self.base_height = base_height;
+  }
+  /// Get the base height
+  pub fn get_base_height(&self) -> f64 {
+    self.base_height
+  }
+  /// Keep the x scale along each segment of the guides, see
+  ///   [`TransSinglePath::preserve_x_scale`]
+  pub fn preserve_x_scale(&mut self, preserve: bool) {
+    self.preserve_x_scale = preserve;
+  }
+  /// Get if the x scale is kept
+  pub fn get_preserve_x_scale(&self) -> bool {
+    self.preserve_x_scale
+  }
+  /// Length of the first guide
+  pub fn total_length1(&self) -> f64 {
+    self.guide1.length()
+  }
+  /// Length of the second guide
+  pub fn total_length2(&self) -> f64 {
+    self.guide2.length()
+  }
+  /// Perform the transform
+  pub fn transform(&self, x: f64, y: f64) -> (f64, f64) {
+    if !self.guide1.is_valid() || !self.guide2.is_valid() {
+      return (x, y);
+    }
+    let (l1, l2) = (self.guide1.length(), self.guide2.length());
+    let x = if self.base_length > 1e-10 {
+      x * l1 / self.base_length
+    } else {
+      x
+    };
+    let (x1, y1, _, _) = self.guide1.at(x, self.preserve_x_scale);
+    let (x2, y2, _, _) = self.guide2.at(x * l2 / l1, self.preserve_x_scale);
+    let k = y / self.base_height;
+    (x1 + (x2 - x1) * k, y1 + (y2 - y1) * k)
+  }
+}
+
+impl Transformer for TransDoublePath {
+  fn transform(&self, x: f64, y: f64) -> (f64, f64) {
+    TransDoublePath::transform(self, x, y)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Path;
+
+  fn close(a: (f64, f64), b: (f64, f64)) -> bool {
+    (a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9
+  }
+
+  fn corner() -> Path {
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(10.0, 0.0);
+    path.line_to(10.0, 30.0);
+    path
+  }
+
+  #[test]
+  fn single_path() {
+    let mut t = TransSinglePath::new(&corner());
+    assert_eq!(t.total_length(), 40.0);
+    assert!(close(t.transform(5.0, 0.0), (5.0, 0.0)));
+    // Offsets are to the left of the direction of travel
+    assert!(close(t.transform(5.0, 2.0), (5.0, 2.0)));
+    assert!(close(t.transform(20.0, 2.0), (8.0, 10.0)));
+    // Beyond the ends
+    assert!(close(t.transform(-5.0, 0.0), (-5.0, 0.0)));
+    assert!(close(t.transform(45.0, 0.0), (10.0, 35.0)));
+    // Both segments cover half of x
+    t.preserve_x_scale(false);
+    assert!(close(t.transform(20.0, 0.0), (10.0, 0.0)));
+    assert!(close(t.transform(30.0, 0.0), (10.0, 15.0)));
+    // Fit a length onto the guide
+    t.preserve_x_scale(true);
+    t.base_length(4.0);
+    assert!(close(t.transform(2.0, 0.0), (10.0, 10.0)));
+  }
+
+  #[test]
+  fn double_path() {
+    let mut top = Path::new();
+    top.move_to(0.0, 10.0);
+    top.line_to(20.0, 10.0);
+    let mut t = TransDoublePath::new(&corner(), &top);
+    t.base_height(5.0);
+    assert_eq!((t.total_length1(), t.total_length2()), (40.0, 20.0));
+    // Same fraction along both guides
+    assert!(close(t.transform(20.0, 0.0), (10.0, 10.0)));
+    assert!(close(t.transform(20.0, 5.0), (10.0, 10.0)));
+    assert!(close(t.transform(0.0, 2.5), (0.0, 5.0)));
+    assert!(close(t.transform(40.0, 5.0), (20.0, 10.0)));
+  }
+
+  #[test]
+  fn empty_guide() {
+    let t = TransSinglePath::new(&Path::new());
+    assert_eq!(t.transform(3.0, 4.0), (3.0, 4.0));
+  }
+}
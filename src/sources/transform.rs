@@ -4,7 +4,9 @@ use crate::paths::Path;
 use crate::paths::Vertex;
 
 use crate::VertexSource;
+use crate::Vertices;
 
+use std::f64::consts::FRAC_1_SQRT_2;
 use std::ops::Mul;
 use std::ops::MulAssign;
 
@@ -93,6 +95,14 @@ impl Transform {
       x * self.shy + y * self.sy + self.ty,
     )
   }
+  /// Average scale factor of the transform
+  ///
+  /// Used to set the approximation scale of curves and strokes
+  pub fn scale(&self) -> f64 {
+    let x = FRAC_1_SQRT_2 * self.sx + FRAC_1_SQRT_2 * self.shx;
+    let y = FRAC_1_SQRT_2 * self.shy + FRAC_1_SQRT_2 * self.sy;
+    (x * x + y * y).sqrt()
+  }
   fn determinant(&self) -> f64 {
     self.sx * self.sy - self.shy * self.shx
   }
@@ -138,35 +148,83 @@ impl MulAssign<Transform> for Transform {
   }
 }
 
-/// Path Transform
+/// Point Transformation
+///
+/// Implemented by [`Transform`] and the non-linear
+///   [`TransPerspective`](crate::TransPerspective) and
+///   [`TransBilinear`](crate::TransBilinear), for use by
+///   [`ConvTransform`] and [`SpanGradient`](crate::SpanGradient)
+pub trait Transformer {
+  /// Transform a point
+  fn transform(&self, x: f64, y: f64) -> (f64, f64);
+  /// Straight lines stay straight and evenly spaced points stay evenly spaced
+  ///
+  /// Span interpolation is then exact from the end points alone, otherwise
+  ///   spans are subdivided
+  fn is_affine(&self) -> bool {
+    false
+  }
+}
+
+impl Transformer for Transform {
+  fn transform(&self, x: f64, y: f64) -> (f64, f64) {
+    Transform::transform(self, x, y)
+  }
+  fn is_affine(&self) -> bool {
+    true
+  }
+}
+
+impl<T: Transformer + ?Sized> Transformer for &T {
+  fn transform(&self, x: f64, y: f64) -> (f64, f64) {
+    (**self).transform(x, y)
+  }
+  fn is_affine(&self) -> bool {
+    (**self).is_affine()
+  }
+}
+
+/// Transform Converter for Paths and Vertex Sources
+///
+/// Only vertices are transformed, for non-linear transforms long segments
+///   should be subdivided first with a [`ConvSegmentator`](crate::ConvSegmentator)
 #[derive(Debug, Default)]
-pub struct ConvTransform {
-  /// Source Path to Transform
-  pub source: Path,
+pub struct ConvTransform<T: VertexSource = Path, Tr: Transformer = Transform> {
+  /// Source of Vertices to Transform
+  pub source: T,
   /// Transform to apply
-  pub trans: Transform,
+  pub trans: Tr,
 }
 
-impl VertexSource for ConvTransform {
+impl<T, Tr> VertexSource for ConvTransform<T, Tr>
+where
+  T: VertexSource,
+  Tr: Transformer,
+{
   /// Apply the Transform
   fn xconvert(&self) -> Vec<Vertex<f64>> {
     self.transform()
   }
+  fn vertices(&self) -> Vertices<'_> {
+    Box::new(self.source.vertices().map(|v| {
+      let (x, y) = self.trans.transform(v.x, v.y);
+      Vertex::new(x, y, v.cmd)
+    }))
+  }
 }
 
-impl ConvTransform {
-  /// Create a new Path Transform
-  pub fn new(source: Path, trans: Transform) -> Self {
+impl<T, Tr> ConvTransform<T, Tr>
+where
+  T: VertexSource,
+  Tr: Transformer,
+{
+  /// Create a new Transform Converter from a Vertex Source
+  pub fn new(source: T, trans: Tr) -> Self {
     Self { source, trans }
   }
-  /// Transform the Path
+  /// Transform the Vertex Source
   pub fn transform(&self) -> Vec<Vertex<f64>> {
-    let mut out = vec![];
-    for v in &self.source.xconvert() {
-      let (x, y) = self.trans.transform(v.x, v.y);
-      out.push(Vertex::new(x, y, v.cmd));
-    }
-    out
+    self.vertices().collect()
   }
 }
 
@@ -198,4 +256,30 @@ mod tests {
     assert!((x2 - 1.0).abs() < 1e-10);
     assert!((y2 - 1.0).abs() < 1e-10);
   }
+
+  #[test]
+  fn test_conv_transform_chain() {
+    use crate::paths::bounding_rect;
+    use crate::{Ellipse, Path};
+    let bounds = |vs: &dyn VertexSource| {
+      let r = bounding_rect(&vs).unwrap();
+      (r.x1().round(), r.y1().round(), r.x2().round(), r.y2().round())
+    };
+    // Any vertex source can be transformed
+    let e = Ellipse::new(0.0, 0.0, 10.0, 10.0, 32).transformed(Transform::translation(20.0, 30.0));
+    assert_eq!(bounds(&e), (10.0, 20.0, 30.0, 40.0));
+    // Stroke in a scaled space, then move the outline
+    let mut path = Path::new();
+    path.move_to(0.0, 0.0);
+    path.line_to(10.0, 0.0);
+    let outline = path
+      .clone()
+      .transformed(Transform::scaling(2.0, 2.0))
+      .stroked(4.0)
+      .transformed(Transform::translation(5.0, 5.0));
+    assert_eq!(bounds(&outline), (5.0, 3.0, 25.0, 7.0));
+    let dashes = path.dashed(&[(3.0, 2.0)]).xconvert();
+    let xs: Vec<_> = dashes.iter().map(|v| v.x).collect();
+    assert_eq!(xs, [0.0, 3.0, 5.0, 8.0]);
+  }
 }
@@ -0,0 +1,857 @@
+//! SVG Documents
+//!
+//! A minimal SVG renderer built on [`RasterizerScanline`]
+//!
+//! Supported elements are `svg`, `g`, `path`, `rect`, `circle`, `ellipse`,
+//! `line`, `polyline` and `polygon`; other elements are not rendered, along
+//! with their children. Styling may be given as presentation attributes or
+//! in the `style` attribute: `fill`, `fill-opacity`, `fill-rule`, `stroke`,
+//! `stroke-opacity`, `stroke-width`, `stroke-linejoin`, `stroke-linecap`,
+//! `stroke-miterlimit` and `opacity`. Gradients and patterns are not
+//! supported, a paint server with a fallback color uses the fallback.
+//!
+//! Nested `svg` elements establish a new viewport from their `x`, `y`,
+//! `width`, `height` and `viewBox`, their content is not clipped to it.
+//!
+//! Group opacity is applied to each shape in the group, overlapping shapes
+//! are not composited as one layer.
+//!
+//! Malformed markup, geometry and transforms are errors; invalid style values
+//! are ignored, as in CSS.
+//!
+//! # Example
+//!
+//! ```
+//! use agg::prelude::*;
+//!
+//! let doc: agg::SvgDocument = r#"
+//!   <svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+//!     <g transform="translate(50 50)" stroke="black" stroke-width="2">
+//!       <circle r="40" fill="yellow"/>
+//!       <path d="M -20 10 Q 0 30 20 10" fill="none"/>
+//!     </g>
+//!   </svg>"#
+//!   .parse()
+//!   .unwrap();
+//! assert_eq!(doc.shapes.len(), 2);
+//!
+//! let pix = Pixfmt::<Rgb8>::create(doc.width as i64, doc.height as i64);
+//! let mut ren_base = agg::RenderingBase::new(pix);
+//! ren_base.clear(Rgb8::WHITE);
+//! doc.render(&mut ren_base);
+//! ```
+
+use crate::color::Rgba8;
+use crate::paths::Path;
+use crate::stroke::{LineCap, LineJoin, Stroke};
+use crate::svg::SvgPathErrorKind;
+use crate::svg::color::parse_color;
+use crate::svg::parse_path;
+use crate::svg::xml::{Attribute, XmlEvent, XmlReader};
+use crate::transform::Transform;
+use crate::{Color, FillingRule, FromRaw4, Pixel, RasterizerScanline, Render, RenderingBase, VertexSource};
+use crate::{RenderingScanlineAASolid, render_scanlines};
+
+use std::str::FromStr;
+
+/// Error Kind while parsing an SVG document
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SvgErrorKind {
+  /// Document ended inside markup or with open elements
+  UnexpectedEof,
+  /// Character is not valid markup
+  UnexpectedCharacter(char),
+  /// Closing tag does not match the open element
+  MismatchedTag,
+  /// Document element is not `svg`
+  MissingRoot,
+  /// Geometry or transform attribute could not be parsed
+  InvalidAttribute,
+  /// Path data could not be parsed
+  PathData(SvgPathErrorKind),
+}
+
+/// Error while parsing an SVG document
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SvgError {
+  /// Byte offset of the error in the document
+  pub offset: usize,
+  /// Type of error
+  pub kind: SvgErrorKind,
+}
+
+impl std::fmt::Display for SvgError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self.kind {
+      SvgErrorKind::UnexpectedEof => write!(f, "unexpected end of document")?,
+      SvgErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character {c:?}")?,
+      SvgErrorKind::MismatchedTag => write!(f, "closing tag does not match")?,
+      SvgErrorKind::MissingRoot => write!(f, "document element must be svg")?,
+      SvgErrorKind::InvalidAttribute => write!(f, "invalid attribute value")?,
+      SvgErrorKind::PathData(kind) => write!(f, "invalid path data ({kind:?})")?,
+    }
+    write!(f, " at byte {}", self.offset)
+  }
+}
+
+impl std::error::Error for SvgError {}
+
+/// Single shape of an SVG document, ready to render
+#[derive(Debug, Clone)]
+pub struct SvgShape {
+  /// Outline in user coordinates
+  pub path: Path,
+  /// User to document coordinates
+  pub transform: Transform,
+  /// Fill color, including opacity
+  pub fill: Option<Rgba8>,
+  /// Filling Rule of the fill
+  pub fill_rule: FillingRule,
+  /// Stroke color, including opacity
+  pub stroke: Option<Rgba8>,
+  /// Stroke width in user coordinates
+  pub stroke_width: f64,
+  /// Line Join Style of the stroke
+  pub line_join: LineJoin,
+  /// Line Cap Style of the stroke
+  pub line_cap: LineCap,
+  /// Miter Limit of the stroke
+  pub miter_limit: f64,
+}
+
+/// Parsed SVG document
+#[derive(Debug, Clone, Default)]
+pub struct SvgDocument {
+  /// Width of the document, from the `svg` element
+  pub width: f64,
+  /// Height of the document, from the `svg` element
+  pub height: f64,
+  /// Shapes in painting order
+  pub shapes: Vec<SvgShape>,
+}
+
+impl SvgDocument {
+  /// Parse an SVG document
+  pub fn parse(src: &str) -> Result<Self, SvgError> {
+    let mut reader = XmlReader::new(src);
+    let mut doc = SvgDocument::default();
+    let mut stack: Vec<State> = vec![];
+    let mut root = false;
+    while let Some(event) = reader.next_event()? {
+      let (name, attributes, empty, offset) = match event {
+        XmlEvent::Start {
+          name,
+          attributes,
+          empty,
+          offset,
+        } => (name, attributes, empty, offset),
+        XmlEvent::End => {
+          stack.pop();
+          continue;
+        }
+      };
+      if !root && name != "svg" {
+        return Err(SvgError {
+          offset,
+          kind: SvgErrorKind::MissingRoot,
+        });
+      }
+      let parent = stack.last().copied().unwrap_or_default();
+      match name {
+        "svg" if !root => {
+          root = true;
+          let mut state = parent.child(&attributes)?;
+          let view_box = view_box(&attributes)?.map(|(_, _, w, h)| (w, h));
+          // Size of the viewBox if missing
+          let (vw, vh) = view_box.unwrap_or((0.0, 0.0));
+          doc.width = size(&attributes, "width")?.unwrap_or(vw);
+          doc.height = size(&attributes, "height")?.unwrap_or(vh);
+          state.viewport = view_box.unwrap_or((doc.width, doc.height));
+          state.transform = viewport(&attributes, doc.width, doc.height)?;
+          if !empty {
+            stack.push(state);
+          }
+        }
+        "svg" => {
+          // Nested viewport, placed in the coordinates of the parent
+          let (pw, ph) = parent.viewport;
+          let x = relative_length(&attributes, "x", pw, 0.0)?;
+          let y = relative_length(&attributes, "y", ph, 0.0)?;
+          let w = relative_length(&attributes, "width", pw, pw)?;
+          let h = relative_length(&attributes, "height", ph, ph)?;
+          let mut state = parent.child(&attributes)?;
+          state.viewport = view_box(&attributes)?.map_or((w, h), |(_, _, w, h)| (w, h));
+          state.transform = viewport(&attributes, w, h)?.then_translate(x, y).then(state.transform);
+          if w <= 0.0 || h <= 0.0 {
+            // Empty viewport, not rendered
+            if !empty {
+              reader.skip_element()?;
+            }
+          } else if !empty {
+            stack.push(state);
+          }
+        }
+        "g" => {
+          let state = parent.child(&attributes)?;
+          if !empty {
+            stack.push(state);
+          }
+        }
+        "path" | "rect" | "circle" | "ellipse" | "line" | "polyline" | "polygon" => {
+          let state = parent.child(&attributes)?;
+          if let Some(path) = shape_path(name, &attributes)? {
+            doc.shapes.push(state.shape(path));
+          }
+          if !empty {
+            reader.skip_element()?;
+          }
+        }
+        _ => {
+          if !empty {
+            reader.skip_element()?;
+          }
+        }
+      }
+    }
+    if !root {
+      return Err(SvgError {
+        offset: src.len(),
+        kind: SvgErrorKind::MissingRoot,
+      });
+    }
+    Ok(doc)
+  }
+  /// Render the document
+  pub fn render<T: Pixel>(&self, ren_base: &mut RenderingBase<T>) {
+    self.render_with_transform(ren_base, &Transform::new());
+  }
+  /// Render the document, with an additional transform applied to all shapes
+  pub fn render_with_transform<T: Pixel>(&self, ren_base: &mut RenderingBase<T>, mtx: &Transform) {
+    let mut ras = RasterizerScanline::new();
+    let mut ren = RenderingScanlineAASolid::new_black(ren_base);
+    for shape in &self.shapes {
+      let trans = shape.transform.then(*mtx);
+      if let Some(fill) = shape.fill {
+        ras.reset();
+        ras.filling_rule(shape.fill_rule);
+        ras.add_path(&(&shape.path).transformed(trans));
+        ren.color(fill);
+        render_scanlines(&mut ras, &mut ren);
+      }
+      if let Some(stroke_color) = shape.stroke {
+        // Stroke in user space, so the pen is transformed with the shape
+        let mut stroke = Stroke::new(&shape.path);
+        stroke.width(shape.stroke_width);
+        stroke.line_join(shape.line_join);
+        stroke.line_cap(shape.line_cap);
+        stroke.miter_limit(shape.miter_limit);
+        stroke.transform(trans);
+        ras.reset();
+        ras.filling_rule(FillingRule::NonZero);
+        ras.add_path(&stroke);
+        ren.color(stroke_color);
+        render_scanlines(&mut ras, &mut ren);
+      }
+    }
+  }
+}
+
+impl FromStr for SvgDocument {
+  type Err = SvgError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::parse(s)
+  }
+}
+
+/// Inherited style properties
+#[derive(Debug, Copy, Clone)]
+struct Style {
+  fill: Option<Rgba8>,
+  fill_opacity: f64,
+  fill_rule: FillingRule,
+  stroke: Option<Rgba8>,
+  stroke_opacity: f64,
+  stroke_width: f64,
+  line_join: LineJoin,
+  line_cap: LineCap,
+  miter_limit: f64,
+}
+
+impl Default for Style {
+  fn default() -> Self {
+    Self {
+      fill: Some(Rgba8::from_raw(0, 0, 0, 255)),
+      fill_opacity: 1.0,
+      fill_rule: FillingRule::NonZero,
+      stroke: None,
+      stroke_opacity: 1.0,
+      stroke_width: 1.0,
+      line_join: LineJoin::Miter,
+      line_cap: LineCap::Butt,
+      miter_limit: 4.0,
+    }
+  }
+}
+
+impl Style {
+  /// Apply a single property, invalid values are ignored
+  fn set(&mut self, name: &str, value: &str) {
+    let value = value.trim();
+    if value == "inherit" {
+      return;
+    }
+    match name {
+      "fill" => self.fill = parse_paint(value).unwrap_or(self.fill),
+      "stroke" => self.stroke = parse_paint(value).unwrap_or(self.stroke),
+      "fill-opacity" => self.fill_opacity = parse_opacity(value).unwrap_or(self.fill_opacity),
+      "stroke-opacity" => self.stroke_opacity = parse_opacity(value).unwrap_or(self.stroke_opacity),
+      "stroke-width" => match parse_length(value) {
+        Some(w) if w >= 0.0 => self.stroke_width = w,
+        _ => {}
+      },
+      "stroke-miterlimit" => match value.parse::<f64>() {
+        Ok(m) if m >= 1.0 => self.miter_limit = m,
+        _ => {}
+      },
+      "fill-rule" => match value {
+        "nonzero" => self.fill_rule = FillingRule::NonZero,
+        "evenodd" => self.fill_rule = FillingRule::EvenOdd,
+        _ => {}
+      },
+      "stroke-linejoin" => match value {
+        "miter" => self.line_join = LineJoin::Miter,
+        "round" => self.line_join = LineJoin::Round,
+        "bevel" => self.line_join = LineJoin::Bevel,
+        _ => {}
+      },
+      "stroke-linecap" => match value {
+        "butt" => self.line_cap = LineCap::Butt,
+        "round" => self.line_cap = LineCap::Round,
+        "square" => self.line_cap = LineCap::Square,
+        _ => {}
+      },
+      _ => {}
+    }
+  }
+}
+
+/// Graphics state of an element
+#[derive(Debug, Copy, Clone)]
+struct State {
+  style: Style,
+  /// User to document coordinates
+  transform: Transform,
+  /// Size of the nearest `svg` viewport in its user coordinates, for
+  ///   percentages
+  viewport: (f64, f64),
+  /// Product of the element and group opacities
+  opacity: f64,
+}
+
+impl Default for State {
+  fn default() -> Self {
+    Self {
+      style: Style::default(),
+      transform: Transform::new(),
+      viewport: (0.0, 0.0),
+      opacity: 1.0,
+    }
+  }
+}
+
+impl State {
+  /// State of a child element with `attributes`
+  fn child(&self, attributes: &[Attribute]) -> Result<Self, SvgError> {
+    let mut state = *self;
+    let mut opacity = 1.0;
+    for a in attributes {
+      match a.name {
+        "transform" => {
+          let t = parse_transform(&a.value).ok_or_else(|| invalid(a))?;
+          state.transform = t.then(state.transform);
+        }
+        "opacity" => opacity = parse_opacity(&a.value).unwrap_or(opacity),
+        "style" => {}
+        name => state.style.set(name, &a.value),
+      }
+    }
+    // Declarations in the style attribute override presentation attributes
+    if let Some(a) = attr(attributes, "style") {
+      for decl in a.value.split(';') {
+        if let Some((name, value)) = decl.split_once(':') {
+          match name.trim() {
+            "opacity" => opacity = parse_opacity(value).unwrap_or(opacity),
+            name => state.style.set(name, value),
+          }
+        }
+      }
+    }
+    state.opacity *= opacity;
+    Ok(state)
+  }
+  fn shape(&self, path: Path) -> SvgShape {
+    let s = &self.style;
+    let paint = |c: Option<Rgba8>, opacity: f64| {
+      c.map(|c| {
+        let alpha = (c.alpha64() * opacity * self.opacity * 255.0).round() as u8;
+        Rgba8::from_raw(c.red8(), c.green8(), c.blue8(), alpha)
+      })
+    };
+    SvgShape {
+      path,
+      transform: self.transform,
+      fill: paint(s.fill, s.fill_opacity),
+      fill_rule: s.fill_rule,
+      stroke: if s.stroke_width > 0.0 {
+        paint(s.stroke, s.stroke_opacity)
+      } else {
+        None
+      },
+      stroke_width: s.stroke_width,
+      line_join: s.line_join,
+      line_cap: s.line_cap,
+      miter_limit: s.miter_limit,
+    }
+  }
+}
+
+/// `viewBox` attribute as x, y, width and height
+fn view_box(attributes: &[Attribute]) -> Result<Option<(f64, f64, f64, f64)>, SvgError> {
+  match attr(attributes, "viewBox") {
+    Some(a) => match parse_numbers(&a.value).as_deref() {
+      Some(&[x, y, w, h]) if w > 0.0 && h > 0.0 => Ok(Some((x, y, w, h))),
+      _ => Err(invalid(a)),
+    },
+    None => Ok(None),
+  }
+}
+
+/// Transform from the `viewBox` of an `svg` element to its viewport
+fn viewport(attributes: &[Attribute], width: f64, height: f64) -> Result<Transform, SvgError> {
+  let Some((x, y, w, h)) = view_box(attributes)? else {
+    return Ok(Transform::new());
+  };
+  let (sx, sy) = (width / w, height / h);
+  let aspect = attr(attributes, "preserveAspectRatio").map(|a| a.value.trim());
+  if aspect == Some("none") {
+    return Ok(Transform::translation(-x, -y).then_scale(sx, sy));
+  }
+  // xMidYMid meet
+  let s = sx.min(sy);
+  let dx = (width - w * s) / 2.0;
+  let dy = (height - h * s) / 2.0;
+  Ok(Transform::translation(-x, -y).then_scale(s, s).then_translate(dx, dy))
+}
+
+fn attr<'a, 'b>(attributes: &'b [Attribute<'a>], name: &str) -> Option<&'b Attribute<'a>> {
+  attributes.iter().find(|a| a.name == name)
+}
+
+fn invalid(a: &Attribute) -> SvgError {
+  SvgError {
+    offset: a.offset,
+    kind: SvgErrorKind::InvalidAttribute,
+  }
+}
+
+/// Length attribute, `default` if it is missing
+fn length(attributes: &[Attribute], name: &str, default: f64) -> Result<f64, SvgError> {
+  match attr(attributes, name) {
+    Some(a) => parse_length(&a.value).ok_or_else(|| invalid(a)),
+    None => Ok(default),
+  }
+}
+
+/// Size of the `svg` element, percentages are treated as missing
+fn size(attributes: &[Attribute], name: &str) -> Result<Option<f64>, SvgError> {
+  match attr(attributes, name) {
+    Some(a) if a.value.trim_end().ends_with('%') => Ok(None),
+    Some(a) => parse_length(&a.value).map(Some).ok_or_else(|| invalid(a)),
+    None => Ok(None),
+  }
+}
+
+/// Length or percentage of `reference`, `default` if it is missing
+fn relative_length(attributes: &[Attribute], name: &str, reference: f64, default: f64) -> Result<f64, SvgError> {
+  match attr(attributes, name) {
+    Some(a) => match a.value.trim().strip_suffix('%') {
+      Some(p) => p.parse::<f64>().ok().map(|p| p * reference / 100.0),
+      None => parse_length(&a.value),
+    }
+    .ok_or_else(|| invalid(a)),
+    None => Ok(default),
+  }
+}
+
+/// Outline of a basic shape or path element, None if it is not rendered
+fn shape_path(name: &str, attributes: &[Attribute]) -> Result<Option<Path>, SvgError> {
+  let mut path = Path::new();
+  match name {
+    "path" => {
+      let Some(d) = attr(attributes, "d") else {
+        return Ok(None);
+      };
+      path = parse_path(&d.value).map_err(|e| SvgError {
+        offset: d.offset + e.offset,
+        kind: SvgErrorKind::PathData(e.kind),
+      })?;
+    }
+    "rect" => {
+      let x = length(attributes, "x", 0.0)?;
+      let y = length(attributes, "y", 0.0)?;
+      let w = length(attributes, "width", 0.0)?;
+      let h = length(attributes, "height", 0.0)?;
+      if w <= 0.0 || h <= 0.0 {
+        return Ok(None);
+      }
+      let rx = attr(attributes, "rx")
+        .map(|_| length(attributes, "rx", 0.0))
+        .transpose()?;
+      let ry = attr(attributes, "ry")
+        .map(|_| length(attributes, "ry", 0.0))
+        .transpose()?;
+      let (rx, ry) = match (rx, ry) {
+        (Some(rx), Some(ry)) => (rx, ry),
+        (Some(r), None) | (None, Some(r)) => (r, r),
+        (None, None) => (0.0, 0.0),
+      };
+      let rx = rx.clamp(0.0, w / 2.0);
+      let ry = ry.clamp(0.0, h / 2.0);
+      if rx > 0.0 && ry > 0.0 {
+        path.move_to(x + rx, y);
+        path.line_to(x + w - rx, y);
+        path.arc_to(rx, ry, 0.0, false, true, x + w, y + ry);
+        path.line_to(x + w, y + h - ry);
+        path.arc_to(rx, ry, 0.0, false, true, x + w - rx, y + h);
+        path.line_to(x + rx, y + h);
+        path.arc_to(rx, ry, 0.0, false, true, x, y + h - ry);
+        path.line_to(x, y + ry);
+        path.arc_to(rx, ry, 0.0, false, true, x + rx, y);
+      } else {
+        path.move_to(x, y);
+        path.line_to(x + w, y);
+        path.line_to(x + w, y + h);
+        path.line_to(x, y + h);
+      }
+      path.close_polygon();
+    }
+    "circle" | "ellipse" => {
+      let cx = length(attributes, "cx", 0.0)?;
+      let cy = length(attributes, "cy", 0.0)?;
+      let (rx, ry) = if name == "circle" {
+        let r = length(attributes, "r", 0.0)?;
+        (r, r)
+      } else {
+        (length(attributes, "rx", 0.0)?, length(attributes, "ry", 0.0)?)
+      };
+      if rx <= 0.0 || ry <= 0.0 {
+        return Ok(None);
+      }
+      path.move_to(cx + rx, cy);
+      path.arc_to(rx, ry, 0.0, false, true, cx - rx, cy);
+      path.arc_to(rx, ry, 0.0, false, true, cx + rx, cy);
+      path.close_polygon();
+    }
+    "line" => {
+      path.move_to(length(attributes, "x1", 0.0)?, length(attributes, "y1", 0.0)?);
+      path.line_to(length(attributes, "x2", 0.0)?, length(attributes, "y2", 0.0)?);
+    }
+    "polyline" | "polygon" => {
+      let Some(points) = attr(attributes, "points") else {
+        return Ok(None);
+      };
+      let v = parse_numbers(&points.value).ok_or_else(|| invalid(points))?;
+      if v.len() < 2 {
+        return Ok(None);
+      }
+      path.move_to(v[0], v[1]);
+      // An odd number of coordinates drops the last one
+      for p in v[2..].chunks_exact(2) {
+        path.line_to(p[0], p[1]);
+      }
+      if name == "polygon" {
+        path.close_polygon();
+      }
+    }
+    _ => return Ok(None),
+  }
+  Ok(Some(path))
+}
+
+/// End of a number starting at `pos`, None if there is no number
+fn scan_number(s: &[u8], mut pos: usize) -> Option<usize> {
+  let digits = |pos: &mut usize| {
+    let start = *pos;
+    while s.get(*pos).is_some_and(u8::is_ascii_digit) {
+      *pos += 1;
+    }
+    *pos - start
+  };
+  if matches!(s.get(pos), Some(b'+' | b'-')) {
+    pos += 1;
+  }
+  let mut n = digits(&mut pos);
+  if s.get(pos) == Some(&b'.') {
+    pos += 1;
+    n += digits(&mut pos);
+  }
+  if n == 0 {
+    return None;
+  }
+  if matches!(s.get(pos), Some(b'e' | b'E')) {
+    let mut exp = pos + 1;
+    if matches!(s.get(exp), Some(b'+' | b'-')) {
+      exp += 1;
+    }
+    if digits(&mut exp) > 0 {
+      pos = exp;
+    }
+  }
+  Some(pos)
+}
+
+/// List of numbers separated by whitespace and commas
+fn parse_numbers(s: &str) -> Option<Vec<f64>> {
+  let b = s.as_bytes();
+  let mut out = vec![];
+  let mut pos = 0;
+  loop {
+    while b.get(pos).is_some_and(|c| c.is_ascii_whitespace() || *c == b',') {
+      pos += 1;
+    }
+    if pos >= b.len() {
+      return Some(out);
+    }
+    let end = scan_number(b, pos)?;
+    out.push(s[pos..end].parse().ok()?);
+    pos = end;
+  }
+}
+
+/// Length with an optional absolute unit, in pixels
+fn parse_length(s: &str) -> Option<f64> {
+  let s = s.trim();
+  let end = scan_number(s.as_bytes(), 0)?;
+  let v: f64 = s[..end].parse().ok()?;
+  let unit = match &s[end..] {
+    "" | "px" => 1.0,
+    "in" => 96.0,
+    "cm" => 96.0 / 2.54,
+    "mm" => 96.0 / 25.4,
+    "pt" => 96.0 / 72.0,
+    "pc" => 16.0,
+    _ => return None,
+  };
+  Some(v * unit)
+}
+
+/// Opacity, clamped to [0,1]
+fn parse_opacity(s: &str) -> Option<f64> {
+  let s = s.trim();
+  let v = match s.strip_suffix('%') {
+    Some(p) => p.parse::<f64>().ok()? / 100.0,
+    None => s.parse::<f64>().ok()?,
+  };
+  Some(v.clamp(0.0, 1.0))
+}
+
+/// Paint value, `Some(None)` for `none`, None if it is invalid
+fn parse_paint(s: &str) -> Option<Option<Rgba8>> {
+  let s = s.trim();
+  if s == "none" {
+    return Some(None);
+  }
+  // Paint servers are not supported, use the fallback color if any
+  if s.starts_with("url(") {
+    let fallback = s[s.find(')')? + 1..].trim();
+    return if fallback.is_empty() {
+      Some(None)
+    } else {
+      parse_paint(fallback)
+    };
+  }
+  parse_color(s).map(Some)
+}
+
+/// Transform list, the right-most transform is applied first
+fn parse_transform(s: &str) -> Option<Transform> {
+  let mut m = Transform::new();
+  let mut rest = s.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+  while !rest.is_empty() {
+    let open = rest.find('(')?;
+    let close = open + rest[open..].find(')')?;
+    let a = parse_numbers(&rest[open + 1..close])?;
+    let t = match (rest[..open].trim(), a.as_slice()) {
+      ("matrix", &[sx, shy, shx, sy, tx, ty]) => Transform::affine(sx, shy, shx, sy, tx, ty),
+      ("translate", &[tx]) => Transform::translation(tx, 0.0),
+      ("translate", &[tx, ty]) => Transform::translation(tx, ty),
+      ("scale", &[s]) => Transform::scaling(s, s),
+      ("scale", &[sx, sy]) => Transform::scaling(sx, sy),
+      ("rotate", &[angle]) => Transform::rotation(angle.to_radians()),
+      ("rotate", &[angle, cx, cy]) => Transform::translation(-cx, -cy)
+        .then(Transform::rotation(angle.to_radians()))
+        .then_translate(cx, cy),
+      ("skewX", &[angle]) => Transform::skewing(angle.to_radians(), 0.0),
+      ("skewY", &[angle]) => Transform::skewing(0.0, angle.to_radians()),
+      _ => return None,
+    };
+    m = t.then(m);
+    rest = rest[close + 1..].trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+  }
+  Some(m)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::NamedColor;
+  use crate::paths::PathCommand;
+
+  fn parse(s: &str) -> SvgDocument {
+    SvgDocument::parse(s).unwrap()
+  }
+
+  #[test]
+  fn test_shapes() {
+    let doc = parse(
+      r#"<?xml version="1.0"?>
+      <!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">
+      <svg width="10cm" height="20">
+        <!-- comment <rect/> -->
+        <title>Shapes &amp; more</title>
+        <rect width="10" height="5"/>
+        <rect width="10" height="5" rx="1"/>
+        <rect width="0" height="5"/>
+        <circle cx="5" cy="5" r="2"></circle>
+        <ellipse rx="2" ry="1"/>
+        <line x1="0" y1="0" x2="3" y2="4"/>
+        <polyline points="0,0 1,1 2,0 5"/>
+        <polygon points="0 0 1-1 2 0"/>
+        <path d="M0 0 L 1 1"/>
+        <defs><rect width="1" height="1"/></defs>
+      </svg>"#,
+    );
+    assert!((doc.width - 96.0 / 0.254).abs() < 1e-9);
+    assert_eq!(doc.height, 20.0);
+    let cmds: Vec<Vec<PathCommand>> = doc
+      .shapes
+      .iter()
+      .map(|s| s.path.vertices.iter().map(|v| v.cmd).collect())
+      .collect();
+    use PathCommand::*;
+    assert_eq!(cmds.len(), 8);
+    assert_eq!(cmds[0], vec![MoveTo, LineTo, LineTo, LineTo, Close]);
+    assert_eq!(cmds[1].iter().filter(|c| **c == Curve4).count(), 12);
+    assert_eq!(cmds[2].iter().filter(|c| **c == Curve4).count(), 12);
+    assert_eq!(cmds[4], vec![MoveTo, LineTo]);
+    assert_eq!(cmds[5], vec![MoveTo, LineTo, LineTo]);
+    assert_eq!(cmds[6], vec![MoveTo, LineTo, LineTo, Close]);
+    // Quarter arcs meet at the axes
+    let c = &doc.shapes[2].path.vertices;
+    assert_eq!((c[3].x, c[3].y), (5.0, 7.0));
+    assert_eq!((c[6].x, c[6].y), (3.0, 5.0));
+  }
+
+  #[test]
+  fn test_style() {
+    let doc = parse(
+      r##"<svg width="10" height="10">
+        <g fill="red" stroke="#00f" opacity="0.5" stroke-width="3">
+          <rect width="1" height="1" style="fill: rgb(0, 255, 0); stroke-width: 2" fill-opacity="0.5"/>
+          <rect width="1" height="1" fill="none" fill-rule="evenodd" stroke-linejoin="round"/>
+          <g fill="bogus" opacity="50%"><rect width="1" height="1" stroke="url(#g) yellow"/></g>
+          <rect width="1" height="1" stroke-width="0" fill="url(#g)"/>
+        </g>
+        <rect width="1" height="1"/>
+      </svg>"##,
+    );
+    let s = &doc.shapes;
+    assert_eq!(s[0].fill, Some(Rgba8::from_raw(0, 255, 0, 64)));
+    assert_eq!(s[0].stroke, Some(Rgba8::from_raw(0, 0, 255, 128)));
+    assert_eq!(s[0].stroke_width, 2.0);
+    assert_eq!(s[1].fill, None);
+    assert_eq!(s[1].fill_rule, FillingRule::EvenOdd);
+    assert_eq!(s[1].line_join, LineJoin::Round);
+    assert_eq!(s[1].stroke_width, 3.0);
+    assert_eq!(s[2].fill, Some(Rgba8::from_raw(255, 0, 0, 64)));
+    assert_eq!(s[2].stroke, Some(Rgba8::from_raw(255, 255, 0, 64)));
+    assert_eq!(s[3].fill, None);
+    assert_eq!(s[3].stroke, None);
+    assert_eq!(s[4].fill, Some(Rgba8::BLACK));
+    assert_eq!(s[4].stroke, None);
+  }
+
+  #[test]
+  fn test_transform() {
+    let t = parse_transform("translate(10) scale(2, 3)").unwrap();
+    assert_eq!(t.transform(1.0, 1.0), (12.0, 3.0));
+    let t = parse_transform("rotate(90 10 10)").unwrap();
+    let (x, y) = t.transform(20.0, 10.0);
+    assert!((x - 10.0).abs() < 1e-9 && (y - 20.0).abs() < 1e-9);
+    let t = parse_transform(" matrix(1 0 0 1 5 6),skewX(45)").unwrap();
+    let (x, y) = t.transform(0.0, 1.0);
+    assert!((x - 6.0).abs() < 1e-9 && (y - 7.0).abs() < 1e-9);
+    assert!(parse_transform("rotate(1 2)").is_none());
+    assert!(parse_transform("translate(1").is_none());
+
+    let doc = parse(
+      r#"<svg width="200" height="100" viewBox="-10 0 20 20">
+        <g transform="translate(1 2)"><rect transform="scale(2)" width="1" height="1"/></g>
+      </svg>"#,
+    );
+    // viewBox is scaled by 5 and centered horizontally
+    let t = doc.shapes[0].transform;
+    assert_eq!(t.transform(0.0, 0.0), (105.0, 10.0));
+    assert_eq!(t.transform(1.0, 1.0), (115.0, 20.0));
+  }
+
+  #[test]
+  fn test_nested_viewport() {
+    let doc = parse(
+      r#"<svg width="200" height="100" viewBox="0 0 100 50">
+        <svg x="10" y="5" width="20" height="20" viewBox="0 0 2 2"><rect width="1" height="1"/></svg>
+        <svg x="50%" width="50%" height="10"><rect width="50" height="1"/></svg>
+        <svg width="0"><rect width="1" height="1"/></svg>
+      </svg>"#,
+    );
+    assert_eq!(doc.shapes.len(), 2);
+    // Scaled by 10 into the nested viewport, then by 2 into the document
+    let t = doc.shapes[0].transform;
+    assert_eq!(t.transform(0.0, 0.0), (20.0, 10.0));
+    assert_eq!(t.transform(1.0, 1.0), (40.0, 30.0));
+    // Percentages of the parent viewBox, moved without scaling
+    let t = doc.shapes[1].transform;
+    assert_eq!(t.transform(0.0, 0.0), (100.0, 0.0));
+    assert_eq!(t.transform(50.0, 1.0), (200.0, 2.0));
+    let err = SvgDocument::parse(r#"<svg><svg x="1em"/></svg>"#).unwrap_err();
+    assert_eq!(err.kind, SvgErrorKind::InvalidAttribute);
+  }
+
+  #[test]
+  fn test_colors() {
+    assert_eq!(parse_color("#f80"), Some(Rgba8::from_raw(255, 136, 0, 255)));
+    assert_eq!(parse_color("#FF8000"), Some(Rgba8::from_raw(255, 128, 0, 255)));
+    assert_eq!(parse_color("rgba(100%, 0, 10, 0.5)"), Some(Rgba8::from_raw(255, 0, 10, 128)));
+    assert_eq!(parse_color("CornflowerBlue"), Some(Rgba8::from_raw(100, 149, 237, 255)));
+    assert_eq!(parse_color("transparent"), Some(Rgba8::EMPTY));
+    assert_eq!(parse_color("#ff80"), None);
+    assert_eq!(parse_color("notacolor"), None);
+  }
+
+  #[test]
+  fn test_errors() {
+    let err = |s: &str| SvgDocument::parse(s).unwrap_err();
+    assert_eq!(err("<svg><g></svg>").kind, SvgErrorKind::MismatchedTag);
+    assert_eq!(err("<svg><g>").kind, SvgErrorKind::UnexpectedEof);
+    assert_eq!(err("<html/>").kind, SvgErrorKind::MissingRoot);
+    assert_eq!(err("").kind, SvgErrorKind::MissingRoot);
+    let e = err(r#"<svg><rect width="x"/></svg>"#);
+    assert_eq!((e.offset, e.kind), (18, SvgErrorKind::InvalidAttribute));
+    let e = err(r#"<svg><path d="M 0 0 L 1"/></svg>"#);
+    assert_eq!(e.offset, 23);
+    assert_eq!(e.kind, SvgErrorKind::PathData(SvgPathErrorKind::ExpectedNumber));
+    let e = err("<svg <");
+    assert_eq!((e.offset, e.kind), (5, SvgErrorKind::UnexpectedCharacter('<')));
+    assert_eq!(e.to_string(), "unexpected character '<' at byte 5");
+  }
+}
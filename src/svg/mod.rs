@@ -0,0 +1,9 @@
+//! Scalable Vector Graphics (SVG)
+
+mod color;
+mod document;
+mod path;
+mod xml;
+
+pub use document::*;
+pub use path::*;
@@ -0,0 +1,461 @@
+//! SVG Path Data
+//!
+//! Parser for the path data mini-language of the SVG `d` attribute
+//!
+//! See [SVG Path Data](https://www.w3.org/TR/SVG/paths.html#PathData)
+//!
+//! # Example
+//!
+//! ```
+//! let path = agg::parse_path("M10,10 L50,90 h40 q20 -40 0 -80 z").unwrap();
+//! assert_eq!(path.vertices.len(), 6);
+//!
+//! let err = agg::parse_path("M10,10 L50,x").unwrap_err();
+//! assert_eq!(err.offset, 11);
+//! ```
+
+use crate::paths::Path;
+
+use std::str::FromStr;
+
+/// Error Kind while parsing SVG path data
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SvgPathErrorKind {
+  /// Character is not a command, number or separator
+  UnexpectedCharacter(char),
+  /// Number is missing or malformed
+  ExpectedNumber,
+  /// Arc flag is not `0` or `1`
+  ExpectedFlag,
+  /// Numbers found before any command
+  ExpectedCommand,
+  /// Path data does not start with a MoveTo command
+  MissingMoveTo,
+}
+
+/// Error while parsing SVG path data
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SvgPathError {
+  /// Byte offset of the malformed token
+  pub offset: usize,
+  /// Type of error
+  pub kind: SvgPathErrorKind,
+}
+
+impl std::fmt::Display for SvgPathError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self.kind {
+      SvgPathErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character {c:?}")?,
+      SvgPathErrorKind::ExpectedNumber => write!(f, "expected number")?,
+      SvgPathErrorKind::ExpectedFlag => write!(f, "expected flag")?,
+      SvgPathErrorKind::ExpectedCommand => write!(f, "expected command")?,
+      SvgPathErrorKind::MissingMoveTo => write!(f, "path data must start with a moveto")?,
+    }
+    write!(f, " at byte {}", self.offset)
+  }
+}
+
+impl std::error::Error for SvgPathError {}
+
+/// Parse SVG path data into a Path
+///
+/// All absolute and relative commands are supported, `M`, `L`, `H`, `V`,
+/// `C`, `S`, `Q`, `T`, `A` and `Z`, including implicit repeats of a command.
+/// Curves are stored as `Curve3` and `Curve4` commands, arcs are converted
+/// to `Curve4` commands.
+pub fn parse_path(d: &str) -> Result<Path, SvgPathError> {
+  PathParser::new(d).parse()
+}
+
+impl FromStr for Path {
+  type Err = SvgPathError;
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    parse_path(s)
+  }
+}
+
+/// Previous control point, used by the smooth curve commands
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum LastControl {
+  None,
+  Cubic(f64, f64),
+  Quad(f64, f64),
+}
+
+struct PathParser<'a> {
+  /// Path data
+  s: &'a [u8],
+  /// Current byte offset
+  pos: usize,
+  /// Output Path
+  path: Path,
+  /// Current point
+  x: f64,
+  y: f64,
+  /// Start of the current sub-path
+  start_x: f64,
+  start_y: f64,
+  /// Previous control point
+  last: LastControl,
+  /// Sub-path was closed and needs a MoveTo before continuing
+  closed: bool,
+}
+
+impl<'a> PathParser<'a> {
+  fn new(d: &'a str) -> Self {
+    Self {
+      s: d.as_bytes(),
+      pos: 0,
+      path: Path::new(),
+      x: 0.0,
+      y: 0.0,
+      start_x: 0.0,
+      start_y: 0.0,
+      last: LastControl::None,
+      closed: false,
+    }
+  }
+  fn error(&self, kind: SvgPathErrorKind) -> SvgPathError {
+    SvgPathError { offset: self.pos, kind }
+  }
+  fn peek(&self) -> Option<u8> {
+    self.s.get(self.pos).copied()
+  }
+  fn skip_wsp(&mut self) {
+    while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r' | b'\x0C')) {
+      self.pos += 1;
+    }
+  }
+  /// Skip whitespace and at most one comma
+  fn skip_comma_wsp(&mut self) {
+    self.skip_wsp();
+    if self.peek() == Some(b',') {
+      self.pos += 1;
+      self.skip_wsp();
+    }
+  }
+  /// Next token starts a number
+  fn at_number(&self) -> bool {
+    matches!(self.peek(), Some(b'0'..=b'9' | b'+' | b'-' | b'.'))
+  }
+  fn digits(&mut self) -> usize {
+    let start = self.pos;
+    while matches!(self.peek(), Some(b'0'..=b'9')) {
+      self.pos += 1;
+    }
+    self.pos - start
+  }
+  /// Read a number, preceded by optional whitespace and a comma
+  fn number(&mut self) -> Result<f64, SvgPathError> {
+    self.skip_comma_wsp();
+    let start = self.pos;
+    if matches!(self.peek(), Some(b'+' | b'-')) {
+      self.pos += 1;
+    }
+    let mut n = self.digits();
+    if self.peek() == Some(b'.') {
+      self.pos += 1;
+      n += self.digits();
+    }
+    if n == 0 {
+      self.pos = start;
+      return Err(self.error(SvgPathErrorKind::ExpectedNumber));
+    }
+    if matches!(self.peek(), Some(b'e' | b'E')) {
+      let mantissa = self.pos;
+      self.pos += 1;
+      if matches!(self.peek(), Some(b'+' | b'-')) {
+        self.pos += 1;
+      }
+      if self.digits() == 0 {
+        self.pos = mantissa;
+        return Err(self.error(SvgPathErrorKind::ExpectedNumber));
+      }
+    }
+    // Only ascii characters were consumed
+    let txt = std::str::from_utf8(&self.s[start..self.pos]).unwrap();
+    txt.parse::<f64>().map_err(|_| SvgPathError {
+      offset: start,
+      kind: SvgPathErrorKind::ExpectedNumber,
+    })
+  }
+  /// Read an arc flag, a single `0` or `1`
+  fn flag(&mut self) -> Result<bool, SvgPathError> {
+    self.skip_comma_wsp();
+    match self.peek() {
+      Some(b'0') => {
+        self.pos += 1;
+        Ok(false)
+      }
+      Some(b'1') => {
+        self.pos += 1;
+        Ok(true)
+      }
+      _ => Err(self.error(SvgPathErrorKind::ExpectedFlag)),
+    }
+  }
+  /// Read a coordinate pair, relative coordinates are made absolute
+  fn point(&mut self, rel: bool) -> Result<(f64, f64), SvgPathError> {
+    let x = self.number()?;
+    let y = self.number()?;
+    Ok(if rel { (self.x + x, self.y + y) } else { (x, y) })
+  }
+  fn parse(mut self) -> Result<Path, SvgPathError> {
+    let mut first = true;
+    loop {
+      self.skip_wsp();
+      let c = match self.peek() {
+        None => break,
+        Some(c) => c,
+      };
+      if !c.is_ascii_alphabetic() {
+        let kind = if self.at_number() {
+          SvgPathErrorKind::ExpectedCommand
+        } else {
+          SvgPathErrorKind::UnexpectedCharacter(self.current_char())
+        };
+        return Err(self.error(kind));
+      }
+      if !b"MmLlHhVvCcSsQqTtAaZz".contains(&c) {
+        return Err(self.error(SvgPathErrorKind::UnexpectedCharacter(self.current_char())));
+      }
+      if first && c != b'M' && c != b'm' {
+        return Err(self.error(SvgPathErrorKind::MissingMoveTo));
+      }
+      first = false;
+      self.pos += 1;
+      if c == b'Z' || c == b'z' {
+        self.close();
+        continue;
+      }
+      // Command followed by one or more sets of arguments
+      let mut cmd = c;
+      loop {
+        self.command(cmd)?;
+        // Implicit MoveTo repeats are LineTo's
+        cmd = match cmd {
+          b'M' => b'L',
+          b'm' => b'l',
+          _ => cmd,
+        };
+        self.skip_comma_wsp();
+        if !self.at_number() {
+          break;
+        }
+      }
+    }
+    Ok(self.path)
+  }
+  /// Character at the current position
+  fn current_char(&self) -> char {
+    std::str::from_utf8(&self.s[self.pos..])
+      .ok()
+      .and_then(|s| s.chars().next())
+      .unwrap_or(char::from(self.s[self.pos]))
+  }
+  /// Start a new sub-path at the current point, if the last one was closed
+  fn ensure_sub_path(&mut self) {
+    if self.closed {
+      self.path.move_to(self.x, self.y);
+      self.closed = false;
+    }
+  }
+  fn close(&mut self) {
+    self.path.close_polygon();
+    self.x = self.start_x;
+    self.y = self.start_y;
+    self.last = LastControl::None;
+    self.closed = true;
+  }
+  fn line_to(&mut self, x: f64, y: f64) {
+    self.ensure_sub_path();
+    self.path.line_to(x, y);
+    self.x = x;
+    self.y = y;
+    self.last = LastControl::None;
+  }
+  /// Execute a single command, reading its arguments
+  fn command(&mut self, cmd: u8) -> Result<(), SvgPathError> {
+    let rel = cmd.is_ascii_lowercase();
+    match cmd.to_ascii_uppercase() {
+      b'M' => {
+        let (x, y) = self.point(rel)?;
+        self.path.move_to(x, y);
+        self.x = x;
+        self.y = y;
+        self.start_x = x;
+        self.start_y = y;
+        self.last = LastControl::None;
+        self.closed = false;
+      }
+      b'L' => {
+        let (x, y) = self.point(rel)?;
+        self.line_to(x, y);
+      }
+      b'H' => {
+        let x = self.number()?;
+        let x = if rel { self.x + x } else { x };
+        self.line_to(x, self.y);
+      }
+      b'V' => {
+        let y = self.number()?;
+        let y = if rel { self.y + y } else { y };
+        self.line_to(self.x, y);
+      }
+      b'C' => {
+        let (x1, y1) = self.point(rel)?;
+        let (x2, y2) = self.point(rel)?;
+        let (x, y) = self.point(rel)?;
+        self.curve4(x1, y1, x2, y2, x, y);
+      }
+      b'S' => {
+        let (x2, y2) = self.point(rel)?;
+        let (x, y) = self.point(rel)?;
+        let (x1, y1) = match self.last {
+          LastControl::Cubic(cx, cy) => (2.0 * self.x - cx, 2.0 * self.y - cy),
+          _ => (self.x, self.y),
+        };
+        self.curve4(x1, y1, x2, y2, x, y);
+      }
+      b'Q' => {
+        let (x1, y1) = self.point(rel)?;
+        let (x, y) = self.point(rel)?;
+        self.curve3(x1, y1, x, y);
+      }
+      b'T' => {
+        let (x, y) = self.point(rel)?;
+        let (x1, y1) = match self.last {
+          LastControl::Quad(cx, cy) => (2.0 * self.x - cx, 2.0 * self.y - cy),
+          _ => (self.x, self.y),
+        };
+        self.curve3(x1, y1, x, y);
+      }
+      b'A' => {
+        let rx = self.number()?;
+        let ry = self.number()?;
+        let angle = self.number()?;
+        let large_arc = self.flag()?;
+        let sweep = self.flag()?;
+        let (x, y) = self.point(rel)?;
+        self.ensure_sub_path();
+        self.path.arc_to(rx, ry, angle.to_radians(), large_arc, sweep, x, y);
+        self.x = x;
+        self.y = y;
+        self.last = LastControl::None;
+      }
+      _ => unreachable!("command checked before execution"),
+    }
+    Ok(())
+  }
+  fn curve3(&mut self, x1: f64, y1: f64, x: f64, y: f64) {
+    self.ensure_sub_path();
+    self.path.curve3(x1, y1, x, y);
+    self.x = x;
+    self.y = y;
+    self.last = LastControl::Quad(x1, y1);
+  }
+  fn curve4(&mut self, x1: f64, y1: f64, x2: f64, y2: f64, x: f64, y: f64) {
+    self.ensure_sub_path();
+    self.path.curve4(x1, y1, x2, y2, x, y);
+    self.x = x;
+    self.y = y;
+    self.last = LastControl::Cubic(x2, y2);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::paths::PathCommand;
+
+  fn cmds(p: &Path) -> Vec<PathCommand> {
+    p.vertices.iter().map(|v| v.cmd).collect()
+  }
+  fn xy(p: &Path) -> Vec<(f64, f64)> {
+    p.vertices.iter().map(|v| (v.x, v.y)).collect()
+  }
+
+  #[test]
+  fn test_lines() {
+    let p = parse_path("M 10 20 L30,40 h 5 v-5 H0 V0 z").unwrap();
+    use PathCommand::*;
+    assert_eq!(cmds(&p), vec![MoveTo, LineTo, LineTo, LineTo, LineTo, LineTo, Close]);
+    assert_eq!(
+      xy(&p)[..6],
+      [(10., 20.), (30., 40.), (35., 40.), (35., 35.), (0., 35.), (0., 0.)]
+    );
+  }
+
+  #[test]
+  fn test_implicit_and_relative() {
+    // Implicit LineTo after a relative MoveTo, compact numbers
+    let p = parse_path("m10-10 5.5.5-1e1-2E+1zl1 1").unwrap();
+    assert_eq!(xy(&p)[..3], [(10., -10.), (15.5, -9.5), (5.5, -29.5)]);
+    // After closing, the current point is the start of the sub-path
+    let n = p.vertices.len();
+    assert_eq!(p.vertices[n - 2].cmd, PathCommand::MoveTo);
+    assert_eq!((p.vertices[n - 2].x, p.vertices[n - 2].y), (10., -10.));
+    assert_eq!((p.vertices[n - 1].x, p.vertices[n - 1].y), (11., -9.));
+  }
+
+  #[test]
+  fn test_curves() {
+    let p = parse_path("M0 0 C 10 10 20 10 30 0 S 50 -10 60 0 Q 70 10 80 0 t 20 0").unwrap();
+    use PathCommand::*;
+    assert_eq!(
+      cmds(&p),
+      vec![MoveTo, Curve4, Curve4, Curve4, Curve4, Curve4, Curve4, Curve3, Curve3, Curve3, Curve3]
+    );
+    // Reflected control points
+    assert_eq!(xy(&p)[4], (40., -10.));
+    assert_eq!(xy(&p)[9], (90., -10.));
+    // Smooth quadratic after a cubic uses the current point
+    let p = parse_path("M0 0 C 10 10 20 10 30 0 T 60 0").unwrap();
+    assert_eq!(xy(&p)[4], (30., 0.));
+  }
+
+  #[test]
+  fn test_arcs() {
+    // Half circle, compact flags
+    let p = parse_path("M0 0 A5 5 0 0110 0").unwrap();
+    let last = p.vertices[p.vertices.len() - 1];
+    assert_eq!((last.x, last.y), (10.0, 0.0));
+    assert!(p.vertices[1..].iter().all(|v| v.cmd == PathCommand::Curve4));
+    // Positive sweep is a positive angle direction, towards negative y here
+    assert!(p.vertices.iter().all(|v| v.y <= 1e-9));
+    assert!(
+      p.vertices
+        .iter()
+        .any(|v| (v.x - 5.0).abs() < 1e-9 && (v.y + 5.0).abs() < 1e-9)
+    );
+    // Radius is scaled up to reach the end point
+    let p = parse_path("M0 0 A1 1 0 0 0 10 0").unwrap();
+    assert!(
+      p.vertices
+        .iter()
+        .any(|v| (v.x - 5.0).abs() < 1e-9 && (v.y - 5.0).abs() < 1e-9)
+    );
+    // Zero radius is a line
+    let p = parse_path("M0 0 a0 5 0 1 1 10 0").unwrap();
+    assert_eq!(cmds(&p), vec![PathCommand::MoveTo, PathCommand::LineTo]);
+  }
+
+  #[test]
+  fn test_errors() {
+    let e = parse_path("M 10 10 L 20 # 30").unwrap_err();
+    assert_eq!(e.offset, 13);
+    assert_eq!(e.kind, SvgPathErrorKind::ExpectedNumber);
+    let e = parse_path("L 10 10").unwrap_err();
+    assert_eq!((e.offset, e.kind), (0, SvgPathErrorKind::MissingMoveTo));
+    let e = parse_path("M 10 10 X").unwrap_err();
+    assert_eq!((e.offset, e.kind), (8, SvgPathErrorKind::UnexpectedCharacter('X')));
+    let e = parse_path("M 0 0 A 1 1 0 2 1 3 3").unwrap_err();
+    assert_eq!((e.offset, e.kind), (14, SvgPathErrorKind::ExpectedFlag));
+    let e = parse_path("M 0 0 L 1 1e").unwrap_err();
+    assert_eq!((e.offset, e.kind), (11, SvgPathErrorKind::ExpectedNumber));
+    let e = parse_path("M 0 0 z 1 1").unwrap_err();
+    assert_eq!((e.offset, e.kind), (8, SvgPathErrorKind::ExpectedCommand));
+    assert_eq!(e.to_string(), "expected command at byte 8");
+    assert!("M0 0 1 1".parse::<Path>().is_ok());
+  }
+}
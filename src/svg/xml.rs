@@ -0,0 +1,261 @@
+//! Minimal XML reader
+//!
+//! Only what an SVG document needs, elements and attributes. Text,
+//! comments, processing instructions, CDATA sections and the doctype are
+//! skipped. Tags are checked to be properly nested.
+
+use crate::svg::{SvgError, SvgErrorKind};
+
+/// Attribute of an element
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Attribute<'a> {
+  /// Attribute name
+  pub name: &'a str,
+  /// Value with entities replaced
+  pub value: String,
+  /// Byte offset of the value in the document
+  pub offset: usize,
+}
+
+/// Event produced while walking the document
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum XmlEvent<'a> {
+  /// Start of an element, `empty` for `<element/>`
+  Start {
+    name: &'a str,
+    attributes: Vec<Attribute<'a>>,
+    empty: bool,
+    offset: usize,
+  },
+  /// End of an element
+  End,
+}
+
+pub(crate) struct XmlReader<'a> {
+  /// Document text
+  src: &'a str,
+  /// Current byte offset
+  pos: usize,
+  /// Names of the open elements
+  open: Vec<&'a str>,
+}
+
+impl<'a> XmlReader<'a> {
+  pub fn new(src: &'a str) -> Self {
+    Self {
+      src,
+      pos: 0,
+      open: vec![],
+    }
+  }
+  fn error(&self, kind: SvgErrorKind) -> SvgError {
+    SvgError { offset: self.pos, kind }
+  }
+  fn rest(&self) -> &'a str {
+    &self.src[self.pos..]
+  }
+  fn peek(&self) -> Option<char> {
+    self.rest().chars().next()
+  }
+  fn unexpected(&self) -> SvgError {
+    match self.peek() {
+      Some(c) => self.error(SvgErrorKind::UnexpectedCharacter(c)),
+      None => self.error(SvgErrorKind::UnexpectedEof),
+    }
+  }
+  fn skip_wsp(&mut self) {
+    let rest = self.rest();
+    self.pos += rest.len() - rest.trim_start().len();
+  }
+  /// Move past the next occurrence of `end`
+  fn skip_past(&mut self, end: &str) -> Result<(), SvgError> {
+    match self.rest().find(end) {
+      Some(i) => {
+        self.pos += i + end.len();
+        Ok(())
+      }
+      None => {
+        self.pos = self.src.len();
+        Err(self.error(SvgErrorKind::UnexpectedEof))
+      }
+    }
+  }
+  /// Skip a doctype declaration, which may hold an internal subset in brackets
+  fn skip_doctype(&mut self) -> Result<(), SvgError> {
+    let mut depth = 0;
+    for (i, c) in self.rest().char_indices() {
+      match c {
+        '[' => depth += 1,
+        ']' => depth -= 1,
+        '>' if depth <= 0 => {
+          self.pos += i + 1;
+          return Ok(());
+        }
+        _ => {}
+      }
+    }
+    self.pos = self.src.len();
+    Err(self.error(SvgErrorKind::UnexpectedEof))
+  }
+  fn name(&mut self) -> Result<&'a str, SvgError> {
+    let rest = self.rest();
+    let n = rest
+      .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '=' | '<' | '"' | '\''))
+      .unwrap_or(rest.len());
+    if n == 0 {
+      return Err(self.unexpected());
+    }
+    self.pos += n;
+    Ok(&rest[..n])
+  }
+  fn expect(&mut self, c: char) -> Result<(), SvgError> {
+    if self.peek() != Some(c) {
+      return Err(self.unexpected());
+    }
+    self.pos += c.len_utf8();
+    Ok(())
+  }
+  /// Next element event, None at the end of the document
+  pub fn next_event(&mut self) -> Result<Option<XmlEvent<'a>>, SvgError> {
+    loop {
+      match self.rest().find('<') {
+        Some(i) => self.pos += i,
+        None => {
+          self.pos = self.src.len();
+          if !self.open.is_empty() {
+            return Err(self.error(SvgErrorKind::UnexpectedEof));
+          }
+          return Ok(None);
+        }
+      }
+      let rest = self.rest();
+      if rest.starts_with("<!--") {
+        self.skip_past("-->")?;
+      } else if rest.starts_with("<![CDATA[") {
+        self.skip_past("]]>")?;
+      } else if rest.starts_with("<!") {
+        self.skip_doctype()?;
+      } else if rest.starts_with("<?") {
+        self.skip_past("?>")?;
+      } else if rest.starts_with("</") {
+        self.pos += 2;
+        let offset = self.pos;
+        let name = self.name()?;
+        self.skip_wsp();
+        self.expect('>')?;
+        if self.open.pop() != Some(name) {
+          return Err(SvgError {
+            offset,
+            kind: SvgErrorKind::MismatchedTag,
+          });
+        }
+        return Ok(Some(XmlEvent::End));
+      } else {
+        return self.start_tag().map(Some);
+      }
+    }
+  }
+  fn start_tag(&mut self) -> Result<XmlEvent<'a>, SvgError> {
+    let offset = self.pos;
+    self.pos += 1;
+    let name = self.name()?;
+    let mut attributes = vec![];
+    loop {
+      self.skip_wsp();
+      if self.rest().starts_with("/>") {
+        self.pos += 2;
+        return Ok(XmlEvent::Start {
+          name,
+          attributes,
+          empty: true,
+          offset,
+        });
+      }
+      if self.peek() == Some('>') {
+        self.pos += 1;
+        self.open.push(name);
+        return Ok(XmlEvent::Start {
+          name,
+          attributes,
+          empty: false,
+          offset,
+        });
+      }
+      let attr = self.name()?;
+      self.skip_wsp();
+      self.expect('=')?;
+      self.skip_wsp();
+      let quote = match self.peek() {
+        Some(q @ ('"' | '\'')) => q,
+        _ => return Err(self.unexpected()),
+      };
+      self.pos += 1;
+      let start = self.pos;
+      let n = self.rest().find(quote).ok_or(SvgError {
+        offset: self.src.len(),
+        kind: SvgErrorKind::UnexpectedEof,
+      })?;
+      self.pos += n + 1;
+      attributes.push(Attribute {
+        name: attr,
+        value: decode_entities(&self.src[start..start + n]),
+        offset: start,
+      });
+    }
+  }
+  /// Skip the remainder of the element just started, including children
+  pub fn skip_element(&mut self) -> Result<(), SvgError> {
+    let mut depth = 1;
+    while depth > 0 {
+      match self.next_event()? {
+        Some(XmlEvent::Start { empty: false, .. }) => depth += 1,
+        Some(XmlEvent::Start { .. }) => {}
+        Some(XmlEvent::End) => depth -= 1,
+        None => return Err(self.error(SvgErrorKind::UnexpectedEof)),
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Replace the predefined and numeric character references
+///
+/// Unknown references are kept as is
+fn decode_entities(s: &str) -> String {
+  let mut out = String::with_capacity(s.len());
+  let mut rest = s;
+  while let Some(i) = rest.find('&') {
+    out.push_str(&rest[..i]);
+    rest = &rest[i..];
+    let decoded = rest.find(';').and_then(|end| {
+      let c = match &rest[1..end] {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        e => {
+          let code = if let Some(hex) = e.strip_prefix("#x").or_else(|| e.strip_prefix("#X")) {
+            u32::from_str_radix(hex, 16).ok()?
+          } else {
+            e.strip_prefix('#')?.parse().ok()?
+          };
+          char::from_u32(code)?
+        }
+      };
+      Some((c, end))
+    });
+    match decoded {
+      Some((c, end)) => {
+        out.push(c);
+        rest = &rest[end + 1..];
+      }
+      None => {
+        out.push('&');
+        rest = &rest[1..];
+      }
+    }
+  }
+  out.push_str(rest);
+  out
+}
@@ -0,0 +1,49 @@
+extern crate agg;
+use agg::prelude::*;
+
+fn render<VS: agg::VertexSource>(path: &VS) -> agg::RenderingBase<agg::Pixfmt<agg::Rgb8>> {
+  let pixf = agg::Pixfmt::<agg::Rgb8>::create(100, 100);
+  let mut ren_base = agg::RenderingBase::new(pixf);
+  ren_base.clear(agg::Rgb8::WHITE);
+  let mut ras: agg::RasterizerScanline = agg::RasterizerScanline::new();
+  let mut ren = agg::RenderingScanlineAASolid::new(&mut ren_base, agg::Rgb8::BLACK);
+  ras.add_path(path);
+  agg::render_scanlines(&mut ras, &mut ren);
+  ren_base
+}
+
+#[test]
+fn conv_clip_polyline() {
+  // Zig-zag, mostly off the canvas
+  let mut path = agg::Path::new();
+  path.move_to(-2000.0, 20.0);
+  for i in 1..=400 {
+    path.line_to(-2000.0 + 10.0 * i as f64, 20.0 + (i % 2) as f64 * 60.0);
+  }
+  let clip = agg::ConvClipPolyline::new(&path, agg::Rectangle::new(-10.0, -10.0, 110.0, 110.0));
+  let visible = clip.xconvert();
+  assert!(visible.len() < 20);
+
+  let full = render(&(&path).stroked(3.0));
+  let clipped = render(&(&clip).stroked(3.0));
+  clipped.to_file("tests/tmp/conv_clip_polyline.png").unwrap();
+  for y in 0..100 {
+    for x in 0..100 {
+      assert_eq!(full.pixf.get((x, y)), clipped.pixf.get((x, y)), "({x},{y})");
+    }
+  }
+}
+
+#[test]
+fn conv_clip_polygon() {
+  let ellipse = agg::Ellipse::new(50.0, 50.0, 80.0, 30.0, 128);
+  let clip = agg::ConvClipPolygon::new(&ellipse, agg::Rectangle::new(-1.0, -1.0, 101.0, 101.0));
+  let full = render(&ellipse);
+  let clipped = render(&clip);
+  clipped.to_file("tests/tmp/conv_clip_polygon.png").unwrap();
+  for y in 0..100 {
+    for x in 0..100 {
+      assert_eq!(full.pixf.get((x, y)), clipped.pixf.get((x, y)), "({x},{y})");
+    }
+  }
+}
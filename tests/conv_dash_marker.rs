@@ -1 +1,78 @@
+extern crate agg;
+use agg::prelude::*;
 
+fn arrowhead() -> agg::Path {
+  let mut head = agg::Path::new();
+  head.move_to(0.0, 0.0);
+  head.line_to(-16.0, 7.0);
+  head.line_to(-12.0, 0.0);
+  head.line_to(-16.0, -7.0);
+  head.close_polygon();
+  head
+}
+
+#[test]
+fn conv_dash_marker() {
+  let mut path = agg::Path::new();
+  path.move_to(20.0, 50.0);
+  path.line_to(180.0, 50.0);
+  path.curve3(190.0, 150.0, 100.0, 150.0);
+
+  let pixf = agg::Pixfmt::<agg::Rgb8>::create(200, 200);
+  let mut ren_base = agg::RenderingBase::new(pixf);
+  ren_base.clear(agg::Rgb8::WHITE);
+  let mut ras: agg::RasterizerScanline = agg::RasterizerScanline::new();
+  let mut ren = agg::RenderingScanlineAASolid::new(&mut ren_base, agg::Rgb8::BLACK);
+
+  // Dashed line, stopping under the arrowheads
+  let mut short = agg::ConvShortenPath::new(&path);
+  short.shorten(12.0);
+  short.shorten_start(12.0);
+  let mut stroke = agg::Stroke::new(short.dashed(&[(10.0, 4.0)]));
+  stroke.width(4.0);
+  ras.add_path(&stroke);
+  agg::render_scanlines(&mut ras, &mut ren);
+
+  // Arrowheads, rendered in red
+  let ends = agg::MarkersTerm::new(&path);
+  assert_eq!(ends.positions().len(), 2);
+  let arrows = agg::ConvMarker::new(&ends, arrowhead());
+  ras.add_path(&arrows);
+  ren.color(agg::Rgb8::from_raw(255, 0, 0));
+  agg::render_scanlines(&mut ras, &mut ren);
+  ren_base.to_file("tests/tmp/conv_dash_marker.png").unwrap();
+
+  let px = |x, y| ren_base.pixf.get((x, y)).into_raw();
+  let red = |(r, g, b): (u8, u8, u8)| r > 200 && g < 100 && b < 100;
+  const BLACK: (u8, u8, u8) = (0, 0, 0);
+  const WHITE: (u8, u8, u8) = (255, 255, 255);
+  // Tips of the arrows at the ends, pointing outwards
+  assert!(red(px(24, 50)));
+  assert_eq!(px(17, 50), WHITE);
+  assert!(red(px(104, 150)));
+  assert_eq!(px(97, 150), WHITE);
+  // Dashes, stopping inside the arrowhead
+  assert!((33..170).any(|x| px(x, 50) == BLACK));
+  assert!((33..170).any(|x| px(x, 50) == WHITE));
+  assert!((20..33).all(|x| px(x, 50) != BLACK));
+}
+
+#[test]
+fn conv_marker_stroke_shorten() {
+  let mut path = agg::Path::new();
+  path.move_to(10.0, 10.0);
+  path.line_to(90.0, 10.0);
+
+  let mut stroke = agg::Stroke::new(&path);
+  stroke.width(4.0);
+  stroke.shorten(15.0);
+  let r = agg::bounding_rect(&stroke).unwrap();
+  assert_eq!((r.x1(), r.x2()), (10.0, 75.0));
+
+  // Markers scaled and flipped to point inwards
+  let mut marker = agg::ConvMarker::new(agg::MarkersTerm::new(&path), arrowhead());
+  marker.transform(agg::Transform::scaling(-0.5, 0.5));
+  let r = agg::bounding_rect(&marker).unwrap();
+  assert_eq!((r.x1(), r.x2()), (2.0, 98.0));
+  assert_eq!((r.y1(), r.y2()), (6.5, 13.5));
+}
@@ -0,0 +1,166 @@
+use agg::prelude::*;
+use agg::{RendererHairline, Transform};
+
+fn canvas() -> RenderingBase<Pixfmt<Rgb8>> {
+  let pix = Pixfmt::<Rgb8>::create(100, 100);
+  let mut ren_base = agg::RenderingBase::new(pix);
+  ren_base.clear(Rgb8::WHITE);
+  ren_base
+}
+
+fn red(ren_base: &RenderingBase<Pixfmt<Rgb8>>, x: i64, y: i64) -> u8 {
+  ren_base.pixf.get((x, y)).into_raw().0
+}
+
+/// Total darkness of the image, in pixels
+fn ink(ren_base: &RenderingBase<Pixfmt<Rgb8>>) -> f64 {
+  let mut sum = 0.0;
+  for y in 0..100 {
+    for x in 0..100 {
+      sum += f64::from(255 - red(ren_base, x, y)) / 255.0;
+    }
+  }
+  sum
+}
+
+#[test]
+fn hairline_one_pixel_at_any_zoom() {
+  for zoom in [1.0, 3.0, 10.0, 37.0] {
+    let mut ren_base = canvas();
+    let mtx = Transform::scaling(zoom, zoom);
+    let mut ren = RendererHairline::new_black(&mut ren_base)
+      .with_transform(mtx)
+      .with_snap(true);
+    ren.line(20.2 / zoom, 30.3 / zoom, 80.7 / zoom, 30.3 / zoom);
+    ren.line(50.4 / zoom, 40.0 / zoom, 50.4 / zoom, 90.0 / zoom);
+
+    // Horizontal line, row 30 from pixel 20 to 80
+    for x in 0..100 {
+      let expected = if (20..=80).contains(&x) { 0 } else { 255 };
+      assert_eq!(red(&ren_base, x, 30), expected, "zoom {zoom} x {x}");
+      assert_eq!(red(&ren_base, x, 29), 255, "zoom {zoom} x {x}");
+      assert_eq!(red(&ren_base, x, 31), 255, "zoom {zoom} x {x}");
+    }
+    // Vertical line, column 50 from pixel 40 to 90
+    for y in 35..100 {
+      let expected = if (40..=90).contains(&y) { 0 } else { 255 };
+      assert_eq!(red(&ren_base, 50, y), expected, "zoom {zoom} y {y}");
+      assert_eq!(red(&ren_base, 49, y), 255, "zoom {zoom} y {y}");
+      assert_eq!(red(&ren_base, 51, y), 255, "zoom {zoom} y {y}");
+    }
+  }
+}
+
+#[test]
+fn hairline_unsnapped_splits_coverage() {
+  let mut ren_base = canvas();
+  let mut ren = RendererHairline::new_black(&mut ren_base);
+  ren.line(20.0, 30.0, 80.0, 30.0);
+  // Centered on the boundary between rows 29 and 30
+  assert_eq!(red(&ren_base, 50, 29), 127);
+  assert_eq!(red(&ren_base, 50, 30), 127);
+  assert_eq!(red(&ren_base, 50, 28), 255);
+  assert_eq!(red(&ren_base, 50, 31), 255);
+}
+
+#[test]
+fn hairline_consistent_under_rotation() {
+  let mut path = agg::Path::new();
+  path.move_to(-30.0, 0.0);
+  path.line_to(30.0, 0.0);
+
+  let mut inks = vec![];
+  for deg in [0.0, 10.0, 30.0, 45.0, 60.0, 90.0, 135.0] {
+    let mut ren_base = canvas();
+    let mtx = Transform::rotation(f64::to_radians(deg)).then_translate(50.3, 50.6);
+    let mut ren = RendererHairline::new_black(&mut ren_base).with_transform(mtx);
+    ren.add_path(&path);
+    inks.push(ink(&ren_base));
+  }
+  // One pixel wide for a length of 60 plus the ends
+  for ink in &inks {
+    assert!((ink - 61.0).abs() < 1.5, "{inks:?}");
+  }
+}
+
+#[test]
+fn hairline_closed_path() {
+  let mut ren_base = canvas();
+  let mut path = agg::Path::new();
+  path.move_to(1.0, 1.0);
+  path.line_to(8.0, 1.0);
+  path.line_to(8.0, 8.0);
+  path.line_to(1.0, 8.0);
+  path.close_polygon();
+  let mtx = Transform::scaling(10.0, 10.0);
+  let mut ren = RendererHairline::new_black(&mut ren_base)
+    .with_transform(mtx)
+    .with_snap(true);
+  ren.add_path(&path);
+  ren_base.to_file("tests/tmp/hairline.png").unwrap();
+
+  for i in 10..=80 {
+    assert_eq!(red(&ren_base, i, 10), 0);
+    assert_eq!(red(&ren_base, i, 80), 0);
+    assert_eq!(red(&ren_base, 10, i), 0);
+    assert_eq!(red(&ren_base, 80, i), 0);
+  }
+  assert_eq!(red(&ren_base, 45, 45), 255);
+  assert_eq!(red(&ren_base, 45, 11), 255);
+}
+
+#[test]
+fn hairline_polyline_matches_segment() {
+  let (x1, y1) = (20.0, 30.0);
+  let (dx, dy) = (f64::cos(0.3), f64::sin(0.3));
+  let draw = |step: f64| {
+    let mut path = agg::Path::new();
+    path.move_to(x1, y1);
+    let mut s = 0.0;
+    while s < 60.0 {
+      s = f64::min(s + step, 60.0);
+      path.line_to(x1 + s * dx, y1 + s * dy);
+    }
+    let mut ren_base = canvas();
+    RendererHairline::new_black(&mut ren_base).add_path(&path);
+    ink(&ren_base)
+  };
+  let single = draw(60.0);
+  assert!((single - 61.0).abs() < 0.5, "{single}");
+  for step in [5.0, 2.0, 1.0, 0.5] {
+    let ink = draw(step);
+    assert!((ink - single).abs() < 0.5, "step {step}: {ink} vs {single}");
+  }
+
+  // Closed circle, one pixel wide along its circumference
+  let mut ren_base = canvas();
+  RendererHairline::new_black(&mut ren_base).add_path(&agg::Ellipse::new(50.0, 50.0, 40.0, 40.0, 0));
+  let ink = ink(&ren_base);
+  let expected = 2.0 * std::f64::consts::PI * 40.0;
+  assert!((ink - expected).abs() < 2.0, "{ink} vs {expected}");
+}
+
+#[test]
+fn hairline_snapped_curve() {
+  // Quarter circle-like curve, only its end points are snapped
+  let curve = |x1: f64, y1: f64, x2: f64, y2: f64| {
+    let mut path = agg::Path::new();
+    path.move_to(x1, y1);
+    path.curve3(80.3, 10.2, x2, y2);
+    path
+  };
+  let mut snapped = canvas();
+  RendererHairline::new_black(&mut snapped)
+    .with_snap(true)
+    .add_path(&curve(10.2, 10.2, 80.3, 80.3));
+  assert_eq!(red(&snapped, 10, 10), 0);
+  assert_eq!(red(&snapped, 80, 80), 0);
+  // Same as the curve from the pixel centers, without stair-steps
+  let mut centered = canvas();
+  RendererHairline::new_black(&mut centered).add_path(&curve(10.5, 10.5, 80.5, 80.5));
+  for y in 0..100 {
+    for x in 0..100 {
+      assert_eq!(red(&snapped, x, y), red(&centered, x, y), "({x},{y})");
+    }
+  }
+}
@@ -0,0 +1,58 @@
+extern crate agg;
+
+fn square(x1: f64, y1: f64, x2: f64, y2: f64) -> agg::Path {
+  let mut path = agg::Path::new();
+  path.move_to(x1, y1);
+  path.line_to(x2, y1);
+  path.line_to(x2, y2);
+  path.line_to(x1, y2);
+  path.close_polygon();
+  path
+}
+
+#[test]
+fn rasterizer_hit_test() {
+  let mut ras: agg::RasterizerScanline = agg::RasterizerScanline::new();
+  ras.add_path(&agg::Ellipse::new(50.0, 50.0, 20.0, 20.0, 64));
+  assert!(ras.hit_test(50, 50));
+  assert!(ras.hit_test(31, 50));
+  assert!(!ras.hit_test(25, 50));
+  assert!(!ras.hit_test(50, 80));
+  assert!(!ras.hit_test(50, -5));
+  // Testing does not consume the rasterizer
+  assert!(ras.hit_test(50, 50));
+
+  let mut ras: agg::RasterizerScanline = agg::RasterizerScanline::new();
+  assert!(!ras.hit_test(0, 0));
+}
+
+#[test]
+fn path_contains() {
+  let mut path = square(0.0, 0.0, 10.0, 10.0);
+  path.vertices.extend(square(2.0, 2.0, 8.0, 8.0).vertices);
+  assert!(agg::contains(&path, 1.0, 5.0, agg::FillingRule::NonZero));
+  assert!(agg::contains(&path, 5.0, 5.0, agg::FillingRule::NonZero));
+  assert!(agg::contains(&path, 1.0, 5.0, agg::FillingRule::EvenOdd));
+  assert!(!agg::contains(&path, 5.0, 5.0, agg::FillingRule::EvenOdd));
+  assert!(!agg::contains(&path, 11.0, 5.0, agg::FillingRule::NonZero));
+  // Open sub-paths are closed
+  let mut open = agg::Path::new();
+  open.move_to(0.0, 0.0);
+  open.line_to(10.0, 0.0);
+  open.line_to(0.0, 10.0);
+  assert!(agg::contains(&open, 2.0, 2.0, agg::FillingRule::NonZero));
+}
+
+#[test]
+fn top_most_path() {
+  let paths = [
+    square(10.0, 10.0, 60.0, 60.0),
+    square(40.0, 40.0, 90.0, 90.0),
+    square(80.0, 10.0, 90.0, 20.0),
+  ];
+  let mut ras = agg::RasterizerScanline::new();
+  assert_eq!(agg::hit_test_all_paths(&mut ras, &paths, 20, 20), Some(0));
+  assert_eq!(agg::hit_test_all_paths(&mut ras, &paths, 50, 50), Some(1));
+  assert_eq!(agg::hit_test_all_paths(&mut ras, &paths, 85, 15), Some(2));
+  assert_eq!(agg::hit_test_all_paths(&mut ras, &paths, 5, 95), None);
+}
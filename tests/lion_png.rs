@@ -2,7 +2,7 @@ extern crate agg;
 use agg::prelude::*;
 
 mod utils;
-use utils::assets::{parse_lion, transform_paths};
+use utils::assets::{load_lion, parse_lion, transform_paths};
 
 mod tests {
   use super::*;
@@ -145,4 +145,26 @@ mod tests {
     assert!(agg::utils::img_diff("tests/tmp/lion_outline.png", "images/lion_outline.png").unwrap());
   }
   // compare -verbose -metric AE lion.ppm ./tests/lion.ppm diff.ppm
+
+  #[test]
+  fn lion_path_storage() {
+    let (w, h) = (400, 400);
+
+    let lion = load_lion(true);
+    let r = lion.bounding_rect().unwrap();
+    let mtx = agg::Transform::new()
+      .then_translate(-(r.x2() - r.x1()) / 2.0, -(r.y2() - r.y1()) / 2.0)
+      .then_translate(w as f64 / 2.0, h as f64 / 2.0);
+
+    let pixf = agg::Pixfmt::<agg::Rgb8>::create(w, h);
+    let mut ren_base = agg::RenderingBase::new(pixf);
+    ren_base.clear(agg::Rgb8::WHITE);
+    let mut ren = agg::RenderingScanlineAASolid::new(&mut ren_base, Rgb8::RED);
+    let mut ras = agg::RasterizerScanline::new();
+
+    agg::render_path_storage(&mut ras, &mut ren, &lion, &mtx);
+
+    ren.to_file("tests/tmp/lion_path_storage.png").unwrap();
+    assert!(agg::utils::img_diff("tests/tmp/lion_path_storage.png", "images/lion_cw_aa.png").unwrap());
+  }
 }
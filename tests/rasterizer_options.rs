@@ -0,0 +1,61 @@
+extern crate agg;
+
+fn square(x1: f64, y1: f64, x2: f64, y2: f64) -> agg::Path {
+  let mut path = agg::Path::new();
+  path.move_to(x1, y1);
+  path.line_to(x2, y1);
+  path.line_to(x2, y2);
+  path.line_to(x1, y2);
+  path.close_polygon();
+  path
+}
+
+#[test]
+fn filling_rule_and_accumulate() {
+  let outer = square(0.0, 0.0, 40.0, 40.0);
+  let inner = square(10.0, 10.0, 30.0, 30.0);
+  let mut ras: agg::RasterizerScanline = agg::RasterizerScanline::new();
+  assert_eq!(ras.get_filling_rule(), agg::FillingRule::NonZero);
+  ras.add_paths(&[&outer, &inner]);
+  assert!(ras.hit_test(20, 20));
+  // Same winding, a hole under even-odd
+  ras.filling_rule(agg::FillingRule::EvenOdd);
+  assert!(!ras.hit_test(20, 20));
+  assert!(ras.hit_test(5, 20));
+
+  // Paths added after testing join the same fill
+  ras.add_path(&outer);
+  assert!(ras.hit_test(20, 20));
+  ras.add_path_accumulate(&inner);
+  assert!(!ras.hit_test(20, 20));
+  // add_path starts over
+  ras.add_path(&inner);
+  assert!(ras.hit_test(20, 20));
+  assert!(!ras.hit_test(5, 20));
+}
+
+#[test]
+fn auto_close() {
+  // Two open triangles, each closed before the next one starts
+  let mut path = agg::Path::new();
+  path.move_to(0.0, 0.0);
+  path.line_to(20.0, 0.0);
+  path.line_to(0.0, 20.0);
+  path.move_to(30.0, 0.0);
+  path.line_to(50.0, 0.0);
+  path.line_to(30.0, 20.0);
+  let mut ras: agg::RasterizerScanline = agg::RasterizerScanline::new();
+  assert!(ras.get_auto_close());
+  ras.add_path(&path);
+  assert!(ras.hit_test(3, 3));
+  assert!(!ras.hit_test(25, 3));
+  assert!(ras.hit_test(33, 3));
+  assert!(!ras.hit_test(60, 3));
+
+  // Left open, the fill runs past the edges of the first triangle
+  ras.reset();
+  ras.auto_close(false);
+  ras.add_path(&path);
+  assert!(!ras.get_auto_close());
+  assert!(ras.hit_test(25, 3));
+}
@@ -0,0 +1,79 @@
+extern crate agg;
+use agg::prelude::*;
+
+fn combine<S1: agg::Scanline, S2: agg::Scanline>(
+  op: agg::SboolOp,
+  sl1: &mut S1,
+  sl2: &mut S2,
+) -> agg::RenderingBase<agg::Pixfmt<agg::Rgb8>> {
+  let pixf = agg::Pixfmt::<agg::Rgb8>::create(100, 100);
+  let mut ren_base = agg::RenderingBase::new(pixf);
+  ren_base.clear(agg::Rgb8::WHITE);
+
+  let mut ras1 = agg::RasterizerScanline::new();
+  ras1.add_path(&agg::Ellipse::new(40.0, 50.0, 30.0, 30.0, 64));
+  let mut ras2 = agg::RasterizerScanline::new();
+  ras2.add_path(&agg::Ellipse::new(60.0, 50.0, 30.0, 30.0, 64));
+
+  let mut ren = agg::RenderingScanlineAASolid::new(&mut ren_base, agg::Rgb8::BLACK);
+  agg::sbool_combine_shapes(op, &mut ras1, &mut ras2, sl1, sl2, &mut ren);
+  ren_base
+}
+
+#[test]
+fn scanline_boolean() {
+  const BLACK: (u8, u8, u8) = (0, 0, 0);
+  const WHITE: (u8, u8, u8) = (255, 255, 255);
+  // Only A, both, only B, neither
+  let cases = [
+    (agg::SboolOp::Or, [BLACK, BLACK, BLACK, WHITE]),
+    (agg::SboolOp::And, [WHITE, BLACK, WHITE, WHITE]),
+    (agg::SboolOp::XorLinear, [BLACK, WHITE, BLACK, WHITE]),
+    (agg::SboolOp::XorSaddle, [BLACK, WHITE, BLACK, WHITE]),
+    (agg::SboolOp::XorAbsDiff, [BLACK, WHITE, BLACK, WHITE]),
+    (agg::SboolOp::AMinusB, [BLACK, WHITE, WHITE, WHITE]),
+    (agg::SboolOp::BMinusA, [WHITE, WHITE, BLACK, WHITE]),
+  ];
+  for (op, expected) in cases {
+    let ren_base = combine(op, &mut agg::ScanlineU8::new(), &mut agg::ScanlineU8::new());
+    ren_base
+      .to_file(format!("tests/tmp/scanline_boolean_{op:?}.png"))
+      .unwrap();
+    let px = |x, y| ren_base.pixf.get((x, y)).into_raw();
+    assert_eq!([px(20, 50), px(50, 50), px(80, 50), px(50, 10)], expected, "{op:?}");
+  }
+}
+
+#[test]
+fn scanline_boolean_packed() {
+  for op in [
+    agg::SboolOp::Or,
+    agg::SboolOp::And,
+    agg::SboolOp::XorSaddle,
+    agg::SboolOp::AMinusB,
+  ] {
+    let unpacked = combine(op, &mut agg::ScanlineU8::new(), &mut agg::ScanlineU8::new());
+    let packed = combine(op, &mut agg::ScanlineP8::new(), &mut agg::ScanlineP8::new());
+    let mixed = combine(op, &mut agg::ScanlineP8::new(), &mut agg::ScanlineU8::new());
+    assert!(unpacked.as_bytes() == packed.as_bytes(), "{op:?}");
+    assert!(unpacked.as_bytes() == mixed.as_bytes(), "{op:?}");
+  }
+}
+
+#[test]
+fn scanline_packed_solid_spans() {
+  use agg::Scanline;
+  let mut sl = agg::ScanlineP8::new();
+  Scanline::reset(&mut sl, 0, 100);
+  Scanline::add_cell(&mut sl, 9, 128);
+  Scanline::add_span(&mut sl, 10, 40, 255);
+  let mut spans = vec![];
+  sl.for_each_span(|x, cover| {
+    let solid = match cover {
+      agg::SpanCover::Solid(_, c) => Some(c),
+      agg::SpanCover::Covers(_) => None,
+    };
+    spans.push((x, cover.len(), solid));
+  });
+  assert_eq!(spans, [(9, 1, None), (10, 40, Some(agg::U8::new(255)))]);
+}
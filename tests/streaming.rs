@@ -0,0 +1,88 @@
+extern crate agg;
+use agg::prelude::*;
+use std::cell::Cell;
+
+/// Streams the vertices of a path and counts them, building the vector is
+///   an error
+struct Streamed<'a> {
+  path: agg::Path,
+  pulled: &'a Cell<usize>,
+}
+
+impl agg::VertexSource for Streamed<'_> {
+  fn xconvert(&self) -> Vec<agg::Vertex<f64>> {
+    panic!("vertices were collected")
+  }
+  fn vertices(&self) -> agg::Vertices<'_> {
+    Box::new(self.path.vertices().inspect(|_| self.pulled.set(self.pulled.get() + 1)))
+  }
+}
+
+/// Row of closed squares, each one a sub-path of 5 vertices
+fn squares(pulled: &Cell<usize>) -> Streamed<'_> {
+  let mut path = agg::Path::new();
+  for i in 0..100 {
+    let x = 10.0 * f64::from(i);
+    path.move_to(x, 0.0);
+    path.line_to(x + 5.0, 0.0);
+    path.line_to(x + 5.0, 5.0);
+    path.line_to(x, 5.0);
+    path.close_polygon();
+  }
+  Streamed { path, pulled }
+}
+
+/// Vertices pulled from the source for the first output vertex
+fn pulled_for_first<VS: agg::VertexSource>(chain: &VS, pulled: &Cell<usize>) -> usize {
+  pulled.set(0);
+  assert!(chain.vertices().next().is_some());
+  let n = pulled.get();
+  // The whole chain is rasterized without collecting the source either
+  let mut ras: agg::RasterizerScanline = agg::RasterizerScanline::new();
+  ras.add_path(chain);
+  n
+}
+
+#[test]
+fn converters_stream_sub_paths() {
+  let pulled = Cell::new(0);
+  let clip_box = agg::Rectangle::new(-10.0, -10.0, 2000.0, 20.0);
+  let mut dash = agg::Dash::new(squares(&pulled));
+  dash.dash_array(&[2.0, 1.0]);
+  let mut contour = agg::Contour::new(squares(&pulled));
+  contour.width(1.0);
+  // The first sub-path and the MoveTo ending it
+  let first = 6;
+  let counts = [
+    ("stroke", pulled_for_first(&squares(&pulled).stroked(1.0), &pulled)),
+    ("dash", pulled_for_first(&(&dash).stroked(1.0), &pulled)),
+    ("contour", pulled_for_first(&contour, &pulled)),
+    (
+      "spline",
+      pulled_for_first(&agg::ConvSpline::new(squares(&pulled)), &pulled),
+    ),
+    (
+      "smooth",
+      pulled_for_first(
+        &agg::ConvSmoothPoly1Curve::new(agg::ConvSmoothPoly1::new(squares(&pulled))),
+        &pulled,
+      ),
+    ),
+    (
+      "clip polygon",
+      pulled_for_first(&agg::ConvClipPolygon::new(squares(&pulled), clip_box), &pulled),
+    ),
+    (
+      "clip polyline",
+      pulled_for_first(&agg::ConvClipPolyline::new(squares(&pulled), clip_box), &pulled),
+    ),
+  ];
+  for (name, n) in counts {
+    assert_eq!(n, first, "{name}");
+  }
+
+  // Transformed stroke of a dashed path
+  let mut stroke = (&dash).stroked(1.0);
+  stroke.transform(agg::Transform::scaling(2.0, 2.0));
+  assert_eq!(pulled_for_first(&stroke, &pulled), first);
+}
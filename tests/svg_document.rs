@@ -0,0 +1,78 @@
+extern crate agg;
+use agg::prelude::*;
+
+const SVG: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200" viewBox="0 0 100 100">
+  <rect x="5" y="5" width="40" height="40" fill="#ff0000"/>
+  <g transform="translate(75 25)" fill="blue">
+    <circle r="15"/>
+    <circle r="5" fill="white"/>
+  </g>
+  <path d="M 55 55 h 40 v 40 h -40 z M 65 65 h 20 v 20 h -20 z" fill="green" fill-rule="evenodd"/>
+  <polygon points="10,55 40,55 40,95 10,95" fill="none" stroke="black" stroke-width="4"/>
+  <rect x="5" y="5" width="40" height="40" fill="white" opacity="0.5"/>
+</svg>
+"##;
+
+#[test]
+fn svg_document() {
+  let doc: agg::SvgDocument = SVG.parse().unwrap();
+  assert_eq!((doc.width, doc.height), (200.0, 200.0));
+  assert_eq!(doc.shapes.len(), 6);
+
+  let pixf = agg::Pixfmt::<agg::Rgb8>::create(200, 200);
+  let mut ren_base = agg::RenderingBase::new(pixf);
+  ren_base.clear(agg::Rgb8::WHITE);
+  doc.render(&mut ren_base);
+  ren_base.to_file("tests/tmp/svg_document.png").unwrap();
+
+  let px = |x, y| ren_base.pixf.get((x, y)).into_raw();
+  // Red square, half covered in white
+  assert_eq!(px(50, 50), (255, 128, 128));
+  // Blue ring around a white center, scaled by the viewBox
+  assert_eq!(px(150, 50), (255, 255, 255));
+  assert_eq!(px(150, 70), (0, 0, 255));
+  assert_eq!(px(150, 85), (255, 255, 255));
+  // Even-odd fill leaves a hole
+  assert_eq!(px(120, 120), (0, 128, 0));
+  assert_eq!(px(150, 150), (255, 255, 255));
+  // Stroked outline, 8 pixels wide after scaling
+  assert_eq!(px(20, 150), (0, 0, 0));
+  assert_eq!(px(23, 150), (0, 0, 0));
+  assert_eq!(px(50, 150), (255, 255, 255));
+}
+
+#[test]
+fn svg_document_transform() {
+  let doc: agg::SvgDocument = r#"<svg width="10" height="10"><rect width="10" height="10" fill="black"/></svg>"#
+    .parse()
+    .unwrap();
+  let pixf = agg::Pixfmt::<agg::Rgb8>::create(40, 40);
+  let mut ren_base = agg::RenderingBase::new(pixf);
+  ren_base.clear(agg::Rgb8::WHITE);
+  doc.render_with_transform(
+    &mut ren_base,
+    &agg::Transform::scaling(2.0, 2.0).then_translate(10.0, 10.0),
+  );
+  assert_eq!(ren_base.pixf.get((5, 5)).into_raw(), (255, 255, 255));
+  assert_eq!(ren_base.pixf.get((20, 20)).into_raw(), (0, 0, 0));
+  assert_eq!(ren_base.pixf.get((35, 35)).into_raw(), (255, 255, 255));
+}
+
+#[test]
+fn svg_document_zero_length() {
+  let doc: agg::SvgDocument = r#"<svg width="40" height="20">
+      <path d="M10 10 L10 10 Z" stroke="black"/>
+      <path d="M30 10 Z" stroke="black" stroke-width="6" stroke-linecap="round"/>
+    </svg>"#
+    .parse()
+    .unwrap();
+  let pixf = agg::Pixfmt::<agg::Rgb8>::create(40, 20);
+  let mut ren_base = agg::RenderingBase::new(pixf);
+  ren_base.clear(agg::Rgb8::WHITE);
+  doc.render(&mut ren_base);
+  // Butt caps draw nothing, round caps a dot
+  assert_eq!(ren_base.pixf.get((10, 10)).into_raw(), (255, 255, 255));
+  assert_eq!(ren_base.pixf.get((30, 10)).into_raw(), (0, 0, 0));
+  assert_eq!(ren_base.pixf.get((30, 15)).into_raw(), (255, 255, 255));
+}
@@ -1 +1,75 @@
+extern crate agg;
+use agg::prelude::*;
 
+fn guide() -> agg::Path {
+  let mut path = agg::Path::new();
+  path.move_to(20.0, 150.0);
+  path.curve4(80.0, 30.0, 220.0, 270.0, 280.0, 150.0);
+  path
+}
+
+fn render<VS: agg::VertexSource>(label: VS) -> agg::RenderingBase<agg::Pixfmt<agg::Rgb8>> {
+  let pixf = agg::Pixfmt::<agg::Rgb8>::create(300, 300);
+  let mut ren_base = agg::RenderingBase::new(pixf);
+  ren_base.clear(agg::Rgb8::WHITE);
+  let mut ras: agg::RasterizerScanline = agg::RasterizerScanline::new();
+  let mut ren = agg::RenderingScanlineAASolid::new(&mut ren_base, agg::Rgb8::BLACK);
+  ras.add_path(&agg::Stroke::new(label));
+  agg::render_scanlines(&mut ras, &mut ren);
+  ren_base
+}
+
+fn text() -> agg::ConvSegmentator<agg::GsvText> {
+  let mut text = agg::GsvText::new();
+  text.size(14.0, 0.0);
+  text.text("Anti-Grain Geometry");
+  text.flip(true);
+  let mut segments = agg::ConvSegmentator::new(text);
+  segments.approximation_scale(0.5);
+  segments
+}
+
+#[test]
+fn trans_curve1() {
+  let guide = guide();
+  let along = agg::TransSinglePath::new(&guide);
+  let label = agg::ConvTransform::new(text(), &along);
+
+  // Every point of the text stays close to the guide
+  let samples = agg::ConvSegmentator::new(&guide).xconvert();
+  for v in label.xconvert() {
+    let d = samples
+      .iter()
+      .map(|p| (p.x - v.x).hypot(p.y - v.y))
+      .fold(f64::MAX, f64::min);
+    assert!(d < 25.0, "({}, {}) is {d} from the guide", v.x, v.y);
+  }
+
+  let ren_base = render(&label);
+  ren_base.to_file("tests/tmp/trans_curve1.png").unwrap();
+  let inked = ren_base.as_bytes().chunks(3).filter(|p| p[0] < 128).count();
+  assert!(inked > 300, "{inked}");
+}
+
+#[test]
+fn trans_curve1_fit_length() {
+  let guide = guide();
+  let text = text();
+  let width = agg::bounding_rect(&text).unwrap().x2();
+
+  // Stretch the text over the whole guide
+  let mut along = agg::TransSinglePath::new(&guide);
+  along.base_length(width);
+  along.preserve_x_scale(false);
+  let r = agg::bounding_rect(&agg::ConvTransform::new(&text, &along)).unwrap();
+  assert!(r.x1() < 30.0 && r.x2() > 270.0, "{r:?}");
+
+  // Without fitting, the text is shorter than the guide
+  let along = agg::TransSinglePath::new(&guide);
+  assert!(width < along.total_length());
+  let r = agg::bounding_rect(&agg::ConvTransform::new(&text, &along)).unwrap();
+  assert!(r.x2() < 270.0, "{r:?}");
+  render(agg::ConvTransform::new(&text, &along))
+    .to_file("tests/tmp/trans_curve1_short.png")
+    .unwrap();
+}
@@ -1 +1,48 @@
+extern crate agg;
+use agg::prelude::*;
 
+#[test]
+fn trans_curve2() {
+  let mut bottom = agg::Path::new();
+  bottom.move_to(20.0, 200.0);
+  bottom.curve4(100.0, 120.0, 200.0, 280.0, 280.0, 200.0);
+  let mut top = agg::Path::new();
+  top.move_to(20.0, 80.0);
+  top.curve4(100.0, 40.0, 200.0, 120.0, 280.0, 60.0);
+
+  let mut text = agg::GsvText::new();
+  text.size(40.0, 0.0);
+  text.text("Warped");
+  let r = agg::bounding_rect(&text).unwrap();
+  let mut segments = agg::ConvSegmentator::new(text);
+  segments.approximation_scale(0.5);
+
+  // Fit the text between the two curves
+  let mut between = agg::TransDoublePath::new(&bottom, &top);
+  between.base_length(r.x2());
+  between.base_height(r.y2());
+  let label = agg::ConvTransform::new(segments, &between);
+
+  // The ends of the text touch the ends of the guides
+  let bounds = agg::bounding_rect(&label).unwrap();
+  assert!((bounds.x1() - 20.0).abs() < 5.0, "{bounds:?}");
+  assert!((bounds.x2() - 280.0).abs() < 5.0, "{bounds:?}");
+  assert!(bounds.y1() > 50.0 && bounds.y2() < 260.0, "{bounds:?}");
+
+  let pixf = agg::Pixfmt::<agg::Rgb8>::create(300, 300);
+  let mut ren_base = agg::RenderingBase::new(pixf);
+  ren_base.clear(agg::Rgb8::WHITE);
+  let mut ras: agg::RasterizerScanline = agg::RasterizerScanline::new();
+  let mut ren = agg::RenderingScanlineAASolid::new(&mut ren_base, agg::Rgb8::BLACK);
+  let mut stroke = agg::Stroke::new(&label);
+  stroke.width(2.0);
+  ras.add_path(&stroke);
+  agg::render_scanlines(&mut ras, &mut ren);
+  ren_base.to_file("tests/tmp/trans_curve2.png").unwrap();
+
+  let inked = ren_base.as_bytes().chunks(3).filter(|p| p[0] < 128).count();
+  assert!(inked > 500, "{inked}");
+  // Nothing outside the band between the guides
+  assert_eq!(ren_base.pixf.get((150, 20)).into_raw(), (255, 255, 255));
+  assert_eq!(ren_base.pixf.get((150, 290)).into_raw(), (255, 255, 255));
+}